@@ -0,0 +1,14 @@
+use std::env;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(rlsf_soft_bit_scan)");
+
+    // ARMv6-M and ARMv8-M Baseline lack a count-leading-zeros instruction.
+    // Their target features (`thumb-mode` without `thumb2`) are only visible
+    // to `cfg` on the nightly toolchain, so look at the target name instead.
+    let target = env::var("TARGET").unwrap_or_default();
+    if target.starts_with("thumbv6m") || target.starts_with("thumbv8m.base") {
+        println!("cargo:rustc-cfg=rlsf_soft_bit_scan");
+    }
+}
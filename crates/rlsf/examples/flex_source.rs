@@ -0,0 +1,122 @@
+//! A minimal implementation of [`FlexSource`] that you can use as a starting
+//! point for your own.
+//!
+//! `ChunkFlexSource` hands out memory from a fixed region (e.g., a memory
+//! area reserved by the linker script on an embedded target; a `Vec` here)
+//! in units of `CHUNK_SIZE` bytes and keeps track of used chunks so that
+//! [`FlexTlsf`] can return memory pools it no longer needs.
+//!
+//! Run it with `cargo run --example flex_source`.
+use rlsf::{FlexSource, FlexTlsf};
+use std::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+
+/// The allocation unit. `FlexTlsf` only requests sizes that are multiples of
+/// `rlsf::GRANULARITY`, but a larger unit keeps the bookkeeping small.
+const CHUNK_SIZE: usize = 1024;
+
+/// The alignment of `Chunk` also serves as `FlexSource::min_align`. Returning
+/// a value at least as large as `rlsf::GRANULARITY` lets `FlexTlsf` use the
+/// whole memory block returned by `alloc`.
+#[repr(C, align(64))]
+struct Chunk([MaybeUninit<u8>; CHUNK_SIZE]);
+
+struct ChunkFlexSource {
+    region: Box<[Chunk]>,
+    /// `used[i]` indicates whether `region[i]` is allocated.
+    used: Vec<bool>,
+}
+
+impl ChunkFlexSource {
+    fn new(num_chunks: usize) -> Self {
+        Self {
+            region: (0..num_chunks)
+                .map(|_| Chunk([MaybeUninit::uninit(); CHUNK_SIZE]))
+                .collect(),
+            used: vec![false; num_chunks],
+        }
+    }
+
+    fn num_used_chunks(&self) -> usize {
+        self.used.iter().filter(|&&x| x).count()
+    }
+}
+
+// Safety: The returned memory blocks are valid and don't overlap with each
+//         other until they are deallocated. `min_align` and
+//         `supports_dealloc` are truthful.
+unsafe impl FlexSource for ChunkFlexSource {
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        let num_chunks = (min_size + CHUNK_SIZE - 1) / CHUNK_SIZE;
+
+        // Find `num_chunks` consecutive free chunks (first fit)
+        let start = (0..self.used.len()).find(
+            |&i| matches!(self.used.get(i..i + num_chunks), Some(s) if s.iter().all(|&x| !x)),
+        )?;
+        self.used[start..start + num_chunks].fill(true);
+
+        let ptr = self.region[start..].as_mut_ptr() as *mut u8;
+        NonNull::new(std::ptr::slice_from_raw_parts_mut(
+            ptr,
+            num_chunks * CHUNK_SIZE,
+        ))
+    }
+
+    unsafe fn dealloc(&mut self, ptr: NonNull<[u8]>) {
+        // `ptr` is exactly what `alloc` returned
+        let start = (ptr.as_ptr() as *mut u8 as usize - self.region.as_ptr() as usize) / CHUNK_SIZE;
+        let num_chunks = (*ptr.as_ptr()).len() / CHUNK_SIZE;
+        self.used[start..start + num_chunks].fill(false);
+    }
+
+    fn supports_dealloc(&self) -> bool {
+        true
+    }
+
+    fn max_total_bytes(&self) -> Option<usize> {
+        Some(self.region.len() * CHUNK_SIZE)
+    }
+
+    fn min_align(&self) -> usize {
+        core::mem::align_of::<Chunk>()
+    }
+}
+
+type TheTlsf = FlexTlsf<ChunkFlexSource, u16, u16, 12, 16>;
+
+fn main() {
+    let mut tlsf = TheTlsf::new(ChunkFlexSource::new(64));
+
+    // `FlexTlsf` acquires memory pools from the source as needed
+    let ptrs: Vec<_> = (1..64)
+        .map(|i| {
+            let layout = Layout::from_size_align(i * 16, 8).unwrap();
+            let ptr = tlsf.allocate(layout).unwrap();
+            unsafe { ptr.as_ptr().write_bytes(i as u8, layout.size()) };
+            (ptr, layout)
+        })
+        .collect();
+    println!(
+        "{} chunks in use after {} allocations",
+        tlsf.source_ref().num_used_chunks(),
+        ptrs.len()
+    );
+    assert_ne!(tlsf.source_ref().num_used_chunks(), 0);
+
+    for (ptr, layout) in ptrs {
+        unsafe { tlsf.deallocate(ptr, layout.align()) };
+    }
+
+    // Memory pools are returned to the source when `FlexTlsf` is reset or
+    // dropped
+    unsafe { tlsf.reset() };
+    println!(
+        "{} chunks in use after reset",
+        tlsf.source_ref().num_used_chunks()
+    );
+    assert_eq!(tlsf.source_ref().num_used_chunks(), 0);
+}
+
+#[test]
+fn test_main() {
+    main();
+}
@@ -0,0 +1,91 @@
+//! Demonstrates the three allocator types provided by this crate and when to
+//! use each of them.
+//!
+//!  - [`Tlsf`] manages memory pools that you insert explicitly. It never
+//!    acquires memory by itself. Use it when all memory is known upfront,
+//!    e.g., a fixed arena on a real-time system.
+//!
+//!  - [`FlexTlsf`] is `Tlsf` plus a [`FlexSource`], from which it acquires
+//!    more memory pools when an allocation doesn't fit. Use it when the
+//!    memory can grow, e.g., chunks handed out by another allocator.
+//!
+//!  - [`GlobalTlsf`] is `FlexTlsf` wired to the platform's memory source
+//!    behind a lock. Use it as `#[global_allocator]`.
+//!
+//! Run it with `cargo run --example three_tiers`.
+use rlsf::{FlexTlsf, GlobalAllocAsFlexSource, Tlsf};
+use std::{alloc::Layout, mem::MaybeUninit};
+
+/// `Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>`: The bitmap types must
+/// have at least `FLLEN` and `SLLEN` bits, respectively. `FLLEN` determines
+/// the maximum block size, which is `GRANULARITY << FLLEN` bytes.
+type FixedTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16>;
+
+/// `FlexTlsf` takes the same parameters as `Tlsf` after the source.
+/// `GlobalAllocAsFlexSource` acquires memory pools from `std::alloc::System`
+/// with 1024-byte alignment.
+type GrowableTlsf = FlexTlsf<GlobalAllocAsFlexSource<std::alloc::System, 1024>, u16, u16, 12, 16>;
+
+/// Tier 1: A fixed memory pool
+fn use_tlsf() {
+    let mut pool = [MaybeUninit::uninit(); 4096];
+    let mut tlsf: FixedTlsf<'_> = Tlsf::new();
+    tlsf.insert_free_block(&mut pool);
+
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let ptr = tlsf.allocate(layout).unwrap();
+    unsafe { tlsf.deallocate(ptr, layout.align()) };
+
+    // The pool can't grow, so a request larger than the pool fails
+    assert!(tlsf
+        .allocate(Layout::from_size_align(8192, 8).unwrap())
+        .is_none());
+    println!("Tlsf: an 8192-byte request doesn't fit in a 4096-byte pool");
+}
+
+/// Tier 2: Memory pools acquired on demand
+fn use_flex_tlsf() {
+    let mut tlsf = GrowableTlsf::new(GlobalAllocAsFlexSource(std::alloc::System));
+
+    // `FlexTlsf` acquires a memory pool large enough for this request
+    let layout = Layout::from_size_align(8192, 8).unwrap();
+    let ptr = tlsf.allocate(layout).unwrap();
+    println!(
+        "FlexTlsf: acquired {} bytes for an 8192-byte request",
+        tlsf.resident_bytes()
+    );
+    assert!(tlsf.resident_bytes() >= layout.size());
+    unsafe { tlsf.deallocate(ptr, layout.align()) };
+
+    // The memory pools are returned to the source when `tlsf` is dropped
+}
+
+/// Tier 3: A global allocator. A program would register it by
+/// `#[global_allocator] static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();`.
+/// It's used explicitly here not to replace the example's allocator.
+#[cfg(unix)]
+fn use_global_tlsf() {
+    use std::alloc::GlobalAlloc;
+
+    static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+
+    let layout = Layout::from_size_align(8192, 8).unwrap();
+    unsafe {
+        let ptr = A.alloc(layout);
+        assert!(!ptr.is_null());
+        A.dealloc(ptr, layout);
+    }
+    println!("GlobalTlsf: served an 8192-byte request from system memory");
+}
+
+fn main() {
+    use_tlsf();
+    use_flex_tlsf();
+    #[cfg(unix)]
+    use_global_tlsf();
+}
+
+#[test]
+fn test_main() {
+    main();
+}
@@ -0,0 +1,93 @@
+#ifndef RLSF_H
+#define RLSF_H
+
+/* Warning: this file is autogenerated by cbindgen. Don't modify this manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * An opaque handle to an allocator created by [`rlsf_create`].
+ *
+ * A handle is not thread-safe. The caller is responsible for serializing
+ * the function calls on a handle.
+ */
+typedef struct Rlsf Rlsf;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create an allocator on the specified memory pool.
+ *
+ * The allocator's control structure is placed at the beginning of
+ * `[pool, pool + len)`, and the rest becomes available for allocation.
+ * Returns a handle to the allocator, or `NULL` if the memory pool is too
+ * small to contain the control structure.
+ *
+ * # Safety
+ *
+ * `[pool, pool + len)` must be valid for reads and writes and must not be
+ * accessed by other means while the handle is in use. The handle must not
+ * be used after the memory pool is released.
+ */
+Rlsf *rlsf_create(void *pool, size_t len);
+
+/**
+ * Allocate a memory block of `size` bytes aligned to `align` bytes.
+ *
+ * Returns the starting address of the allocated memory block, or `NULL` if
+ * the allocation failed or `align` is not a power of two.
+ *
+ * # Safety
+ *
+ * `rlsf` must be a handle returned by [`rlsf_create`].
+ */
+void *rlsf_alloc(Rlsf *rlsf, size_t size, size_t align);
+
+/**
+ * Deallocate a memory block. Does nothing if `ptr` is `NULL`.
+ *
+ * # Safety
+ *
+ * `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+ * or a memory block allocated from `rlsf` and not deallocated yet.
+ */
+void rlsf_free(Rlsf *rlsf, void *ptr);
+
+/**
+ * Shrink or grow a memory block, preserving its contents up to the lesser of
+ * the old and new sizes. Behaves like [`rlsf_alloc`] if `ptr` is `NULL`.
+ *
+ * Returns the new starting address of the memory block, or `NULL` if the
+ * reallocation failed, in which case the original memory block is left
+ * intact.
+ *
+ * # Safety
+ *
+ * `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+ * or a memory block allocated from `rlsf` with the alignment `align` and not
+ * deallocated yet.
+ */
+void *rlsf_realloc(Rlsf *rlsf, void *ptr, size_t size, size_t align);
+
+/**
+ * Get the number of bytes usable in a memory block, which is at least as
+ * large as the requested size. Returns `0` if `ptr` is `NULL`.
+ *
+ * # Safety
+ *
+ * `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+ * or a memory block allocated from `rlsf` and not deallocated yet.
+ */
+size_t rlsf_usable_size(const Rlsf *rlsf, const void *ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RLSF_H */
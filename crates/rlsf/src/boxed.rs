@@ -0,0 +1,201 @@
+//! [`Tlsf`] bundled with a heap-allocated memory pool (requires the `alloc`
+//! Cargo feature)
+use alloc::{boxed::Box, vec};
+use core::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+
+use crate::{int::BinInteger, Tlsf};
+
+/// [`Tlsf`] that owns its memory pool, which is allocated from the global
+/// allocator. Created by [`Tlsf::new_boxed`].
+///
+/// The memory pool is released when `BoxedTlsf` is dropped, so there's no
+/// `'pool` lifetime to manage. The read-only methods of `Tlsf` are available
+/// through [`Deref`](core::ops::Deref).
+///
+/// `BoxedTlsf` doesn't implement `DerefMut` because it would let the inner
+/// `Tlsf` be moved out (e.g., by `core::mem::replace`) and outlive the memory
+/// pool. The allocation methods are provided by `BoxedTlsf` itself instead.
+///
+/// # Examples
+///
+/// ```
+/// use rlsf::{BoxedTlsf, Tlsf};
+/// use std::alloc::Layout;
+///
+/// let mut tlsf: BoxedTlsf<u16, u16, 12, 16> = Tlsf::new_boxed(65536);
+/// let layout = Layout::new::<u64>();
+/// let ptr = tlsf.allocate(layout).unwrap();
+/// unsafe { tlsf.deallocate(ptr, layout.align()) };
+/// ```
+#[derive(Debug)]
+pub struct BoxedTlsf<
+    FLBitmap,
+    SLBitmap,
+    const FLLEN: usize,
+    const SLLEN: usize,
+    const FIFO: bool = false,
+    const BEST_FIT: bool = false,
+    const COALESCE: bool = true,
+> {
+    tlsf: Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>,
+    /// The memory pool, created by `Box::into_raw`. It's kept as a raw
+    /// pointer because `tlsf` points into it.
+    pool: NonNull<[MaybeUninit<u8>]>,
+}
+
+// Safety: `pool` is owned by `BoxedTlsf` and only accessed through `tlsf`,
+//         which is `Send` and `Sync`
+unsafe impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Send for BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+}
+
+unsafe impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Sync for BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+}
+
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    /// Allocate a memory pool of `pool_len` bytes from the global allocator
+    /// and construct a [`BoxedTlsf`] managing it.
+    ///
+    /// Some bytes of the memory pool are consumed by alignment and
+    /// bookkeeping (see [`POOL_OVERHEAD`](crate::POOL_OVERHEAD)). If
+    /// `pool_len` is too small to create a memory pool, the returned
+    /// `BoxedTlsf` will fail all allocation requests.
+    ///
+    /// # Panics
+    ///
+    /// This method panics or aborts if the global allocator fails to
+    /// allocate the memory pool, like [`Box::new`].
+    pub fn new_boxed(
+        pool_len: usize,
+    ) -> BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE> {
+        let pool = Box::into_raw(vec![MaybeUninit::uninit(); pool_len].into_boxed_slice());
+        // Safety: `Box::into_raw` returns a non-null pointer
+        let pool = unsafe { NonNull::new_unchecked(pool) };
+
+        let mut tlsf = Tlsf::new();
+        // Safety: `pool` is owned by the `BoxedTlsf` being created, which
+        //         releases it after `tlsf` is dropped
+        unsafe { tlsf.insert_free_block_ptr(NonNull::new_unchecked(pool.as_ptr() as *mut [u8])) };
+
+        BoxedTlsf { tlsf, pool }
+    }
+}
+
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    /// Attempt to allocate a block of memory. See [`Tlsf::allocate`].
+    #[inline]
+    pub fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.tlsf.allocate(layout)
+    }
+
+    /// Deallocate a previously allocated memory block. See
+    /// [`Tlsf::deallocate`].
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `align`.
+    ///
+    #[inline]
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+        self.tlsf.deallocate(ptr, align)
+    }
+
+    /// Shrink or grow a previously allocated memory block. See
+    /// [`Tlsf::reallocate`].
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `new_layout`.
+    ///
+    #[inline]
+    pub unsafe fn reallocate(
+        &mut self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        self.tlsf.reallocate(ptr, new_layout)
+    }
+
+    /// Get the length of the memory pool passed to [`Tlsf::new_boxed`].
+    #[inline]
+    pub fn pool_len(&self) -> usize {
+        crate::utils::nonnull_slice_len(self.pool)
+    }
+}
+
+impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > core::ops::Deref for BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    type Target = Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.tlsf
+    }
+}
+
+impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Drop for BoxedTlsf<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    fn drop(&mut self) {
+        // Safety: `self.pool` was created by `Box::into_raw`. `self.tlsf`
+        //         doesn't access it in its destructor.
+        drop(unsafe { Box::from_raw(self.pool.as_ptr()) });
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,64 @@
+use std::{alloc::Layout, prelude::v1::*};
+
+use super::*;
+
+type TheBoxedTlsf = BoxedTlsf<u16, u16, 12, 16>;
+
+#[test]
+fn allocate_until_full() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut tlsf: TheBoxedTlsf = Tlsf::new_boxed(65536);
+    assert_eq!(tlsf.pool_len(), 65536);
+
+    let layout = Layout::from_size_align(1024, 8).unwrap();
+    let mut ptrs = Vec::new();
+    while let Some(ptr) = tlsf.allocate(layout) {
+        unsafe { ptr.as_ptr().write_bytes(ptrs.len() as u8, layout.size()) };
+        ptrs.push(ptr);
+    }
+    log::trace!("allocated {} blocks", ptrs.len());
+    assert!(ptrs.len() >= 32);
+
+    for (i, ptr) in ptrs.into_iter().enumerate() {
+        let payload = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+        assert!(payload.iter().all(|&x| x == i as u8));
+        unsafe { tlsf.deallocate(ptr, layout.align()) };
+    }
+
+    // The memory pool is fully free again
+    let layout = Layout::from_size_align(32768, 8).unwrap();
+    let ptr = tlsf.allocate(layout).unwrap();
+    let ptr = unsafe { tlsf.reallocate(ptr, Layout::from_size_align(1024, 8).unwrap()) }.unwrap();
+    unsafe { tlsf.deallocate(ptr, layout.align()) };
+}
+
+#[test]
+fn deref() {
+    let tlsf: TheBoxedTlsf = Tlsf::new_boxed(4096);
+    // Read-only methods of `Tlsf` are available through `Deref`
+    let _ = format!("{:?}", *tlsf);
+}
+
+#[test]
+fn too_small() {
+    let mut tlsf: TheBoxedTlsf = Tlsf::new_boxed(1);
+    assert_eq!(tlsf.pool_len(), 1);
+    assert!(tlsf.allocate(Layout::new::<u8>()).is_none());
+
+    let mut tlsf: TheBoxedTlsf = Tlsf::new_boxed(0);
+    assert!(tlsf.allocate(Layout::new::<u8>()).is_none());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn send_to_another_thread() {
+    let mut tlsf: TheBoxedTlsf = Tlsf::new_boxed(4096);
+    let ptr = tlsf.allocate(Layout::new::<u64>()).unwrap();
+    let addr = ptr.as_ptr() as usize;
+    std::thread::spawn(move || unsafe {
+        tlsf.deallocate(NonNull::new(addr as *mut u8).unwrap(), 8);
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,175 @@
+//! C API for arena allocation (requires the `capi` Cargo feature)
+//!
+//! These functions let C code create [`Tlsf`] instances on caller-provided
+//! memory pools and allocate from them. Unlike `rlsf_override`, they don't
+//! replace the system allocator; each allocator is an explicit handle
+//! ([`Rlsf`]) created by [`rlsf_create`].
+//!
+//! The declarations are available in `include/rlsf.h`, which is generated by
+//! [cbindgen] from this module:
+//!
+//! ```text
+//! cbindgen --config cbindgen.toml --output include/rlsf.h
+//! ```
+//!
+//! To link the functions to a C program, build a `staticlib` crate that
+//! depends on `rlsf` with the `capi` feature and link the resulting library.
+//!
+//! # ABI Stability
+//!
+//! The functions in this module are `extern "C"` and use only C types
+//! (pointers and `size_t`). Their names and signatures are part of the public
+//! API and follow the crate's semantic versioning. [`Rlsf`] is an opaque type
+//! that can only be used through pointers; its layout is not part of the
+//! API.
+//!
+//! [cbindgen]: https://github.com/mozilla/cbindgen
+use core::{
+    alloc::Layout,
+    ffi::c_void,
+    mem,
+    ptr::{self, NonNull},
+};
+
+use crate::Tlsf;
+
+/// The `Tlsf` instantiation used by the C API. Its maximum block size is
+/// `GRANULARITY << 28` bytes (4 GiB on 32-bit targets); larger memory pools
+/// are divided.
+type TheTlsf = Tlsf<'static, u32, u16, 28, 16>;
+
+/// An opaque handle to an allocator created by [`rlsf_create`].
+///
+/// A handle is not thread-safe. The caller is responsible for serializing
+/// the function calls on a handle.
+#[derive(Debug)]
+pub struct Rlsf(TheTlsf);
+
+/// Create an allocator on the specified memory pool.
+///
+/// The allocator's control structure is placed at the beginning of
+/// `[pool, pool + len)`, and the rest becomes available for allocation.
+/// Returns a handle to the allocator, or `NULL` if the memory pool is too
+/// small to contain the control structure.
+///
+/// # Safety
+///
+/// `[pool, pool + len)` must be valid for reads and writes and must not be
+/// accessed by other means while the handle is in use. The handle must not
+/// be used after the memory pool is released.
+#[no_mangle]
+pub unsafe extern "C" fn rlsf_create(pool: *mut c_void, len: usize) -> *mut Rlsf {
+    if pool.is_null() {
+        return ptr::null_mut();
+    }
+
+    let start = pool as usize;
+    let end = start.saturating_add(len);
+
+    // Place `Rlsf` at the beginning of the memory pool
+    let rlsf_start = match start.checked_add(mem::align_of::<Rlsf>() - 1) {
+        Some(x) => x & !(mem::align_of::<Rlsf>() - 1),
+        None => return ptr::null_mut(),
+    };
+    let rlsf_end = match rlsf_start.checked_add(mem::size_of::<Rlsf>()) {
+        Some(x) if x <= end => x,
+        _ => return ptr::null_mut(),
+    };
+
+    let rlsf = rlsf_start as *mut Rlsf;
+    rlsf.write(Rlsf(Tlsf::new()));
+
+    // The rest of the memory pool might be too small to be inserted, in
+    // which case the allocator will fail all allocation requests
+    if let Some(block) = NonNull::new(ptr::slice_from_raw_parts_mut(
+        rlsf_end as *mut u8,
+        end - rlsf_end,
+    )) {
+        // Safety: The memory block is owned by the caller, who handed it
+        //         over to us
+        (*rlsf).0.insert_free_block_ptr(block);
+    }
+
+    rlsf
+}
+
+/// Allocate a memory block of `size` bytes aligned to `align` bytes.
+///
+/// Returns the starting address of the allocated memory block, or `NULL` if
+/// the allocation failed or `align` is not a power of two.
+///
+/// # Safety
+///
+/// `rlsf` must be a handle returned by [`rlsf_create`].
+#[no_mangle]
+pub unsafe extern "C" fn rlsf_alloc(rlsf: *mut Rlsf, size: usize, align: usize) -> *mut c_void {
+    Layout::from_size_align(size, align)
+        .ok()
+        .and_then(|layout| (*rlsf).0.allocate(layout))
+        .map_or(ptr::null_mut(), |ptr| ptr.as_ptr() as *mut c_void)
+}
+
+/// Deallocate a memory block. Does nothing if `ptr` is `NULL`.
+///
+/// # Safety
+///
+/// `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+/// or a memory block allocated from `rlsf` and not deallocated yet.
+#[no_mangle]
+pub unsafe extern "C" fn rlsf_free(rlsf: *mut Rlsf, ptr: *mut c_void) {
+    if let Some(ptr) = NonNull::new(ptr) {
+        (*rlsf).0.deallocate_unknown_align(ptr.cast());
+    }
+}
+
+/// Shrink or grow a memory block, preserving its contents up to the lesser of
+/// the old and new sizes. Behaves like [`rlsf_alloc`] if `ptr` is `NULL`.
+///
+/// Returns the new starting address of the memory block, or `NULL` if the
+/// reallocation failed, in which case the original memory block is left
+/// intact.
+///
+/// # Safety
+///
+/// `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+/// or a memory block allocated from `rlsf` with the alignment `align` and not
+/// deallocated yet.
+#[no_mangle]
+pub unsafe extern "C" fn rlsf_realloc(
+    rlsf: *mut Rlsf,
+    ptr: *mut c_void,
+    size: usize,
+    align: usize,
+) -> *mut c_void {
+    let ptr = if let Some(ptr) = NonNull::new(ptr) {
+        ptr
+    } else {
+        return rlsf_alloc(rlsf, size, align);
+    };
+
+    Layout::from_size_align(size, align)
+        .ok()
+        .and_then(|layout| (*rlsf).0.reallocate(ptr.cast(), layout))
+        .map_or(ptr::null_mut(), |ptr| ptr.as_ptr() as *mut c_void)
+}
+
+/// Get the number of bytes usable in a memory block, which is at least as
+/// large as the requested size. Returns `0` if `ptr` is `NULL`.
+///
+/// # Safety
+///
+/// `rlsf` must be a handle returned by [`rlsf_create`]. `ptr` must be `NULL`
+/// or a memory block allocated from `rlsf` and not deallocated yet.
+#[no_mangle]
+pub unsafe extern "C" fn rlsf_usable_size(rlsf: *const Rlsf, ptr: *const c_void) -> usize {
+    // The memory block's header alone determines the size
+    let _ = rlsf;
+    if let Some(ptr) = NonNull::new(ptr as *mut u8) {
+        TheTlsf::size_of_allocation_unknown_align(ptr)
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,61 @@
+use std::{mem::MaybeUninit, prelude::v1::*, vec};
+
+use super::*;
+
+#[test]
+fn alloc_realloc_free() {
+    let mut pool = vec![MaybeUninit::<u8>::uninit(); 65536];
+    unsafe {
+        let rlsf = rlsf_create(pool.as_mut_ptr() as *mut c_void, pool.len());
+        assert!(!rlsf.is_null());
+
+        assert_eq!(rlsf_usable_size(rlsf, ptr::null()), 0);
+        rlsf_free(rlsf, ptr::null_mut());
+
+        // Invalid alignments
+        assert!(rlsf_alloc(rlsf, 8, 0).is_null());
+        assert!(rlsf_alloc(rlsf, 8, 3).is_null());
+
+        let ptr1 = rlsf_alloc(rlsf, 100, 64) as *mut u8;
+        assert!(!ptr1.is_null());
+        assert_eq!(ptr1 as usize % 64, 0);
+        assert!(rlsf_usable_size(rlsf, ptr1 as *const c_void) >= 100);
+        for i in 0..100 {
+            *ptr1.add(i) = i as u8;
+        }
+
+        let ptr2 = rlsf_realloc(rlsf, ptr1 as *mut c_void, 4000, 64) as *mut u8;
+        assert!(!ptr2.is_null());
+        assert_eq!(ptr2 as usize % 64, 0);
+        for i in 0..100 {
+            assert_eq!(*ptr2.add(i), i as u8);
+        }
+
+        // Too large to fit in the memory pool
+        assert!(rlsf_realloc(rlsf, ptr2 as *mut c_void, 1 << 20, 64).is_null());
+
+        rlsf_free(rlsf, ptr2 as *mut c_void);
+
+        // `realloc(NULL, ...)` allocates
+        let ptr3 = rlsf_realloc(rlsf, ptr::null_mut(), 32768, 1);
+        assert!(!ptr3.is_null());
+        rlsf_free(rlsf, ptr3);
+    }
+}
+
+#[test]
+fn create_too_small() {
+    let mut pool = vec![MaybeUninit::<u8>::uninit(); mem::size_of::<Rlsf>() - 1];
+    unsafe {
+        assert!(rlsf_create(pool.as_mut_ptr() as *mut c_void, pool.len()).is_null());
+        assert!(rlsf_create(ptr::null_mut(), 65536).is_null());
+    }
+
+    // There's no room for allocations, but the allocator can be created
+    let mut pool = vec![MaybeUninit::<u8>::uninit(); mem::size_of::<Rlsf>() * 2];
+    unsafe {
+        let rlsf = rlsf_create(pool.as_mut_ptr() as *mut c_void, pool.len());
+        assert!(!rlsf.is_null());
+        assert!(rlsf_alloc(rlsf, 1 << 16, 1).is_null());
+    }
+}
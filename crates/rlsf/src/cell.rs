@@ -0,0 +1,213 @@
+//! [`Allocator`] implementation for the [`allocator-api2`] crate (requires
+//! the `allocator-api2` Cargo feature)
+//!
+//! [`allocator-api2`]: https://crates.io/crates/allocator-api2
+use allocator_api2::alloc::{AllocError, Allocator};
+use core::{alloc::Layout, cell::RefCell, ptr::NonNull};
+
+use crate::{int::BinInteger, utils::nonnull_slice_from_raw_parts, FlexSource, FlexTlsf, Tlsf};
+
+/// A wrapper of [`Tlsf`] or [`FlexTlsf`] that implements
+/// [`allocator_api2::alloc::Allocator`], letting the collections of
+/// `allocator-api2` (e.g., `allocator_api2::vec::Vec`) allocate memory from
+/// it on stable Rust.
+///
+/// `Allocator`'s methods take `&self`, so the wrapped allocator is kept in a
+/// [`RefCell`] and borrowed mutably for the duration of each method call.
+/// `TlsfCell` is not `Sync`. Use [`GlobalTlsf`](crate::GlobalTlsf) to share an
+/// allocator between threads.
+///
+/// # Examples
+///
+/// ```
+/// use allocator_api2::vec::Vec;
+/// use rlsf::{Tlsf, TlsfCell};
+/// use std::mem::MaybeUninit;
+///
+/// let mut pool = [MaybeUninit::uninit(); 65536];
+/// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+/// tlsf.insert_free_block(&mut pool);
+/// let tlsf = TlsfCell::new(tlsf);
+///
+/// let mut v = Vec::new_in(&tlsf);
+/// v.extend(0..100u32);
+/// assert_eq!(v.iter().sum::<u32>(), 4950);
+/// ```
+#[derive(Debug, Default)]
+pub struct TlsfCell<T>(RefCell<T>);
+
+impl<T> TlsfCell<T> {
+    /// Wrap an allocator.
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self(RefCell::new(inner))
+    }
+
+    /// Unwrap the allocator.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+
+    /// Get a mutable reference to the wrapped allocator, e.g., to insert
+    /// memory pools.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut()
+    }
+}
+
+mod private {
+    use super::*;
+
+    /// The operations of the allocators that can be wrapped by [`TlsfCell`].
+    pub trait CellAllocator {
+        fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>>;
+
+        unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize);
+
+        unsafe fn reallocate(
+            &mut self,
+            ptr: NonNull<u8>,
+            new_layout: Layout,
+        ) -> Option<NonNull<u8>>;
+    }
+
+    impl<
+            'pool,
+            FLBitmap: BinInteger,
+            SLBitmap: BinInteger,
+            const FLLEN: usize,
+            const SLLEN: usize,
+            const FIFO: bool,
+            const BEST_FIT: bool,
+            const COALESCE: bool,
+        > CellAllocator
+        for Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+    {
+        #[inline]
+        fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+            Tlsf::allocate(self, layout)
+        }
+
+        #[inline]
+        unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+            Tlsf::deallocate(self, ptr, align)
+        }
+
+        #[inline]
+        unsafe fn reallocate(
+            &mut self,
+            ptr: NonNull<u8>,
+            new_layout: Layout,
+        ) -> Option<NonNull<u8>> {
+            Tlsf::reallocate(self, ptr, new_layout)
+        }
+    }
+
+    impl<
+            Source: FlexSource,
+            FLBitmap: BinInteger,
+            SLBitmap: BinInteger,
+            const FLLEN: usize,
+            const SLLEN: usize,
+        > CellAllocator for FlexTlsf<Source, FLBitmap, SLBitmap, FLLEN, SLLEN>
+    {
+        #[inline]
+        fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+            FlexTlsf::allocate(self, layout)
+        }
+
+        #[inline]
+        unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+            FlexTlsf::deallocate(self, ptr, align)
+        }
+
+        #[inline]
+        unsafe fn reallocate(
+            &mut self,
+            ptr: NonNull<u8>,
+            new_layout: Layout,
+        ) -> Option<NonNull<u8>> {
+            FlexTlsf::reallocate(self, ptr, new_layout)
+        }
+    }
+}
+
+impl<T: private::CellAllocator> TlsfCell<T> {
+    /// Implements [`Allocator::grow`] and [`Allocator::shrink`].
+    ///
+    /// # Safety
+    ///
+    /// See [`Allocator::grow`].
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let mut inner = self.0.try_borrow_mut().map_err(|_| AllocError)?;
+
+        let new_ptr = if old_layout.align() == new_layout.align() {
+            inner.reallocate(ptr, new_layout)
+        } else {
+            // `reallocate` can't change the alignment
+            let new_ptr = inner.allocate(new_layout);
+            if let Some(new_ptr) = new_ptr {
+                core::ptr::copy_nonoverlapping(
+                    ptr.as_ptr(),
+                    new_ptr.as_ptr(),
+                    old_layout.size().min(new_layout.size()),
+                );
+                inner.deallocate(ptr, old_layout.align());
+            }
+            new_ptr
+        };
+
+        new_ptr
+            .map(|ptr| nonnull_slice_from_raw_parts(ptr, new_layout.size()))
+            .ok_or(AllocError)
+    }
+}
+
+// Safety: The memory blocks are valid until they are deallocated or the
+//         wrapped allocator is dropped, which requires `TlsfCell` to be
+//         moved or dropped. They can be passed to any method of the same
+//         `TlsfCell`.
+unsafe impl<T: private::CellAllocator> Allocator for TlsfCell<T> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let mut inner = self.0.try_borrow_mut().map_err(|_| AllocError)?;
+        let ptr = inner.allocate(layout).ok_or(AllocError)?;
+        Ok(nonnull_slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // This can't fail unless `FlexSource` calls back into `self`
+        self.0.borrow_mut().deallocate(ptr, layout.align());
+    }
+
+    #[inline]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reallocate(ptr, old_layout, new_layout)
+    }
+
+    #[inline]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reallocate(ptr, old_layout, new_layout)
+    }
+}
+
+#[cfg(test)]
+mod tests;
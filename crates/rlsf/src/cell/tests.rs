@@ -0,0 +1,66 @@
+use allocator_api2::{boxed::Box, vec::Vec};
+use std::mem::MaybeUninit;
+
+use super::*;
+use crate::{utils::nonnull_slice_len, GlobalAllocAsFlexSource};
+
+#[repr(align(64))]
+struct Align<T>(T);
+
+type TheTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16>;
+type TheFlexTlsf = FlexTlsf<GlobalAllocAsFlexSource<std::alloc::System, 1024>, u16, u16, 12, 16>;
+
+#[test]
+fn collections() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut pool = Align([MaybeUninit::uninit(); 65536]);
+    let mut tlsf: TheTlsf<'_> = Tlsf::new();
+    tlsf.insert_free_block(&mut pool.0);
+    let tlsf = TlsfCell::new(tlsf);
+
+    // `Vec` grows its buffer by `Allocator::grow`
+    let mut v = Vec::new_in(&tlsf);
+    for i in 0..1000u32 {
+        v.push(i);
+    }
+    v.shrink_to_fit();
+    assert!(v.iter().copied().eq(0..1000));
+
+    let b = Box::new_in([1u64; 16], &tlsf);
+    assert_eq!(b.iter().sum::<u64>(), 16);
+
+    // The pool can't hold this
+    let mut v2 = Vec::<u8, _>::new_in(&tlsf);
+    assert!(v2.try_reserve(65536).is_err());
+}
+
+#[test]
+fn realign() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let tlsf = TlsfCell::new(TheFlexTlsf::new(GlobalAllocAsFlexSource(
+        std::alloc::System,
+    )));
+
+    let layout = Layout::from_size_align(100, 8).unwrap();
+    let ptr = tlsf.allocate(layout).unwrap().cast::<u8>();
+    unsafe { ptr.as_ptr().write_bytes(0x5a, layout.size()) };
+
+    // Grow and change the alignment
+    let new_layout = Layout::from_size_align(1000, 256).unwrap();
+    let new_ptr = unsafe { tlsf.grow(ptr, layout, new_layout) }.unwrap();
+    assert_eq!(nonnull_slice_len(new_ptr), new_layout.size());
+    let new_ptr = new_ptr.cast::<u8>();
+    assert_eq!(new_ptr.as_ptr() as usize % new_layout.align(), 0);
+    let payload = unsafe { std::slice::from_raw_parts(new_ptr.as_ptr(), layout.size()) };
+    assert!(payload.iter().all(|&x| x == 0x5a));
+
+    // Shrink back
+    let ptr = unsafe { tlsf.shrink(new_ptr, new_layout, layout) }.unwrap();
+    let ptr = ptr.cast::<u8>();
+    let payload = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+    assert!(payload.iter().all(|&x| x == 0x5a));
+
+    unsafe { tlsf.deallocate(ptr, layout) };
+}
@@ -0,0 +1,68 @@
+//! Allocation failure injection
+use core::alloc::Layout;
+
+/// Specifies which allocation requests should fail artificially. Used by
+/// [`Tlsf::set_fail_policy`] and its counterparts in other allocator types.
+///
+/// Allocation requests that are failed by the policy return `None` without
+/// touching the memory pools.
+///
+/// [`Tlsf::set_fail_policy`]: crate::Tlsf::set_fail_policy
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "fault-injection")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailPolicy {
+    /// Don't fail any allocation requests. This is the default.
+    Never,
+    /// Fail the `n`-th allocation request made after the policy was set,
+    /// counting from zero.
+    Nth(usize),
+    /// Fail all allocation requests made after the `n`-th allocation request
+    /// (inclusive), counting from zero.
+    AfterNth(usize),
+    /// Fail allocation requests larger than the specified number of bytes.
+    LargerThan(usize),
+}
+
+impl Default for FailPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+/// The state of an allocator's allocation failure injection.
+#[derive(Debug)]
+pub(crate) struct FaultInjector {
+    policy: FailPolicy,
+    /// The number of allocation requests made after `policy` was set
+    num_allocations: usize,
+}
+
+impl FaultInjector {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self {
+            policy: FailPolicy::Never,
+            num_allocations: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_policy(&mut self, policy: FailPolicy) {
+        self.policy = policy;
+        self.num_allocations = 0;
+    }
+
+    /// Record an allocation request and return `true` if it should fail.
+    #[inline]
+    pub(crate) fn should_fail(&mut self, layout: Layout) -> bool {
+        let i = self.num_allocations;
+        self.num_allocations = i.saturating_add(1);
+        match self.policy {
+            FailPolicy::Never => false,
+            FailPolicy::Nth(n) => i == n,
+            FailPolicy::AfterNth(n) => i >= n,
+            FailPolicy::LargerThan(size) => layout.size() > size,
+        }
+    }
+}
@@ -1,20 +1,46 @@
 //! An allocator with flexible backing stores
 use const_default1::ConstDefault;
-use core::{alloc::Layout, debug_assert, ptr::NonNull, unimplemented};
+#[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::{
+    alloc::Layout,
+    debug_assert,
+    mem::{self, MaybeUninit},
+    num::NonZeroUsize,
+    ptr::NonNull,
+    unimplemented,
+};
 
 use super::{
     int::BinInteger,
     utils::{
         nonnull_slice_end, nonnull_slice_from_raw_parts, nonnull_slice_len, nonnull_slice_start,
     },
-    Tlsf, GRANULARITY,
+    Tlsf, GRANULARITY, POOL_OVERHEAD,
 };
 
 /// The trait for dynamic storage allocators that can back [`FlexTlsf`].
+///
+/// See [`examples/flex_source.rs`] for an example implementation.
+///
+/// [`examples/flex_source.rs`]: https://github.com/yvt/rlsf/blob/main/crates/rlsf/examples/flex_source.rs
+///
+/// # Safety
+///
+/// Memory blocks returned by [`Self::alloc`] and grown by
+/// [`Self::realloc_inplace_grow`] must be valid for reads and writes, and they
+/// must stay so until they are deallocated by [`Self::dealloc`] or `self` is
+/// dropped. They must not overlap with each other.
+///
+/// The returned values of the `supports_*`, `is_contiguous_growable`, and
+/// `min_align` methods must be truthful.
 pub unsafe trait FlexSource {
     /// Allocate a memory block of the requested minimum size.
     ///
-    /// Returns the address range of the allocated memory block.
+    /// Returns the address range of the allocated memory block, which must
+    /// be at least `min_size` bytes long. [`FlexTlsf`] treats a shorter
+    /// memory block as an allocation failure and returns it by
+    /// [`Self::dealloc`] (if supported).
     ///
     /// # Safety
     ///
@@ -30,6 +56,13 @@ pub unsafe trait FlexSource {
     /// the final allocation size (which must be greater than or equal to
     /// `min_new_len`) on success.
     ///
+    /// The final allocation size must exceed `min_new_len` by a multiple of
+    /// [`GRANULARITY`], which can be zero. [`FlexTlsf`] chooses `min_new_len` so
+    /// that the memory pool in the allocation ends at a
+    /// `GRANULARITY`-aligned address, and this rule keeps it that way after
+    /// the memory pool is extended. `FlexTlsf` checks this rule by a debug
+    /// assertion.
+    ///
     /// # Safety
     ///
     /// `ptr` must be an existing allocation made by this
@@ -49,6 +82,11 @@ pub unsafe trait FlexSource {
     /// # Safety
     ///
     /// `ptr` must denote an existing allocation made by this allocator.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics. [`FlexTlsf`] never calls this
+    /// method unless [`Self::supports_dealloc`] returns `true`.
     #[inline]
     unsafe fn dealloc(&mut self, ptr: NonNull<[u8]>) {
         let _ = ptr;
@@ -97,6 +135,102 @@ pub unsafe trait FlexSource {
         false
     }
 
+    /// Check if this allocator is permanently unable to provide more memory,
+    /// i.e., further calls to `alloc` and `realloc_inplace_grow` are certain
+    /// to fail regardless of the requested size.
+    ///
+    /// This distinguishes a source that has run out of memory for good (e.g.,
+    /// a fixed-size arena that has been handed out entirely) from one that
+    /// failed transiently (e.g., `mmap` failing under memory pressure).
+    /// [`FlexTlsf`] doesn't call `alloc` and `realloc_inplace_grow` while
+    /// this method returns `true`, and the application can call it through
+    /// [`FlexTlsf::source_ref`] to decide whether retrying a failed allocation
+    /// could help.
+    ///
+    /// Unlike the other query methods, the returned value may change over
+    /// time.
+    #[inline]
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+
+    /// Get the maximum total length of the memory blocks that this allocator
+    /// can have allocated at the same time, or `None` if it's unbounded or
+    /// unknown.
+    ///
+    /// For example, an allocator that hands out a fixed-size arena returns
+    /// the arena's size, while an allocator backed by `mmap` returns `None`.
+    /// [`FlexTlsf`] doesn't call `alloc` and `realloc_inplace_grow` when they
+    /// would make the total exceed this value, and
+    /// [`FlexTlsf::max_resident_bytes`] reports it as a part of an upper bound
+    /// of the memory available to `FlexTlsf`.
+    ///
+    /// The returned value must be constant for a particular instance of `Self`.
+    #[inline]
+    fn max_total_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Commit the physical memory backing the specified allocation so that
+    /// accessing it for the first time doesn't cause a page fault. This must
+    /// not modify the allocation's contents, which may be in use.
+    ///
+    /// This method is called by [`FlexTlsf::prefault`]. The default
+    /// implementation does nothing, which is appropriate for memory that is
+    /// not demand-paged (e.g., a static array on a bare-metal target).
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote an existing allocation made by this allocator.
+    #[inline]
+    unsafe fn prefault(&mut self, ptr: NonNull<[u8]>) {
+        let _ = ptr;
+    }
+
+    /// Release the physical memory backing the specified part of an
+    /// allocation, whose contents are no longer needed. The part must stay
+    /// valid for reads and writes, but its contents may change arbitrarily
+    /// (e.g., `madvise(MADV_FREE)`).
+    ///
+    /// [`FlexTlsf::reallocate`] calls this method with the memory freed by
+    /// shrinking an allocation if [`Self::supports_discard`] returns `true`
+    /// and the shrink freed at least [`Self::discard_threshold`] bytes.
+    /// The default implementation does nothing.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote a part of an existing allocation made by this
+    /// allocator.
+    #[inline]
+    unsafe fn discard(&mut self, ptr: NonNull<[u8]>) {
+        let _ = ptr;
+    }
+
+    /// Check if this allocator implements [`Self::discard`].
+    ///
+    /// If this method returns `false`, [`FlexTlsf`] will not call `discard`,
+    /// saving the work to find the memory to discard.
+    ///
+    /// The returned value must be constant for a particular instance of `Self`.
+    #[inline]
+    fn supports_discard(&self) -> bool {
+        false
+    }
+
+    /// Get the minimum number of bytes a reallocation must free for
+    /// [`FlexTlsf::reallocate`] to call [`Self::discard`].
+    ///
+    /// Discarding memory usually costs a system call, and the discarded pages
+    /// are likely to be faulted in again if the allocation grows back soon.
+    /// The default implementation returns 64 KiB, which is several pages on
+    /// most systems.
+    ///
+    /// The returned value must be constant for a particular instance of `Self`.
+    #[inline]
+    fn discard_threshold(&self) -> usize {
+        1 << 16
+    }
+
     /// Get the minimum alignment of allocations made by this allocator.
     /// [`FlexTlsf`] may be less efficient if this method returns a value
     /// less than [`GRANULARITY`].
@@ -177,6 +311,226 @@ unsafe impl<T: core::alloc::GlobalAlloc, const ALIGN: usize> FlexSource
     }
 }
 
+/// A [`FlexSource`] that acquires memory by growing the WebAssembly linear
+/// memory (`memory.grow`).
+///
+/// Memory blocks are allocated in units of 64 KiB pages. A memory pool can be
+/// grown in place as long as no one else has grown the linear memory since
+/// the memory pool was allocated. Memory is never returned to the system
+/// because the linear memory can't shrink.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use rlsf::{FlexTlsf, WasmMemoryFlexSource};
+///
+/// let mut tlsf: FlexTlsf<WasmMemoryFlexSource, u32, u32, 20, 32> =
+///     FlexTlsf::new(WasmMemoryFlexSource::new());
+/// ```
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(target_arch = "wasm32")))]
+#[derive(Default, Debug, Copy, Clone)]
+pub struct WasmMemoryFlexSource(());
+
+#[cfg(target_arch = "wasm32")]
+impl WasmMemoryFlexSource {
+    /// The index of the linear memory to grow
+    const MEM: u32 = 0;
+    const PAGE_SIZE: usize = 1 << 16;
+
+    /// Construct a `WasmMemoryFlexSource`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ConstDefault for WasmMemoryFlexSource {
+    const DEFAULT: Self = Self::new();
+}
+
+#[cfg(target_arch = "wasm32")]
+unsafe impl FlexSource for WasmMemoryFlexSource {
+    #[inline]
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        use core::arch::wasm32;
+
+        let num_pages = min_size.checked_add(Self::PAGE_SIZE - 1)? / Self::PAGE_SIZE;
+        let num_bytes = num_pages * Self::PAGE_SIZE;
+
+        let old_num_pages = wasm32::memory_grow(Self::MEM, num_pages);
+        if old_num_pages == usize::MAX {
+            // failure
+            return None;
+        }
+
+        Some(nonnull_slice_from_raw_parts(
+            // Assume the old memory size is non-zero. It's likely to be
+            // true because otherwise there wouldn't be even a stack space.
+            NonNull::new((old_num_pages * Self::PAGE_SIZE) as *mut u8)
+                .unwrap_or_else(|| wasm32::unreachable()),
+            num_bytes,
+        ))
+    }
+
+    #[inline]
+    unsafe fn realloc_inplace_grow(
+        &mut self,
+        ptr: NonNull<[u8]>,
+        min_new_len: usize,
+    ) -> Option<usize> {
+        use core::arch::wasm32;
+
+        let ptr_end_page = nonnull_slice_end(ptr) as usize / Self::PAGE_SIZE;
+        if ptr_end_page != wasm32::memory_size(Self::MEM) {
+            // We can't grow the memory from `ptr`; someone else has grown it
+            // past `ptr`, and we don't own that part
+            return None;
+        }
+
+        let new_num_pages = min_new_len.checked_add(Self::PAGE_SIZE - 1)? / Self::PAGE_SIZE;
+        let new_len = new_num_pages * Self::PAGE_SIZE;
+        let ptr_start_page = nonnull_slice_start(ptr).as_ptr() as usize / Self::PAGE_SIZE;
+
+        if wasm32::memory_grow(Self::MEM, ptr_start_page + new_num_pages - ptr_end_page)
+            == usize::MAX
+        {
+            // failure
+            None
+        } else {
+            Some(new_len)
+        }
+    }
+
+    #[inline]
+    fn supports_realloc_inplace_grow(&self) -> bool {
+        true
+    }
+
+    // `is_contiguous_growable` can't return `true` because other code may
+    // issue `memory.grow` without `unsafe` blocks.
+
+    #[inline]
+    fn min_align(&self) -> usize {
+        Self::PAGE_SIZE
+    }
+}
+
+/// Counts of the times [`FlexTlsf`] acquired memory from its [`FlexSource`]
+/// to grow the memory pools. Returned by [`FlexTlsf::grow_stats`].
+///
+/// Frequent growth after the application's start-up indicates that the
+/// initial memory pools are undersized.
+#[cfg(feature = "stats")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GrowStats {
+    /// The number of times an existing memory pool was extended by
+    /// [`FlexSource::realloc_inplace_grow`].
+    pub in_place: u64,
+    /// The number of times a new memory pool was created from a memory block
+    /// returned by [`FlexSource::alloc`].
+    pub fresh: u64,
+}
+
+#[cfg(feature = "stats")]
+impl GrowStats {
+    /// Get the total number of times the memory pools were grown.
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.in_place.wrapping_add(self.fresh)
+    }
+}
+
+/// A copy of [`FlexTlsf`]'s counters that can be read without access to the
+/// `FlexTlsf`. Registered by [`FlexTlsf::set_stats_mirror`].
+///
+/// The counters are updated by the `FlexTlsf` (i.e., under whatever lock
+/// protects it) every time they change and can be read concurrently by
+/// other threads, e.g., for monitoring, without contending the lock. Each
+/// counter is read individually, so a set of readings might mix values from
+/// before and after an update.
+///
+/// # Examples
+///
+/// ```
+/// use rlsf::{AtomicFlexStats, FlexTlsf, GlobalAllocAsFlexSource};
+/// use std::alloc::{Layout, System};
+///
+/// static STATS: AtomicFlexStats = AtomicFlexStats::new();
+///
+/// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+/// tlsf.set_stats_mirror(Some(&STATS));
+///
+/// tlsf.allocate(Layout::new::<u64>()).unwrap();
+///
+/// // This can be done on another thread
+/// assert_eq!(STATS.resident_bytes(), tlsf.resident_bytes());
+/// assert_eq!(STATS.grow_stats().fresh, 1);
+/// ```
+#[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+#[derive(Debug, Default)]
+pub struct AtomicFlexStats {
+    resident_bytes: AtomicUsize,
+    usable_bytes: AtomicUsize,
+    grow_in_place: AtomicUsize,
+    grow_fresh: AtomicUsize,
+}
+
+#[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+impl AtomicFlexStats {
+    /// Construct an `AtomicFlexStats` with all counters set to zero.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            resident_bytes: AtomicUsize::new(0),
+            usable_bytes: AtomicUsize::new(0),
+            grow_in_place: AtomicUsize::new(0),
+            grow_fresh: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the last published value of [`FlexTlsf::resident_bytes`].
+    #[inline]
+    pub fn resident_bytes(&self) -> usize {
+        self.resident_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Get the last published value of [`FlexTlsf::usable_bytes`].
+    #[inline]
+    pub fn usable_bytes(&self) -> usize {
+        self.usable_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Get the last published value of [`FlexTlsf::grow_stats`]. The counts
+    /// wrap around at `usize::MAX`.
+    #[inline]
+    pub fn grow_stats(&self) -> GrowStats {
+        GrowStats {
+            in_place: self.grow_in_place.load(Ordering::Relaxed) as u64,
+            fresh: self.grow_fresh.load(Ordering::Relaxed) as u64,
+        }
+    }
+
+    fn store<Source: FlexSource, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize>(
+        &self,
+        tlsf: &FlexTlsf<Source, FLBitmap, SLBitmap, FLLEN, SLLEN>,
+    ) {
+        self.resident_bytes
+            .store(tlsf.total_alloc_len, Ordering::Relaxed);
+        self.usable_bytes
+            .store(tlsf.total_pool_len, Ordering::Relaxed);
+        self.grow_in_place
+            .store(tlsf.grow_stats.in_place as usize, Ordering::Relaxed);
+        self.grow_fresh
+            .store(tlsf.grow_stats.fresh as usize, Ordering::Relaxed);
+    }
+}
+
 /// A wrapper of [`Tlsf`] that automatically acquires fresh memory pools from
 /// [`FlexSource`].
 #[derive(Debug)]
@@ -184,6 +538,21 @@ pub struct FlexTlsf<Source: FlexSource, FLBitmap, SLBitmap, const FLLEN: usize,
 {
     /// The lastly created memory pool.
     growable_pool: Option<Pool>,
+    /// The total length of the allocations made by `source`.
+    total_alloc_len: usize,
+    /// The total length of the memory pools in `tlsf`.
+    total_pool_len: usize,
+    /// The maximum value of `total_alloc_len` set by
+    /// [`Self::set_pool_limit`].
+    pool_limit: Option<usize>,
+    /// The reserve created by [`Self::reserve_emergency_pool`].
+    emergency_pool: Option<EmergencyPool<FLBitmap, SLBitmap, FLLEN, SLLEN>>,
+    /// The counters reported by [`Self::grow_stats`]
+    #[cfg(feature = "stats")]
+    grow_stats: GrowStats,
+    /// The destination set by [`Self::set_stats_mirror`]
+    #[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+    stats_mirror: Option<&'static AtomicFlexStats>,
     source: Source,
     tlsf: Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>,
 }
@@ -203,8 +572,62 @@ struct Pool {
 unsafe impl Send for Pool {}
 unsafe impl Sync for Pool {}
 
-/// Pool footer stored at the end of each pool. It's only used when
-/// supports_dealloc() == true`.
+/// A memory pool set aside by [`FlexTlsf::reserve_emergency_pool`].
+///
+/// The pool is managed by a separate `Tlsf`, which is placed at the beginning
+/// of the memory allocation containing the pool so that `FlexTlsf` doesn't
+/// grow by the size of a whole `Tlsf`.
+#[derive(Debug)]
+struct EmergencyPool<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> {
+    /// The memory allocation acquired from `FlexSource`.
+    alloc: NonNull<[u8]>,
+    /// The memory block inserted to `tlsf` as a memory pool. This is the part
+    /// of `alloc` following `*tlsf`.
+    pool: NonNull<[u8]>,
+    /// The length of the memory pool created in `pool`.
+    pool_len: usize,
+    tlsf: NonNull<Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>>,
+}
+
+// Safety: `EmergencyPool` owns `*tlsf`, which is `Send + Sync`
+unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Send
+    for EmergencyPool<FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+}
+unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Sync
+    for EmergencyPool<FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+}
+
+impl<FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize>
+    EmergencyPool<FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    /// Check if `ptr` points to somewhere in the memory pool.
+    #[inline]
+    fn contains(&self, ptr: NonNull<u8>) -> bool {
+        (ptr.as_ptr() as usize).wrapping_sub(nonnull_slice_start(self.pool).as_ptr() as usize)
+            < nonnull_slice_len(self.pool)
+    }
+
+    /// Discard all allocations and recreate the memory pool.
+    ///
+    /// # Safety
+    ///
+    /// The memory blocks previously allocated from the pool must not be used
+    /// after calling this method.
+    unsafe fn reset(&mut self) {
+        let tlsf = &mut *self.tlsf.as_ptr();
+        tlsf.forget_pools();
+        // Safety: `self.pool` is owned by us, and no memory blocks in it are
+        //         in use
+        let pool_len = tlsf.insert_free_block_ptr(self.pool);
+        debug_assert_eq!(pool_len.map_or(0, NonZeroUsize::get), self.pool_len);
+    }
+}
+
+/// Pool footer stored at the end of each pool. It links all allocations made
+/// by `FlexSource` so that they can be enumerated by [`Drop`] and
+/// [`FlexTlsf::reset`].
 ///
 /// The footer is stored in the sentinel block's unused space or any padding
 /// present at the end of each pool. This is why `PoolFtr` can't be larger than
@@ -220,6 +643,12 @@ const _: () = if core::mem::size_of::<PoolFtr>() != GRANULARITY / 2 {
     panic!("bad `PoolFtr` size");
 };
 
+// `PoolFtr` must fit in the sentinel block's unused space so that it doesn't
+// add to `POOL_OVERHEAD`
+const _: () = if core::mem::size_of::<PoolFtr>() + GRANULARITY / 2 > POOL_OVERHEAD {
+    panic!("`PoolFtr` doesn't fit in the sentinel block");
+};
+
 impl PoolFtr {
     /// Get a pointer to `PoolFtr` for a given allocation.
     #[inline]
@@ -278,6 +707,17 @@ impl<
             source,
             tlsf: Tlsf::new(),
             growable_pool: None,
+            total_alloc_len: 0,
+            total_pool_len: 0,
+            pool_limit: None,
+            emergency_pool: None,
+            #[cfg(feature = "stats")]
+            grow_stats: GrowStats {
+                in_place: 0,
+                fresh: 0,
+            },
+            #[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+            stats_mirror: None,
         }
     }
 
@@ -298,6 +738,443 @@ impl<
         &mut self.source
     }
 
+    /// Create a new memory pool at the location specified by a slice,
+    /// bypassing `Source`. See [`Tlsf::insert_free_block`].
+    #[inline]
+    pub(crate) fn insert_free_block(&mut self, block: &'static mut [MaybeUninit<u8>]) {
+        // Safety: `block` is `'static` and owned by us
+        let pool_len = unsafe {
+            self.tlsf.insert_free_block_ptr(NonNull::new_unchecked(
+                block as *mut [MaybeUninit<u8>] as *mut [u8],
+            ))
+        };
+        self.total_pool_len = self
+            .total_pool_len
+            .wrapping_add(pool_len.map_or(0, NonZeroUsize::get));
+        self.publish_stats();
+    }
+
+    /// Limit the total number of bytes that `self` may acquire from the
+    /// `Source`. `None` means no limit, which is the default.
+    ///
+    /// Once the limit is reached, [`Self::allocate`] and [`Self::reallocate`]
+    /// fail instead of requesting more memory from the `Source`. The limit is
+    /// checked against the sizes requested from the `Source`; if the `Source`
+    /// returns larger memory blocks than requested, the total may slightly
+    /// exceed the limit. Memory acquired before the limit is lowered is not
+    /// released.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    /// tlsf.set_pool_limit(Some(4096));
+    ///
+    /// assert!(tlsf.allocate(Layout::from_size_align(8192, 1).unwrap()).is_none());
+    /// assert!(tlsf.resident_bytes() <= 4096);
+    /// ```
+    #[inline]
+    pub fn set_pool_limit(&mut self, max_total_bytes: Option<usize>) {
+        self.pool_limit = max_total_bytes;
+    }
+
+    /// Get the limit set by [`Self::set_pool_limit`].
+    #[inline]
+    pub fn pool_limit(&self) -> Option<usize> {
+        self.pool_limit
+    }
+
+    /// Get the total number of bytes that `self` has acquired from the
+    /// `Source`, i.e., the sum of the lengths of the memory blocks returned
+    /// by the `Source`.
+    ///
+    /// This is the amount of memory committed to `self` and includes the bytes
+    /// that could not be made into memory pools due to alignment.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    #[inline]
+    pub fn resident_bytes(&self) -> usize {
+        self.total_alloc_len
+    }
+
+    /// Get the maximum value that [`Self::resident_bytes`] can reach, which
+    /// is the smaller of [`Self::pool_limit`] and
+    /// [`FlexSource::max_total_bytes`]. Returns `None` if neither of them is
+    /// specified.
+    ///
+    /// This is useful for capacity planning. Note that the `Source` may return
+    /// larger memory blocks than requested, in which case
+    /// `Self::resident_bytes` may slightly exceed `Self::pool_limit`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::System;
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    /// assert_eq!(tlsf.max_resident_bytes(), None);
+    ///
+    /// tlsf.set_pool_limit(Some(4096));
+    /// assert_eq!(tlsf.max_resident_bytes(), Some(4096));
+    /// ```
+    #[inline]
+    pub fn max_resident_bytes(&self) -> Option<usize> {
+        match (self.pool_limit, self.source.max_total_bytes()) {
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (x, None) | (None, x) => x,
+        }
+    }
+
+    /// Get the total length of the memory pools managed by `self`.
+    ///
+    /// This excludes the bytes that were discarded when aligning the memory
+    /// blocks returned by the `Source` but includes the memory block headers
+    /// and the sentinel blocks placed in the memory pools.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    #[inline]
+    pub fn usable_bytes(&self) -> usize {
+        self.total_pool_len
+    }
+
+    /// Get the number of times `self` acquired memory from the `Source` to
+    /// grow the memory pools for allocations.
+    ///
+    /// The counts accumulate over the lifetime of `self` and are not reset by
+    /// [`Self::reset`]. [`Self::reserve_emergency_pool`] is not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    /// assert_eq!(tlsf.grow_stats().total(), 0);
+    ///
+    /// tlsf.allocate(Layout::new::<u64>()).unwrap();
+    /// assert_eq!(tlsf.grow_stats().fresh, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    #[inline]
+    pub fn grow_stats(&self) -> GrowStats {
+        self.grow_stats
+    }
+
+    /// Make `self` publish [`Self::resident_bytes`], [`Self::usable_bytes`],
+    /// and [`Self::grow_stats`] to `mirror` whenever they change, so that
+    /// they can be read without access to `self`. The current values are
+    /// published immediately. `None` stops publishing, which is the default.
+    ///
+    /// The values are not updated when `self` is dropped.
+    ///
+    /// See [`AtomicFlexStats`] for an example.
+    #[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    pub fn set_stats_mirror(&mut self, mirror: Option<&'static AtomicFlexStats>) {
+        self.stats_mirror = mirror;
+        self.publish_stats();
+    }
+
+    /// Copy the counters to the [stats mirror](Self::set_stats_mirror) if
+    /// any.
+    #[inline]
+    fn publish_stats(&self) {
+        #[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+        if let Some(mirror) = self.stats_mirror {
+            mirror.store(self);
+        }
+    }
+
+    /// Write a human-readable summary of the memory pools' state to `w`.
+    ///
+    /// This is [`Tlsf::write_report`] preceded by a line reporting
+    /// [`Self::resident_bytes`] and, if it's known, [`Self::max_resident_bytes`].
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_free_blocks)`).
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    pub fn write_report(&self, w: &mut impl core::fmt::Write) -> core::fmt::Result {
+        if let Some(max_resident_bytes) = self.max_resident_bytes() {
+            writeln!(
+                w,
+                "resident: {} bytes (max {} bytes)",
+                self.total_alloc_len, max_resident_bytes
+            )?;
+        } else {
+            writeln!(w, "resident: {} bytes", self.total_alloc_len)?;
+        }
+        self.tlsf.write_report(w)
+    }
+
+    /// Check if there are no live allocations, including the ones made from
+    /// the emergency reserve. See [`Tlsf::is_empty`].
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    pub fn is_empty(&self) -> bool {
+        self.tlsf.is_empty()
+            && self.emergency_pool.as_ref().map_or(true, |emergency_pool| {
+                // Safety: `*tlsf` is owned by `self`
+                unsafe { emergency_pool.tlsf.as_ref() }.is_empty()
+            })
+    }
+
+    /// Commit the physical memory backing all memory blocks acquired from the
+    /// `Source` so far, including the emergency reserve, by calling
+    /// [`FlexSource::prefault`] for each of them.
+    ///
+    /// On a demand-paged system, the first write to each memory page causes
+    /// a page fault, adding jitter to the allocations that happen to touch
+    /// fresh pages. Calling this method before entering a time-critical
+    /// section moves the cost out of it. Memory blocks acquired from the
+    /// `Source` later are not affected.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_memory_pools)`),
+    /// not counting the time taken by `Source`'s methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    ///
+    /// // Acquire enough memory for the time-critical section
+    /// let layout = Layout::from_size_align(16384, 8).unwrap();
+    /// let ptr = tlsf.allocate(layout).unwrap();
+    /// unsafe { tlsf.deallocate(ptr, layout.align()) };
+    ///
+    /// tlsf.prefault();
+    /// ```
+    pub fn prefault(&mut self) {
+        let align = self.source.min_align();
+        let mut cur_alloc_or_none = self
+            .growable_pool
+            .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
+
+        while let Some(cur_alloc) = cur_alloc_or_none {
+            // Safety: We control the referenced pool footer
+            let cur_ftr = unsafe { *PoolFtr::get_for_alloc(cur_alloc, align) };
+
+            // Safety: It's an allocation we allocated from `self.source`
+            unsafe { self.source.prefault(cur_alloc) };
+
+            cur_alloc_or_none = cur_ftr.prev_alloc;
+        }
+
+        if let Some(emergency_pool) = &self.emergency_pool {
+            // Safety: It's an allocation we allocated from `self.source`
+            unsafe { self.source.prefault(emergency_pool.alloc) };
+        }
+    }
+
+    /// Set the policy for failing allocation requests artificially. See
+    /// [`Tlsf::set_fail_policy`].
+    ///
+    /// The policy applies to [`Self::allocate`]. Allocation requests failed by
+    /// the policy don't acquire memory from the `Source`.
+    #[cfg(feature = "fault-injection")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "fault-injection")))]
+    #[inline]
+    pub fn set_fail_policy(&mut self, policy: crate::FailPolicy) {
+        self.tlsf.set_fail_policy(policy);
+    }
+
+    /// Check if acquiring `additional_len` more bytes from the `Source` is
+    /// permitted by [`Self::pool_limit`] and [`FlexSource::max_total_bytes`].
+    #[inline]
+    fn is_within_pool_limit(&self, additional_len: usize) -> bool {
+        self.max_resident_bytes().map_or(true, |limit| {
+            self.total_alloc_len
+                .checked_add(additional_len)
+                .map_or(false, |x| x <= limit)
+        })
+    }
+
+    /// Acquire a memory block of at least `min_size` bytes from the `Source`.
+    ///
+    /// A memory block shorter than requested violates the contract of
+    /// [`FlexSource::alloc`], but it's rejected here (even in release
+    /// builds) rather than being turned into a memory pool that can't contain
+    /// the allocation being served.
+    ///
+    /// # Safety
+    ///
+    /// See [`FlexSource::alloc`].
+    #[inline]
+    unsafe fn alloc_from_source(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        let alloc = self.source.alloc(min_size)?;
+        if nonnull_slice_len(alloc) < min_size {
+            self.reject_short_alloc(alloc);
+            return None;
+        }
+        Some(alloc)
+    }
+
+    /// Give back a memory block that [`FlexSource::alloc`] returned but
+    /// [`Self::alloc_from_source`] rejected. It's leaked if the `Source`
+    /// doesn't support deallocation.
+    #[cold]
+    unsafe fn reject_short_alloc(&mut self, alloc: NonNull<[u8]>) {
+        if self.source.supports_dealloc() {
+            // Safety: It's an allocation we allocated from `self.source`
+            self.source.dealloc(alloc);
+        }
+    }
+
+    /// Acquire a memory block from the `Source` and set it aside as an
+    /// emergency reserve, which is only used by [`Self::allocate_emergency`].
+    ///
+    /// The reserve is a separate memory pool of at least `min_pool_len` bytes
+    /// (including the memory block headers and the sentinel block, as in
+    /// [`Self::usable_bytes`]). Normal allocations never use it, so critical
+    /// allocations can succeed even after the other memory pools are exhausted
+    /// and the `Source` can't provide more memory. The memory block acquired
+    /// from the `Source` additionally contains the data structure managing
+    /// the reserve.
+    ///
+    /// Returns the actual length of the memory pool on success. Returns `None`
+    /// if an emergency reserve already exists or the memory block couldn't be
+    /// acquired from the `Source` (e.g., because of [`Self::pool_limit`]).
+    ///
+    /// The reserve is kept by [`Self::reset`] and released when `self` is
+    /// dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    /// tlsf.reserve_emergency_pool(1024).unwrap();
+    ///
+    /// // Use up the memory we are permitted to acquire
+    /// tlsf.set_pool_limit(Some(tlsf.resident_bytes() + 4096));
+    /// let layout = Layout::from_size_align(256, 8).unwrap();
+    /// while tlsf.allocate(layout).is_some() {}
+    ///
+    /// // Critical allocations can still succeed
+    /// let ptr = tlsf.allocate_emergency(layout).unwrap();
+    /// unsafe { tlsf.deallocate(ptr, layout.align()) };
+    /// ```
+    pub fn reserve_emergency_pool(&mut self, min_pool_len: usize) -> Option<NonZeroUsize> {
+        if self.emergency_pool.is_some() || self.source.is_exhausted() {
+            return None;
+        }
+
+        // Reserve space for the `Tlsf` (before aligning it) and for aligning
+        // both ends of the memory pool
+        let header_len = mem::size_of::<Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>>()
+            .checked_add(mem::align_of::<Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>>() - 1)?;
+        let alloc_len = min_pool_len
+            .max(GRANULARITY * 2)
+            .checked_add(header_len)?
+            .checked_add(GRANULARITY * 3 - 3)?
+            & !(GRANULARITY - 1);
+
+        if !self.is_within_pool_limit(alloc_len) {
+            return None;
+        }
+
+        // Safety: `alloc_len` is non-zero and aligned to `GRANULARITY` bytes
+        let alloc = unsafe { self.alloc_from_source(alloc_len)? };
+        self.total_alloc_len = self.total_alloc_len.wrapping_add(nonnull_slice_len(alloc));
+
+        // Place the `Tlsf` at the beginning of the allocation
+        let alloc_start = nonnull_slice_start(alloc).as_ptr() as usize;
+        let tlsf_align = mem::align_of::<Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>>();
+        let tlsf_start = (alloc_start + tlsf_align - 1) & !(tlsf_align - 1);
+        let tlsf = tlsf_start as *mut Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>;
+        let pool_start = tlsf.wrapping_add(1) as *mut u8;
+        let pool = nonnull_slice_from_raw_parts(
+            // Safety: `pool_start` is inside or at the end of `alloc`
+            unsafe { NonNull::new_unchecked(pool_start) },
+            nonnull_slice_end(alloc) as usize - pool_start as usize,
+        );
+
+        // Safety: `*tlsf` and `pool` are inside the allocation we acquired
+        //         from `self.source`, so we have the ownership
+        let pool_len = unsafe {
+            tlsf.write(Tlsf::new());
+            (*tlsf).insert_free_block_ptr(pool)
+        };
+        // `alloc_len` should be large enough to contain the memory pool
+        debug_assert!(pool_len.map_or(false, |x| x.get() >= min_pool_len));
+        let pool_len_usize = pool_len.map_or(0, NonZeroUsize::get);
+        self.total_pool_len = self.total_pool_len.wrapping_add(pool_len_usize);
+
+        self.emergency_pool = Some(EmergencyPool {
+            alloc,
+            pool,
+            pool_len: pool_len_usize,
+            // Safety: `tlsf` is inside `alloc`
+            tlsf: unsafe { NonNull::new_unchecked(tlsf) },
+        });
+        self.publish_stats();
+
+        pool_len
+    }
+
+    /// Attempt to allocate a block of memory, falling back to the emergency
+    /// reserve created by [`Self::reserve_emergency_pool`] if
+    /// [`Self::allocate`] fails.
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise. The memory block can be deallocated or reallocated
+    /// in the same way as the ones allocated by `allocate`. Memory blocks
+    /// allocated from the reserve are reallocated within the reserve.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time (assuming `Source`'s methods
+    /// do so as well).
+    pub fn allocate_emergency(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        if let Some(ptr) = self.allocate(layout) {
+            return Some(ptr);
+        }
+
+        let emergency_pool = self.emergency_pool.as_ref()?;
+        // Safety: `*emergency_pool.tlsf` is owned by `self`
+        unsafe { (*emergency_pool.tlsf.as_ptr()).allocate(layout) }
+    }
+
+    /// Get the `Tlsf` managing the emergency reserve if `ptr` is a memory
+    /// block allocated from it.
+    #[inline]
+    fn emergency_tlsf_for_allocation(
+        &self,
+        ptr: NonNull<u8>,
+    ) -> Option<NonNull<Tlsf<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>>> {
+        self.emergency_pool
+            .as_ref()
+            .filter(|emergency_pool| emergency_pool.contains(ptr))
+            .map(|emergency_pool| emergency_pool.tlsf)
+    }
+
     /// Attempt to allocate a block of memory.
     ///
     /// Returns the starting address of the allocated memory block on success;
@@ -309,13 +1186,55 @@ impl<
     /// do so as well).
     #[cfg_attr(target_arch = "wasm32", inline(never))]
     pub fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
-        if let Some(x) = self.tlsf.allocate(layout) {
+        #[cfg(feature = "fault-injection")]
+        if self.tlsf.should_inject_failure(layout) {
+            return None;
+        }
+
+        self.allocate_inner(layout)
+    }
+
+    /// Attempt to allocate a block of memory from the existing memory pools
+    /// without acquiring more memory from `Source`.
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise. This lets the caller decide what to do (e.g., evict a
+    /// cache) before [`Self::allocate`] grows the memory pools. The memory
+    /// block can be deallocated or reallocated in the same way as the ones
+    /// allocated by `allocate`.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    #[inline]
+    pub fn try_allocate_no_grow(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.tlsf.allocate(layout)
+    }
+
+    /// [`Self::allocate`] without consulting the [`FailPolicy`].
+    ///
+    /// [`FailPolicy`]: crate::FailPolicy
+    #[inline]
+    fn allocate_inner(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        // A memory pool can't contain an allocation this large, so fail
+        // early without bothering the source
+        if let Some(max_pool_size) =
+            Tlsf::<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>::MAX_POOL_SIZE
+        {
+            if layout.size() >= max_pool_size {
+                return None;
+            }
+        }
+
+        if let Some(x) = self.tlsf.allocate_inner(layout) {
             return Some(x);
         }
 
-        self.increase_pool_to_contain_allocation(layout)?;
+        let increased = self.increase_pool_to_contain_allocation(layout);
+        self.publish_stats();
+        increased?;
 
-        self.tlsf.allocate(layout).or_else(|| {
+        self.tlsf.allocate_inner(layout).or_else(|| {
             // Not a hard error, but it's still unexpected because
             // `increase_pool_to_contain_allocation` was supposed to make this
             // allocation possible
@@ -332,6 +1251,10 @@ impl<
     /// given allocation. Returns `Some(())` on success.
     #[inline]
     fn increase_pool_to_contain_allocation(&mut self, layout: Layout) -> Option<()> {
+        if self.source.is_exhausted() {
+            return None;
+        }
+
         let use_growable_pool = self.source.use_growable_pool();
 
         // How many extra bytes we need to get from the source for the
@@ -358,6 +1281,12 @@ impl<
             //    `insert_free_block_ptr`'s implementation.
             debug_assert!(new_pool_len_desired >= growable_pool.alloc_len);
 
+            // Growing the pool by a smaller amount is impossible, and creating
+            // a new pool would require at least as many bytes
+            if !self.is_within_pool_limit(new_pool_len_desired - growable_pool.alloc_len) {
+                return None;
+            }
+
             // Safety: `new_pool_end_desired >= growable_pool.alloc_len`, and
             //         `(growable_pool.alloc_start, growable_pool.alloc_len)`
             //         represents a previous allocation.
@@ -370,7 +1299,14 @@ impl<
                     new_pool_len_desired,
                 )
             } {
-                if self.source.supports_dealloc() {
+                debug_assert!(
+                    new_alloc_len >= new_pool_len_desired
+                        && (new_alloc_len - new_pool_len_desired) % GRANULARITY == 0,
+                    "`FlexSource::realloc_inplace_grow` returned a size that \
+                    misaligns the end of the memory pool"
+                );
+
+                {
                     // Move `PoolFtr`. Note that `PoolFtr::alloc_start` is
                     // still uninitialized because this allocation is still in
                     // `self.growable_pool`, so we only have to move
@@ -413,7 +1349,7 @@ impl<
                 // This assumption is based on `extra_bytes_well_aligned`'s
                 // implementation. The `debug_assert!` above depends on this.
                 debug_assert!(
-                    (growable_pool.pool_len + num_appended_len) - new_alloc_len < GRANULARITY * 2
+                    new_alloc_len - (growable_pool.pool_len + num_appended_len) < GRANULARITY * 2
                 );
 
                 self.growable_pool = Some(Pool {
@@ -421,6 +1357,15 @@ impl<
                     alloc_len: new_alloc_len,
                     pool_len: growable_pool.pool_len + num_appended_len,
                 });
+                self.total_alloc_len = self
+                    .total_alloc_len
+                    .wrapping_add(new_alloc_len - growable_pool.alloc_len);
+                self.total_pool_len = self.total_pool_len.wrapping_add(num_appended_len);
+
+                #[cfg(feature = "stats")]
+                {
+                    self.grow_stats.in_place = self.grow_stats.in_place.wrapping_add(1);
+                }
 
                 return Some(());
             } // if let Some(new_alloc_len) = ... realloc_inplace_grow
@@ -456,8 +1401,18 @@ impl<
             extra_bytes_well_aligned
         };
 
+        if !self.is_within_pool_limit(extra_bytes) {
+            return None;
+        }
+
         // Safety: `extra_bytes` is non-zero and aligned to `GRANULARITY` bytes
-        let alloc = unsafe { self.source.alloc(extra_bytes)? };
+        let alloc = unsafe { self.alloc_from_source(extra_bytes)? };
+        self.total_alloc_len = self.total_alloc_len.wrapping_add(nonnull_slice_len(alloc));
+
+        #[cfg(feature = "stats")]
+        {
+            self.grow_stats.fresh = self.grow_stats.fresh.wrapping_add(1);
+        }
 
         let is_well_aligned = self.source.min_align() >= super::GRANULARITY;
 
@@ -473,29 +1428,28 @@ impl<
         .unwrap_or_else(|| unsafe {
             debug_assert!(false, "`pool_size_to_contain_allocation` is an impostor");
             // Safety: It's unreachable
-            core::hint::unreachable_unchecked()
+            crate::utils::unreachable_unchecked()
         })
         .get();
-
-        if self.source.supports_dealloc() {
-            // Link the new memory pool's `PoolFtr::prev_alloc_end` to the
-            // previous pool (`self.growable_pool`).
-            let pool_ftr = PoolFtr::get_for_alloc(alloc, self.source.min_align());
-            let prev_alloc = self
-                .growable_pool
-                .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
-            // Safety: `(*pool_ftr).prev_alloc` is within a pool footer
-            //         we control
-            unsafe { (*pool_ftr).prev_alloc = prev_alloc };
-        }
-
-        if use_growable_pool {
-            self.growable_pool = Some(Pool {
-                alloc_start: nonnull_slice_start(alloc),
-                alloc_len: nonnull_slice_len(alloc),
-                pool_len,
-            });
-        }
+        self.total_pool_len = self.total_pool_len.wrapping_add(pool_len);
+
+        // Link the new memory pool's `PoolFtr::prev_alloc_end` to the
+        // previous pool (`self.growable_pool`).
+        let pool_ftr = PoolFtr::get_for_alloc(alloc, self.source.min_align());
+        let prev_alloc = self
+            .growable_pool
+            .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
+        // Safety: `(*pool_ftr).prev_alloc` is within a pool footer
+        //         we control
+        unsafe { (*pool_ftr).prev_alloc = prev_alloc };
+
+        // Remember the new memory pool even if `!use_growable_pool` so that
+        // the pools can be enumerated by following `PoolFtr::prev_alloc`
+        self.growable_pool = Some(Pool {
+            alloc_start: nonnull_slice_start(alloc),
+            alloc_len: nonnull_slice_len(alloc),
+            pool_len,
+        });
 
         Some(())
     }
@@ -512,9 +1466,14 @@ impl<
     ///  - `ptr` must denote a memory block previously allocated via `self`.
     ///  - The memory block must have been allocated with the same alignment
     ///    ([`Layout::align`]) as `align`.
-    ///
     #[cfg_attr(target_arch = "wasm32", inline(never))]
     pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+        if let Some(tlsf) = self.emergency_tlsf_for_allocation(ptr) {
+            // Safety: `*tlsf` is owned by `self`. The rest is upheld by the
+            //         caller.
+            return (*tlsf.as_ptr()).deallocate(ptr, align);
+        }
+
         // Safety: Upheld by the caller
         self.tlsf.deallocate(ptr, align)
     }
@@ -534,6 +1493,12 @@ impl<
     ///  - `ptr` must denote a memory block previously allocated via `self`.
     ///
     pub(crate) unsafe fn deallocate_unknown_align(&mut self, ptr: NonNull<u8>) {
+        if let Some(tlsf) = self.emergency_tlsf_for_allocation(ptr) {
+            // Safety: `*tlsf` is owned by `self`. The rest is upheld by the
+            //         caller.
+            return (*tlsf.as_ptr()).deallocate_unknown_align(ptr);
+        }
+
         // Safety: Upheld by the caller
         self.tlsf.deallocate_unknown_align(ptr)
     }
@@ -568,12 +1533,17 @@ impl<
     ///  - `ptr` must denote a memory block previously allocated via `self`.
     ///  - The memory block must have been allocated with the same alignment
     ///    ([`Layout::align`]) as `new_layout`.
-    ///
     pub unsafe fn reallocate(
         &mut self,
         ptr: NonNull<u8>,
         new_layout: Layout,
     ) -> Option<NonNull<u8>> {
+        if let Some(tlsf) = self.emergency_tlsf_for_allocation(ptr) {
+            // Safety: `*tlsf` is owned by `self`. The rest is upheld by the
+            //         caller.
+            return (*tlsf.as_ptr()).reallocate(ptr, new_layout);
+        }
+
         // Do this early so that the compiler can de-duplicate the evaluation of
         // `size_of_allocation`, which is done here as well as in
         // `Tlsf::reallocate`.
@@ -584,6 +1554,12 @@ impl<
 
         // Safety: Upheld by the caller
         if let Some(x) = self.tlsf.reallocate(ptr, new_layout) {
+            if self.source.supports_discard()
+                && old_size.saturating_sub(new_layout.size()) >= self.source.discard_threshold()
+                && x == ptr
+            {
+                self.discard_shrunk_part(ptr, new_layout.align(), old_size);
+            }
             return Some(x);
         }
 
@@ -591,7 +1567,7 @@ impl<
         // the same as the one in `Tlsf::reallocate`, but `self.allocation`
         // here refers to `FlexTlsf::allocate`, which inserts new meory pools
         // as necessary.
-        let new_ptr = self.allocate(new_layout)?;
+        let new_ptr = self.allocate_inner(new_layout)?;
 
         // Move the existing data into the new location
         debug_assert!(new_layout.size() >= old_size);
@@ -603,6 +1579,247 @@ impl<
         Some(new_ptr)
     }
 
+    /// Pass the memory freed by shrinking an allocation in place to
+    /// [`FlexSource::discard`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must denote an allocation that was `old_size` bytes long and has
+    /// just been shrunk in place. `align` must be its alignment.
+    #[cold]
+    unsafe fn discard_shrunk_part(&mut self, ptr: NonNull<u8>, align: usize, old_size: usize) {
+        let free =
+            match Tlsf::<'static, FLBitmap, SLBitmap, FLLEN, SLLEN>::free_block_after_allocation(
+                ptr, align,
+            ) {
+                Some(x) => x,
+                // The freed memory was too small to form a free block
+                None => return,
+            };
+
+        // The free block might have been merged with the next one. Only
+        // discard the part that was freed by the shrink.
+        let start = nonnull_slice_start(free).as_ptr() as usize;
+        let end = (nonnull_slice_end(free) as usize).min(ptr.as_ptr() as usize + old_size);
+        if end > start {
+            self.source.discard(nonnull_slice_from_raw_parts(
+                nonnull_slice_start(free),
+                end - start,
+            ));
+        }
+    }
+
+    /// Move all allocations to another `FlexTlsf`, yielding the old and new
+    /// address of each of them so that the caller can update its pointers.
+    ///
+    /// This can be used as a compaction pass at idle time. Each allocation is
+    /// reallocated in `dst` with the layout of its whole usable area (see
+    /// [`AllocationInfo::usable`](crate::AllocationInfo::usable)), and its
+    /// contents are copied. Because `dst` allocates them one after another,
+    /// the holes left by freed allocations in `self` don't reappear in `dst`.
+    ///
+    /// An item is `Err(old_ptr)` if `dst` failed to allocate memory for the
+    /// allocation at `old_ptr`. Such allocations stay in `self` and remain
+    /// valid.
+    ///
+    /// The allocations in `self` are not deallocated. Once all pointers have
+    /// been updated, release the old memory pools by calling [`Self::reset`]
+    /// or dropping `self`. Allocations in memory blocks added by
+    /// [`Self::insert_free_block`] are not enumerated.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_blocks +
+    /// total_allocated_bytes)`).
+    ///
+    /// # Safety
+    ///
+    ///  - Every allocation in `self` must have been made with alignment
+    ///    `align`, or both of them must be smaller than [`GRANULARITY`]. See
+    ///    [`Tlsf::iter_allocations`] for why this is needed.
+    ///  - The allocations in `self` must not be accessed while the iterator
+    ///    is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// type TheTlsf = FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16>;
+    /// let mut tlsf = TheTlsf::new(GlobalAllocAsFlexSource(System));
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let mut ptrs: Vec<_> = (0..64u64)
+    ///     .map(|i| {
+    ///         let ptr = tlsf.allocate(layout).unwrap();
+    ///         unsafe { ptr.cast::<u64>().as_ptr().write(i) };
+    ///         ptr
+    ///     })
+    ///     .collect();
+    ///
+    /// // Free every other allocation, leaving holes
+    /// for ptr in ptrs.iter().step_by(2) {
+    ///     unsafe { tlsf.deallocate(*ptr, layout.align()) };
+    /// }
+    /// let mut ptrs: Vec<_> = ptrs.into_iter().skip(1).step_by(2).collect();
+    ///
+    /// // Move the remaining allocations to a fresh arena
+    /// let mut new_tlsf = TheTlsf::new(GlobalAllocAsFlexSource(System));
+    /// for moved in unsafe { tlsf.compact_into(&mut new_tlsf, layout.align()) } {
+    ///     let (old_ptr, new_ptr) = moved.unwrap();
+    ///     let ptr = ptrs.iter_mut().find(|ptr| **ptr == old_ptr).unwrap();
+    ///     *ptr = new_ptr;
+    /// }
+    /// drop(tlsf);
+    ///
+    /// for (i, ptr) in ptrs.iter().enumerate() {
+    ///     assert_eq!(unsafe { *ptr.cast::<u64>().as_ptr() }, i as u64 * 2 + 1);
+    /// }
+    /// ```
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub unsafe fn compact_into<'a, DstSource: FlexSource>(
+        &'a mut self,
+        dst: &'a mut FlexTlsf<DstSource, FLBitmap, SLBitmap, FLLEN, SLLEN>,
+        align: usize,
+    ) -> impl Iterator<Item = Result<(NonNull<u8>, NonNull<u8>), NonNull<u8>>> + 'a {
+        let this = &*self;
+        let src_align = this.source.min_align();
+
+        // The allocations made by `self.source`, most recent first
+        let mut cur_alloc_or_none = this
+            .growable_pool
+            .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
+        let allocs = core::iter::from_fn(move || {
+            let cur_alloc = cur_alloc_or_none?;
+            // Safety: We control the referenced pool footer
+            cur_alloc_or_none = (*PoolFtr::get_for_alloc(cur_alloc, src_align)).prev_alloc;
+            Some((cur_alloc, &this.tlsf))
+        });
+
+        // The emergency reserve is managed by a separate `Tlsf`
+        let emergency_pool = this
+            .emergency_pool
+            .as_ref()
+            // Safety: `*tlsf` is owned by `self`
+            .map(|emergency_pool| (emergency_pool.pool, &*emergency_pool.tlsf.as_ptr()));
+
+        allocs
+            .chain(emergency_pool)
+            // Safety: The memory pools in `block` were created by `tlsf`, and
+            //         the allocations were made with `align` (upheld by the
+            //         caller)
+            .flat_map(move |(block, tlsf)| tlsf.iter_allocations_in_block(block, align))
+            .map(move |info| {
+                let start = nonnull_slice_start(info.block.as_ptr());
+                // Safety: The allocation is inside the memory block
+                let old_ptr = NonNull::new_unchecked(start.as_ptr().wrapping_add(info.overhead));
+
+                let new_ptr = Layout::from_size_align(info.usable, align)
+                    .ok()
+                    .and_then(|layout| dst.allocate(layout))
+                    .ok_or(old_ptr)?;
+
+                // Safety: Both are valid for `info.usable` bytes, and they
+                //         belong to different `FlexTlsf`s
+                core::ptr::copy_nonoverlapping(old_ptr.as_ptr(), new_ptr.as_ptr(), info.usable);
+
+                Ok((old_ptr, new_ptr))
+            })
+    }
+
+    /// Deallocate all memory blocks allocated via `self` at once.
+    ///
+    /// If the `Source` supports deallocation
+    /// ([`FlexSource::supports_dealloc`]), all memory pools are returned to
+    /// the `Source`. Otherwise, each memory pool is rebuilt as a single free
+    /// block to be reused by subsequent allocations. The emergency reserve
+    /// created by [`Self::reserve_emergency_pool`] is always kept for reuse.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_pools)`),
+    /// assuming `Source`'s methods complete in constant time.
+    ///
+    /// # Safety
+    ///
+    /// The memory blocks previously allocated via `self` must not be used
+    /// after calling this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FlexTlsf, GlobalAllocAsFlexSource};
+    /// use std::alloc::{Layout, System};
+    ///
+    /// let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<System, 1024>, u16, u16, 12, 16> =
+    ///     FlexTlsf::new(GlobalAllocAsFlexSource(System));
+    ///
+    /// for _ in 0..8 {
+    ///     // Allocate memory blocks for a frame
+    ///     for _ in 0..64 {
+    ///         tlsf.allocate(Layout::from_size_align(100, 8).unwrap()).unwrap();
+    ///     }
+    ///     // Release them all at the end of the frame
+    ///     unsafe { tlsf.reset() };
+    ///     assert_eq!(tlsf.resident_bytes(), 0);
+    /// }
+    /// ```
+    pub unsafe fn reset(&mut self) {
+        self.tlsf.forget_pools();
+        self.total_pool_len = 0;
+
+        if let Some(emergency_pool) = &mut self.emergency_pool {
+            // Safety: Upheld by the caller
+            emergency_pool.reset();
+            self.total_pool_len = emergency_pool.pool_len;
+        }
+
+        if self.source.supports_dealloc() {
+            // Safety: Upheld by the caller
+            self.dealloc_pools();
+            self.publish_stats();
+            return;
+        }
+
+        let align = self.source.min_align();
+        let is_well_aligned = align >= GRANULARITY;
+        let last_pool = self.growable_pool.take();
+        let mut cur_alloc_or_none =
+            last_pool.map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
+
+        while let Some(cur_alloc) = cur_alloc_or_none {
+            let pool_ftr = PoolFtr::get_for_alloc(cur_alloc, align);
+            // Safety: We control the referenced pool footer
+            let cur_ftr = *pool_ftr;
+
+            // Recreate the memory pool. This will overwrite the pool footer.
+            // Safety: It's an allocation we allocated from `self.source`, and
+            //         no memory blocks in it are in use
+            let pool_len = if is_well_aligned {
+                self.tlsf.insert_free_block_ptr_aligned(cur_alloc)
+            } else {
+                self.tlsf.insert_free_block_ptr(cur_alloc)
+            };
+            // The allocation contained a memory pool before, so it should
+            // contain one now
+            debug_assert!(pool_len.is_some());
+            let pool_len = pool_len.map_or(0, NonZeroUsize::get);
+            self.total_pool_len = self.total_pool_len.wrapping_add(pool_len);
+
+            *pool_ftr = cur_ftr;
+
+            if self.growable_pool.is_none() {
+                self.growable_pool = last_pool.map(|p| Pool { pool_len, ..p });
+            }
+
+            cur_alloc_or_none = cur_ftr.prev_alloc;
+        }
+
+        self.publish_stats();
+    }
+
     /// Get the payload size of the allocation with an unknown alignment. The
     /// returned size might be larger than the size specified at the allocation
     /// time.
@@ -618,31 +1835,64 @@ impl<
     }
 }
 
+impl<Source: FlexSource, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize>
+    FlexTlsf<Source, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    /// Return all memory pools but the emergency reserve to the `Source` and
+    /// forget them. This doesn't update `self.tlsf`.
+    ///
+    /// # Safety
+    ///
+    /// `self.source.supports_dealloc()` must be `true`. The memory blocks
+    /// previously allocated via `self` must not be used after calling this
+    /// method.
+    unsafe fn dealloc_pools(&mut self) {
+        debug_assert!(self.source.use_growable_pool());
+
+        self.total_alloc_len = self
+            .emergency_pool
+            .as_ref()
+            .map_or(0, |emergency_pool| nonnull_slice_len(emergency_pool.alloc));
+
+        let align = self.source.min_align();
+        let mut cur_alloc_or_none = self
+            .growable_pool
+            .take()
+            .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
+
+        while let Some(cur_alloc) = cur_alloc_or_none {
+            // Safety: We control the referenced pool footer
+            let cur_ftr = *PoolFtr::get_for_alloc(cur_alloc, align);
+
+            // Safety: It's an allocation we allocated from `self.source`
+            self.source.dealloc(cur_alloc);
+
+            cur_alloc_or_none = cur_ftr.prev_alloc;
+        }
+    }
+}
+
 impl<Source: FlexSource, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Drop
     for FlexTlsf<Source, FLBitmap, SLBitmap, FLLEN, SLLEN>
 {
     fn drop(&mut self) {
         if self.source.supports_dealloc() {
-            debug_assert!(self.source.use_growable_pool());
-
-            // Deallocate all memory pools
-            let align = self.source.min_align();
-            let mut cur_alloc_or_none = self
-                .growable_pool
-                .map(|p| nonnull_slice_from_raw_parts(p.alloc_start, p.alloc_len));
-
-            while let Some(cur_alloc) = cur_alloc_or_none {
-                // Safety: We control the referenced pool footer
-                let cur_ftr = unsafe { *PoolFtr::get_for_alloc(cur_alloc, align) };
+            // Safety: `self` is being dropped, so the memory pools will not be
+            //         used anymore
+            unsafe { self.dealloc_pools() };
 
+            if let Some(emergency_pool) = self.emergency_pool.take() {
                 // Safety: It's an allocation we allocated from `self.source`
-                unsafe { self.source.dealloc(cur_alloc) };
-
-                cur_alloc_or_none = cur_ftr.prev_alloc;
+                unsafe { self.source.dealloc(emergency_pool.alloc) };
             }
         }
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sbrk;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::sbrk::*;
+
 #[cfg(test)]
 mod tests;
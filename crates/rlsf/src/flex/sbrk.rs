@@ -0,0 +1,117 @@
+//! [`FlexSource`] based on the program break
+use core::ptr::NonNull;
+
+use super::FlexSource;
+use crate::utils::{nonnull_slice_end, nonnull_slice_from_raw_parts, nonnull_slice_len};
+
+/// A [`FlexSource`] that acquires memory by moving the program break
+/// (`sbrk`).
+///
+/// [`alloc`] extends the program break, and [`realloc_inplace_grow`] extends it
+/// further if the allocation being grown ends at the current program break.
+/// Memory is never returned to the system because the program break can't be
+/// shrunk out of order.
+///
+/// This is useful in minimal environments that provide `sbrk` but not `mmap`.
+/// [`GlobalTlsf`] uses the latter on supported systems.
+///
+/// [`alloc`]: FlexSource::alloc
+/// [`realloc_inplace_grow`]: FlexSource::realloc_inplace_grow
+/// [`GlobalTlsf`]: crate::GlobalTlsf
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use rlsf::{FlexTlsf, SbrkFlexSource};
+/// use std::alloc::Layout;
+///
+/// // Safety: Nothing else moves the program break in this program
+/// let source = unsafe { SbrkFlexSource::new() };
+/// let mut tlsf: FlexTlsf<_, u32, u32, 28, 32> = FlexTlsf::new(source);
+///
+/// let ptr = tlsf.allocate(Layout::new::<u64>()).unwrap();
+/// unsafe { tlsf.deallocate(ptr, Layout::new::<u64>().align()) };
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "doc_cfg",
+    doc(cfg(any(target_os = "linux", target_os = "android")))
+)]
+pub struct SbrkFlexSource {
+    _private: (),
+}
+
+impl SbrkFlexSource {
+    /// Construct a `SbrkFlexSource`.
+    ///
+    /// # Safety
+    ///
+    /// `sbrk` is not thread-safe. The program break must not be moved by
+    /// anyone else (e.g., the system allocator or another instance of
+    /// `SbrkFlexSource`) while `self`'s methods are running.
+    #[inline]
+    pub const unsafe fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+unsafe impl FlexSource for SbrkFlexSource {
+    #[inline]
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        let increment = isize::try_from(min_size).ok()?;
+
+        // Safety: Upheld by `Self::new`'s caller
+        let start = libc::sbrk(increment);
+
+        if start as isize == -1 {
+            return None;
+        }
+
+        Some(nonnull_slice_from_raw_parts(
+            NonNull::new(start as *mut u8)?,
+            min_size,
+        ))
+    }
+
+    #[inline]
+    unsafe fn realloc_inplace_grow(
+        &mut self,
+        ptr: NonNull<[u8]>,
+        min_new_len: usize,
+    ) -> Option<usize> {
+        let end = nonnull_slice_end(ptr);
+
+        // We can't grow the allocation if someone else has moved the program
+        // break past it
+        if libc::sbrk(0) as *mut u8 != end {
+            return None;
+        }
+
+        let increment = isize::try_from(min_new_len - nonnull_slice_len(ptr)).ok()?;
+
+        // Safety: Upheld by `Self::new`'s caller
+        if libc::sbrk(increment) as *mut u8 != end {
+            // failure
+            None
+        } else {
+            Some(min_new_len)
+        }
+    }
+
+    #[inline]
+    fn supports_realloc_inplace_grow(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    unsafe fn prefault(&mut self, ptr: NonNull<[u8]>) {
+        // Safety: The heap is a writable private anonymous mapping
+        crate::utils::madvise_populate_write(ptr);
+    }
+
+    // `is_contiguous_growable` can't return `true` because other code (e.g.,
+    // the system allocator) may move the program break.
+
+    // The initial program break isn't necessarily aligned, so use the default
+    // `min_align` (`1`).
+}
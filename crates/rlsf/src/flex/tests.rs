@@ -4,7 +4,9 @@ use std::{fmt, prelude::v1::*};
 use super::*;
 use crate::{
     tests::ShadowAllocator,
-    utils::{nonnull_slice_end, nonnull_slice_len},
+    utils::{
+        nonnull_slice_end, nonnull_slice_from_raw_parts, nonnull_slice_len, nonnull_slice_start,
+    },
 };
 
 trait TestFlexSource: FlexSource {
@@ -23,9 +25,16 @@ impl<T: core::alloc::GlobalAlloc + Default, const ALIGN: usize> TestFlexSource
     }
 }
 
+/// The value returned by `TrackingFlexSource::discard_threshold`
+const DISCARD_THRESHOLD: usize = 4096;
+
 #[derive(Debug)]
 struct TrackingFlexSource<T: FlexSource> {
     sa: ShadowAllocator,
+    /// The total length of the memory blocks passed to `prefault`
+    prefaulted_bytes: usize,
+    /// The total length of the memory blocks passed to `discard`
+    discarded_bytes: usize,
     inner: T,
 }
 
@@ -35,6 +44,8 @@ impl<T: TestFlexSource> TestFlexSource for TrackingFlexSource<T> {
     fn new(options: T::Options) -> Self {
         Self {
             sa: ShadowAllocator::default(),
+            prefaulted_bytes: 0,
+            discarded_bytes: 0,
             inner: T::new(options),
         }
     }
@@ -56,6 +67,10 @@ impl<T: FlexSource> Drop for TrackingFlexSource<T> {
 unsafe impl<T: FlexSource> FlexSource for TrackingFlexSource<T> {
     unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
         log::trace!("FlexSource::alloc({:?})", min_size);
+        assert!(
+            !self.inner.is_exhausted(),
+            "`alloc` called on an exhausted source"
+        );
         let range = self.inner.alloc(min_size)?;
         log::trace!(" FlexSource::alloc(...) = {:?}", range);
         self.sa.insert_free_block(range.as_ptr());
@@ -68,6 +83,10 @@ unsafe impl<T: FlexSource> FlexSource for TrackingFlexSource<T> {
         min_new_len: usize,
     ) -> Option<usize> {
         log::trace!("FlexSource::realloc_inplace_grow{:?}", (ptr, min_new_len));
+        assert!(
+            !self.inner.is_exhausted(),
+            "`realloc_inplace_grow` called on an exhausted source"
+        );
         let new_len = self.inner.realloc_inplace_grow(ptr, min_new_len)?;
         log::trace!(" FlexSource::realloc_inplace_grow(...) = {:?}", new_len);
         self.sa.append_free_block(std::ptr::slice_from_raw_parts(
@@ -91,6 +110,37 @@ unsafe impl<T: FlexSource> FlexSource for TrackingFlexSource<T> {
         self.sa.remove_pool(ptr.as_ptr());
     }
 
+    #[inline]
+    unsafe fn prefault(&mut self, ptr: NonNull<[u8]>) {
+        log::trace!("FlexSource::prefault({:?})", ptr);
+        self.inner.prefault(ptr);
+        self.prefaulted_bytes += nonnull_slice_len(ptr);
+    }
+
+    unsafe fn discard(&mut self, ptr: NonNull<[u8]>) {
+        log::trace!("FlexSource::discard({:?})", ptr);
+        self.inner.discard(ptr);
+        self.discarded_bytes += nonnull_slice_len(ptr);
+
+        // The contents are undefined after discarding. Clobber them to catch
+        // `FlexTlsf` discarding something it needs.
+        ptr.as_ptr()
+            .cast::<u8>()
+            .write_bytes(0xcc, nonnull_slice_len(ptr));
+    }
+
+    #[inline]
+    fn supports_discard(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn discard_threshold(&self) -> usize {
+        // Lower than the default so that `discard_on_shrink` covers both sides
+        // of the threshold
+        DISCARD_THRESHOLD
+    }
+
     #[inline]
     fn is_contiguous_growable(&self) -> bool {
         self.inner.is_contiguous_growable()
@@ -105,6 +155,16 @@ unsafe impl<T: FlexSource> FlexSource for TrackingFlexSource<T> {
     fn supports_realloc_inplace_grow(&self) -> bool {
         self.inner.supports_realloc_inplace_grow()
     }
+
+    #[inline]
+    fn is_exhausted(&self) -> bool {
+        self.inner.is_exhausted()
+    }
+
+    #[inline]
+    fn max_total_bytes(&self) -> Option<usize> {
+        self.inner.max_total_bytes()
+    }
 }
 
 /// Continuous-growing flex source
@@ -160,13 +220,265 @@ unsafe impl FlexSource for CgFlexSource {
         true
     }
 
+    fn is_exhausted(&self) -> bool {
+        self.allocated >= self.pool.len()
+    }
+
     fn min_align(&self) -> usize {
         1
     }
 }
 
+/// A flex source that violates the alignment rule of
+/// [`FlexSource::realloc_inplace_grow`] by growing allocations by one extra
+/// byte
+#[derive(Debug)]
+struct MisaligningFlexSource(CgFlexSource);
+
+unsafe impl FlexSource for MisaligningFlexSource {
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        self.0.alloc(min_size)
+    }
+
+    unsafe fn realloc_inplace_grow(
+        &mut self,
+        ptr: NonNull<[u8]>,
+        min_new_len: usize,
+    ) -> Option<usize> {
+        self.0.realloc_inplace_grow(ptr, min_new_len + 1)
+    }
+
+    fn is_contiguous_growable(&self) -> bool {
+        true
+    }
+
+    fn supports_realloc_inplace_grow(&self) -> bool {
+        true
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.0.is_exhausted()
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "misaligns the end of the memory pool")]
+fn misaligning_source() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source = MisaligningFlexSource(CgFlexSource::new(0));
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(source);
+
+    // The first allocation creates a memory pool, which the second one
+    // attempts to grow
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    tlsf.allocate(layout).unwrap();
+    tlsf.allocate(layout);
+}
+
+/// A flex source that provides one fixed-size memory block
+#[derive(Debug)]
+struct FixedFlexSource {
+    pool: Vec<u8>,
+    exhausted: bool,
+}
+
+unsafe impl FlexSource for FixedFlexSource {
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        if self.exhausted || min_size > self.pool.len() {
+            return None;
+        }
+
+        self.exhausted = true;
+        Some(NonNull::from(&mut self.pool[..]))
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    fn max_total_bytes(&self) -> Option<usize> {
+        Some(self.pool.len())
+    }
+}
+
+#[test]
+fn exhausted_source() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source = TrackingFlexSource {
+        sa: ShadowAllocator::default(),
+        prefaulted_bytes: 0,
+        discarded_bytes: 0,
+        inner: FixedFlexSource {
+            pool: std::vec![0u8; 4096],
+            exhausted: false,
+        },
+    };
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(source);
+    assert!(!tlsf.source_ref().is_exhausted());
+
+    // Use up the memory block. `TrackingFlexSource` checks that `FlexTlsf`
+    // doesn't call `alloc` after the source reports exhaustion.
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    let num_allocations = std::iter::from_fn(|| tlsf.allocate(layout)).count();
+    assert_ne!(num_allocations, 0);
+    assert!(tlsf.source_ref().is_exhausted());
+    assert!(tlsf.allocate(layout).is_none());
+}
+
+#[test]
+fn max_resident_bytes() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let source = TrackingFlexSource {
+        sa: ShadowAllocator::default(),
+        prefaulted_bytes: 0,
+        discarded_bytes: 0,
+        inner: FixedFlexSource {
+            pool: std::vec![0u8; 4096],
+            exhausted: false,
+        },
+    };
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(source);
+    assert_eq!(tlsf.max_resident_bytes(), Some(4096));
+
+    tlsf.set_pool_limit(Some(8192));
+    assert_eq!(tlsf.max_resident_bytes(), Some(4096));
+
+    tlsf.set_pool_limit(Some(1024));
+    assert_eq!(tlsf.max_resident_bytes(), Some(1024));
+
+    tlsf.set_pool_limit(None);
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    assert!(tlsf.allocate(layout).is_some());
+    assert!(tlsf.resident_bytes() <= tlsf.max_resident_bytes().unwrap());
+}
+
+#[test]
+fn request_beyond_max_total_bytes_fails_early() {
+    /// A flex source that can't provide more than 4096 bytes
+    struct SmallFlexSource;
+
+    unsafe impl FlexSource for SmallFlexSource {
+        unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+            panic!("`alloc({})` called", min_size);
+        }
+
+        fn max_total_bytes(&self) -> Option<usize> {
+            Some(4096)
+        }
+    }
+
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(SmallFlexSource);
+    let layout = Layout::from_size_align(8192, 1).unwrap();
+    assert_eq!(tlsf.allocate(layout), None);
+}
+
+#[test]
+fn huge_request_fails_early() {
+    /// A flex source that is never supposed to be asked for memory
+    struct NoAllocFlexSource;
+
+    unsafe impl FlexSource for NoAllocFlexSource {
+        unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+            panic!("`alloc({})` called", min_size);
+        }
+    }
+
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(NoAllocFlexSource);
+    let max_pool_size = Tlsf::<'static, u16, u16, 12, 16>::MAX_POOL_SIZE.unwrap();
+    for size in [max_pool_size, max_pool_size * 2, isize::MAX as usize] {
+        let layout = Layout::from_size_align(size, 1).unwrap();
+        assert_eq!(tlsf.allocate(layout), None);
+    }
+}
+
+#[test]
+fn short_alloc_is_rejected() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    /// A flex source that returns memory blocks one `GRANULARITY` shorter
+    /// than requested
+    #[derive(Debug)]
+    struct ShortFlexSource(GlobalAllocAsFlexSource<std::alloc::System, GRANULARITY>);
+
+    unsafe impl FlexSource for ShortFlexSource {
+        unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+            let alloc = self.0.alloc(min_size)?;
+            Some(nonnull_slice_from_raw_parts(
+                nonnull_slice_start(alloc),
+                nonnull_slice_len(alloc) - GRANULARITY,
+            ))
+        }
+
+        unsafe fn dealloc(&mut self, ptr: NonNull<[u8]>) {
+            self.0.dealloc(nonnull_slice_from_raw_parts(
+                nonnull_slice_start(ptr),
+                nonnull_slice_len(ptr) + GRANULARITY,
+            ))
+        }
+
+        fn supports_dealloc(&self) -> bool {
+            true
+        }
+
+        fn min_align(&self) -> usize {
+            GRANULARITY
+        }
+    }
+
+    let source = TrackingFlexSource {
+        sa: ShadowAllocator::default(),
+        prefaulted_bytes: 0,
+        discarded_bytes: 0,
+        inner: ShortFlexSource(GlobalAllocAsFlexSource(std::alloc::System)),
+    };
+    let mut tlsf: FlexTlsf<_, u16, u16, 12, 16> = FlexTlsf::new(source);
+
+    // `TrackingFlexSource` checks that the rejected memory blocks are
+    // deallocated
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    assert_eq!(tlsf.allocate(layout), None);
+    assert_eq!(tlsf.reserve_emergency_pool(1024), None);
+    assert_eq!(tlsf.resident_bytes(), 0);
+}
+
+#[cfg(all(feature = "stats", target_has_atomic = "ptr"))]
+#[test]
+fn stats_mirror() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    static STATS: AtomicFlexStats = AtomicFlexStats::new();
+
+    let mut tlsf: FlexTlsf<GlobalAllocAsFlexSource<std::alloc::System, 1024>, u16, u16, 12, 16> =
+        FlexTlsf::new(GlobalAllocAsFlexSource(std::alloc::System));
+
+    let check = |tlsf: &FlexTlsf<_, u16, u16, 12, 16>| {
+        assert_eq!(STATS.resident_bytes(), tlsf.resident_bytes());
+        assert_eq!(STATS.usable_bytes(), tlsf.usable_bytes());
+        assert_eq!(STATS.grow_stats(), tlsf.grow_stats());
+    };
+
+    tlsf.set_stats_mirror(Some(&STATS));
+    check(&tlsf);
+
+    tlsf.reserve_emergency_pool(1024).unwrap();
+    check(&tlsf);
+
+    for size in [64, 4096, 16384] {
+        tlsf.allocate(Layout::from_size_align(size, 8).unwrap())
+            .unwrap();
+        check(&tlsf);
+    }
+    assert_ne!(STATS.grow_stats().total(), 0);
+
+    unsafe { tlsf.reset() };
+    check(&tlsf);
+}
+
 fn fill_data(p: NonNull<[u8]>) {
-    use std::mem::MaybeUninit;
     let slice = unsafe { &mut *(p.as_ptr() as *mut [MaybeUninit<u8>]) };
     for (i, p) in slice.iter_mut().enumerate() {
         *p = MaybeUninit::new((i as u8).reverse_bits());
@@ -246,6 +558,312 @@ macro_rules! gen_test {
                 log::trace!("ptr3 = {:?}", ptr3);
             }
 
+            #[quickcheck]
+            fn pool_limit(source_options: <$source as TestFlexSource>::Options, limit: u16, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let limit = limit as usize;
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+                tlsf.set_pool_limit(Some(limit));
+                assert_eq!(tlsf.pool_limit(), Some(limit));
+
+                for size in sizes {
+                    let ptr = tlsf.allocate(Layout::from_size_align(size as usize, 1).unwrap());
+                    log::trace!("ptr = {:?}", ptr);
+                    log::trace!("resident_bytes = {}", tlsf.resident_bytes());
+                    // `GlobalAllocAsFlexSource` rounds up allocation sizes to
+                    // its alignment
+                    assert!(tlsf.resident_bytes() < limit + tlsf.source_ref().min_align());
+                    assert!(tlsf.usable_bytes() <= tlsf.resident_bytes());
+                }
+
+                // Lifting the limit should make room for new allocations
+                tlsf.set_pool_limit(None);
+                let layout = Layout::from_size_align(limit + 1, 1).unwrap();
+                if let Some(ptr) = tlsf.allocate(layout) {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+            }
+
+            #[quickcheck]
+            fn reset(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+
+                for _ in 0..3 {
+                    for &size in &sizes {
+                        let len = size as usize;
+                        if let Some(ptr) = tlsf.allocate(Layout::from_size_align(len, 1).unwrap()) {
+                            fill_data(nonnull_slice_from_raw_parts(ptr, len));
+                        }
+                    }
+
+                    let resident_bytes = tlsf.resident_bytes();
+                    unsafe { tlsf.reset() };
+                    log::trace!("tlsf = {:?}", tlsf);
+
+                    if tlsf.source_ref().supports_dealloc() {
+                        // `TrackingFlexSource` checks that all memory pools
+                        // are eventually returned
+                        assert_eq!(tlsf.resident_bytes(), 0);
+                        assert_eq!(tlsf.usable_bytes(), 0);
+                    } else {
+                        // The memory pools are kept for reuse
+                        assert_eq!(tlsf.resident_bytes(), resident_bytes);
+                        assert!(tlsf.usable_bytes() <= resident_bytes);
+                    }
+                }
+            }
+
+            #[quickcheck]
+            fn emergency_pool(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+
+                let pool_len = if let Some(x) = tlsf.reserve_emergency_pool(4096) {
+                    x.get()
+                } else {
+                    return;
+                };
+                log::trace!("tlsf = {:?}", tlsf);
+                assert!(pool_len >= 4096);
+                assert_eq!(tlsf.usable_bytes(), pool_len);
+                assert!(tlsf.reserve_emergency_pool(4096).is_none());
+
+                // Prevent normal allocations from acquiring memory
+                let resident_bytes = tlsf.resident_bytes();
+                tlsf.set_pool_limit(Some(resident_bytes));
+                assert_eq!(tlsf.allocate(Layout::from_size_align(1, 1).unwrap()), None);
+
+                for _ in 0..2 {
+                    let mut allocs = Vec::new();
+                    for &size in &sizes {
+                        let layout = Layout::from_size_align(size as usize % 1024, 8).unwrap();
+                        if let Some(ptr) = tlsf.allocate_emergency(layout) {
+                            log::trace!("allocate_emergency({:?}) = {:?}", layout, ptr);
+                            fill_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                            allocs.push((ptr, layout));
+                        }
+                    }
+
+                    // Reallocation happens within the emergency reserve
+                    for (ptr, layout) in allocs.iter_mut() {
+                        let new_layout = Layout::from_size_align(layout.size() / 2, 8).unwrap();
+                        *ptr = unsafe { tlsf.reallocate(*ptr, new_layout) }.unwrap();
+                        *layout = new_layout;
+                        verify_data(nonnull_slice_from_raw_parts(*ptr, layout.size()));
+                    }
+
+                    // Free half of them and discard the rest
+                    for &(ptr, layout) in allocs.iter().step_by(2) {
+                        verify_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                        unsafe { tlsf.deallocate(ptr, layout.align()) };
+                    }
+                    unsafe { tlsf.reset() };
+
+                    assert_eq!(tlsf.resident_bytes(), resident_bytes);
+                    assert_eq!(tlsf.usable_bytes(), pool_len);
+                }
+            }
+
+            #[cfg(feature = "unstable")]
+            #[quickcheck]
+            fn compact_into(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options.clone()));
+                tlsf.reserve_emergency_pool(4096);
+
+                let mut allocs = Vec::new();
+                for (i, &size) in sizes.iter().enumerate() {
+                    if i == sizes.len() / 2 {
+                        // Make the rest spill into the emergency reserve
+                        let resident_bytes = tlsf.resident_bytes();
+                        tlsf.set_pool_limit(Some(resident_bytes));
+                    }
+                    let layout = Layout::from_size_align(size as usize % 4096, 8).unwrap();
+                    if let Some(ptr) = tlsf.allocate_emergency(layout) {
+                        fill_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                        allocs.push((ptr, layout));
+                    }
+                }
+
+                // Leave holes
+                for &(ptr, layout) in allocs.iter().skip(1).step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+                let allocs: Vec<_> = allocs.into_iter().step_by(2).collect();
+
+                let mut dst = TheTlsf::new(TrackingFlexSource::new(source_options));
+                let mut results = vec![None; allocs.len()];
+                for result in unsafe { tlsf.compact_into(&mut dst, 8) } {
+                    log::trace!("compact_into(...) yielded {:?}", result);
+                    let old_ptr = match result {
+                        Ok((ptr, _)) | Err(ptr) => ptr,
+                    };
+                    let i = allocs
+                        .iter()
+                        .position(|&(ptr, _)| ptr == old_ptr)
+                        .expect("yielded an unknown allocation");
+                    assert!(results[i].is_none(), "yielded {:?} twice", old_ptr);
+                    results[i] = Some(result);
+                }
+
+                for (&(ptr, layout), result) in allocs.iter().zip(results) {
+                    match result.expect("an allocation was not yielded") {
+                        Ok((_, new_ptr)) => {
+                            verify_data(nonnull_slice_from_raw_parts(new_ptr, layout.size()));
+                            unsafe { dst.deallocate(new_ptr, layout.align()) };
+                        }
+                        Err(_) => {
+                            // It's still in `tlsf`
+                            verify_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                            unsafe { tlsf.deallocate(ptr, layout.align()) };
+                        }
+                    }
+                }
+            }
+
+            #[cfg(feature = "stats")]
+            #[quickcheck]
+            fn grow_stats(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+                assert_eq!(tlsf.grow_stats(), GrowStats::default());
+
+                let mut allocs = Vec::new();
+                for &size in &sizes {
+                    let layout = Layout::from_size_align(size as usize, 8).unwrap();
+                    let resident_bytes = tlsf.resident_bytes();
+                    let grow_count = tlsf.grow_stats().total();
+
+                    if let Some(ptr) = tlsf.allocate(layout) {
+                        allocs.push((ptr, layout));
+                    }
+
+                    // Every growth increases `resident_bytes`
+                    let grown = tlsf.resident_bytes() != resident_bytes;
+                    assert_eq!(tlsf.grow_stats().total(), grow_count + grown as u64);
+                }
+
+                if !tlsf.source_ref().supports_realloc_inplace_grow() {
+                    assert_eq!(tlsf.grow_stats().in_place, 0);
+                }
+
+                for (ptr, layout) in allocs {
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+            }
+
+            #[quickcheck]
+            fn try_allocate_no_grow(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+
+                // There are no memory pools yet
+                let layout = Layout::from_size_align(1, 1).unwrap();
+                assert!(tlsf.try_allocate_no_grow(layout).is_none());
+                assert_eq!(tlsf.resident_bytes(), 0);
+
+                let mut allocs = Vec::new();
+                for (i, &size) in sizes.iter().enumerate() {
+                    let layout = Layout::from_size_align(size as usize, 8).unwrap();
+                    let resident_bytes = tlsf.resident_bytes();
+
+                    let ptr = if i % 2 == 0 {
+                        tlsf.allocate(layout)
+                    } else {
+                        let ptr = tlsf.try_allocate_no_grow(layout);
+                        assert_eq!(tlsf.resident_bytes(), resident_bytes);
+                        ptr
+                    };
+
+                    if let Some(ptr) = ptr {
+                        fill_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                        allocs.push((ptr, layout));
+                    }
+                }
+
+                for (ptr, layout) in allocs {
+                    verify_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+            }
+
+            #[quickcheck]
+            fn discard_on_shrink(source_options: <$source as TestFlexSource>::Options, sizes: Vec<(u16, u16)>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+
+                let mut allocs = Vec::new();
+                for &(size, new_size) in &sizes {
+                    let layout = Layout::from_size_align(size as usize % 8192, 8).unwrap();
+                    let ptr = if let Some(ptr) = tlsf.allocate(layout) {
+                        ptr
+                    } else {
+                        continue;
+                    };
+
+                    // Shrink the allocation. The discarded memory is clobbered
+                    // by `TrackingFlexSource`.
+                    let new_layout =
+                        Layout::from_size_align(new_size as usize % (layout.size() + 1), 8).unwrap();
+                    fill_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                    let discarded_bytes = tlsf.source_ref().discarded_bytes;
+                    let new_ptr = unsafe { tlsf.reallocate(ptr, new_layout) }.unwrap();
+                    assert_eq!(new_ptr, ptr);
+                    let discarded_bytes = tlsf.source_ref().discarded_bytes - discarded_bytes;
+                    assert!(discarded_bytes <= layout.size() - new_layout.size());
+                    if layout.size() - new_layout.size() < DISCARD_THRESHOLD {
+                        assert_eq!(discarded_bytes, 0);
+                    }
+
+                    verify_data(nonnull_slice_from_raw_parts(ptr, new_layout.size()));
+                    allocs.push((ptr, new_layout));
+                }
+
+                // The allocator is still consistent
+                for (ptr, layout) in allocs {
+                    verify_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+                if let Some(ptr) = tlsf.allocate(Layout::from_size_align(8192, 8).unwrap()) {
+                    unsafe { tlsf.deallocate(ptr, 8) };
+                }
+            }
+
+            #[quickcheck]
+            fn prefault(source_options: <$source as TestFlexSource>::Options, sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf = TheTlsf::new(TrackingFlexSource::new(source_options));
+                tlsf.reserve_emergency_pool(1024);
+
+                let mut allocs = Vec::new();
+                for &size in &sizes {
+                    let layout = Layout::from_size_align(size as usize % 4096, 8).unwrap();
+                    if let Some(ptr) = tlsf.allocate(layout) {
+                        fill_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                        allocs.push((ptr, layout));
+                    }
+                }
+
+                // Every memory block acquired from the source is prefaulted
+                // exactly once, and the allocations' contents are preserved
+                tlsf.prefault();
+                assert_eq!(tlsf.source_ref().prefaulted_bytes, tlsf.resident_bytes());
+
+                for (ptr, layout) in allocs {
+                    verify_data(nonnull_slice_from_raw_parts(ptr, layout.size()));
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+            }
+
             #[quickcheck]
             fn random(source_options: <$source as TestFlexSource>::Options, max_alloc_size: usize, bytecode: Vec<u8>) {
                 random_inner(source_options, max_alloc_size, bytecode);
@@ -3,8 +3,10 @@ use core::{
     alloc,
     cell::UnsafeCell,
     marker::PhantomData,
+    mem::MaybeUninit,
     ops,
     ptr::{self, NonNull},
+    sync::atomic::{AtomicPtr, Ordering},
 };
 
 use super::FlexTlsf;
@@ -14,11 +16,27 @@ use super::FlexTlsf;
 if_supported_target! {
     /// [`Tlsf`] as a global allocator.
     ///
+    /// The internal state is protected by a lock of type `Lock`, which is
+    /// the platform's default lock ([`PlatformLock`]) unless specified
+    /// otherwise.
+    ///
+    /// The lock isn't reentrant. If the thread holding it calls into the
+    /// same `GlobalTlsf` again (e.g., a custom [`RawLock`] that allocates
+    /// memory, or a panic raised while the lock is held), it would deadlock.
+    /// If the `std` feature is enabled, this is detected by a thread-local
+    /// variable, and the process is aborted with a message instead.
+    ///
     /// [`Tlsf`]: crate::Tlsf
-    pub struct GlobalTlsf<Options: GlobalTlsfOptions = ()> {
+    pub struct GlobalTlsf<Options: GlobalTlsfOptions = (), Lock: RawLock = PlatformLock> {
         inner: UnsafeCell<TheTlsf<Options>>,
-        #[cfg(not(doc))]
-        mutex: os::Mutex,
+        /// The large object heap (see
+        /// [`GlobalTlsfOptions::LARGE_OBJECT_THRESHOLD`]), allocated from
+        /// `inner` on first use. Protected by `mutex`.
+        large: UnsafeCell<Option<NonNull<TheTlsf<Options>>>>,
+        mutex: Lock,
+        /// The function registered by [`Self::set_oom_hook`], stored as a
+        /// `fn() -> bool` casted to a data pointer. `null` if none.
+        oom_hook: AtomicPtr<()>,
         _phantom: PhantomData<fn() -> Options>,
     }
 }
@@ -29,9 +47,12 @@ cfg_if::cfg_if! {
     } else if #[cfg(unix)] {
         mod unix;
         use self::unix as os;
-    } else if #[cfg(target_arch = "wasm32")] {
+    } else if #[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))] {
         mod wasm32;
         use self::wasm32 as os;
+    } else if #[cfg(feature = "critical-section")] {
+        mod cs;
+        use self::cs as os;
     } else {
         compile_error!(
             "`crate::global` shouldn't be present when \
@@ -40,13 +61,70 @@ cfg_if::cfg_if! {
     }
 }
 
+#[cfg(feature = "std")]
+#[macro_use]
+mod tls;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "stats", feature = "std"))] {
+        mod thread_stats;
+        pub use self::thread_stats::ThreadStats;
+    } else {
+        mod thread_stats {
+            #[inline]
+            pub(super) fn record_allocation(_size: usize) {}
+            #[inline]
+            pub(super) fn record_deallocation() {}
+            #[inline]
+            pub(super) fn record_reallocation(_new_size: usize) {}
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(all(feature = "tracing", feature = "std"))] {
+        mod events;
+    } else {
+        mod events {
+            #[inline]
+            pub(super) fn allocation(_size: usize, _align: usize, _success: bool) {}
+            #[inline]
+            pub(super) fn deallocation(_size: Option<usize>, _align: Option<usize>) {}
+            #[inline]
+            pub(super) fn reallocation(
+                _old_size: Option<usize>,
+                _new_size: usize,
+                _success: bool,
+            ) {
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "std")] {
+        mod reentrancy;
+    } else {
+        mod reentrancy {
+            #[derive(Clone, Copy)]
+            pub(super) struct Entered;
+            #[inline]
+            pub(super) fn enter(_instance: *const ()) -> Entered {
+                Entered
+            }
+            #[inline]
+            pub(super) fn exit(_entered: Entered) {}
+        }
+    }
+}
+
 #[cfg(doc)]
 type TheTlsf<Options> = Options;
 #[cfg(not(doc))]
 type TheTlsf<Options> =
     FlexTlsf<os::Source<Options>, usize, usize, { usize::BITS as usize }, { usize::BITS as usize }>;
 
-impl<Options: GlobalTlsfOptions> ConstDefault for GlobalTlsf<Options> {
+impl<Options: GlobalTlsfOptions, Lock: RawLock> ConstDefault for GlobalTlsf<Options, Lock> {
     #[allow(clippy::declare_interior_mutable_const)]
     const DEFAULT: Self = Self::new();
 }
@@ -70,11 +148,138 @@ if_supported_target! {
         ///
         /// It's enabled by default.
         const COALESCE_POOLS: bool = true;
+
+        /// Pads allocations of at least [`Self::CACHE_LINE_ALIGN_THRESHOLD`]
+        /// bytes to [`Self::CACHE_LINE_SIZE`]-byte boundaries, i.e., aligns
+        /// their starting addresses and rounds up their sizes, so that they
+        /// never share a cache line with other allocations. This reduces
+        /// false sharing in concurrent code at the cost of memory usage.
+        ///
+        /// It's disabled by default.
+        const CACHE_LINE_ALIGN: bool = false;
+
+        /// The cache line size used by [`Self::CACHE_LINE_ALIGN`]. Must be a
+        /// power of two.
+        ///
+        /// It's `64` by default.
+        const CACHE_LINE_SIZE: usize = 64;
+
+        /// The minimum allocation size to which [`Self::CACHE_LINE_ALIGN`]
+        /// applies.
+        ///
+        /// It's `64` by default.
+        const CACHE_LINE_ALIGN_THRESHOLD: usize = 64;
+
+        /// Routes allocations of at least this many bytes (after applying
+        /// [`Self::CACHE_LINE_ALIGN`]) to a separate set of memory pools, a
+        /// *large object heap*. Large and small allocations have different
+        /// fragmentation profiles; keeping them apart prevents long-lived
+        /// small allocations from pinning the memory pools freed by large
+        /// ones and vice versa.
+        ///
+        /// The large object heap is created on the first large allocation.
+        /// Its bookkeeping structure is allocated from the main heap.
+        ///
+        /// The heap is selected by the requested size, which is known to
+        /// [`GlobalAlloc::dealloc`][alloc::GlobalAlloc::dealloc], so no
+        /// lookup is needed when deallocating. This option therefore only
+        /// applies to [`GlobalAlloc`][alloc::GlobalAlloc]. [`CAlloc`] always
+        /// uses the main heap because [`CAlloc::deallocate`] isn't given the
+        /// size.
+        ///
+        /// The large object heap acquires memory from the system, so this
+        /// option is ineffective on targets where [`GlobalTlsf`] has no
+        /// system memory source (i.e., when the `critical-section` backend is
+        /// in use); large allocations will fail there.
+        ///
+        /// It's `None` (disabled) by default.
+        const LARGE_OBJECT_THRESHOLD: Option<usize> = None;
     }
 }
 
 impl GlobalTlsfOptions for () {}
 
+if_supported_target! {
+    /// A lock protecting the internal state of [`GlobalTlsf`].
+    ///
+    /// Implement this trait to use a custom lock, e.g., a
+    /// priority-inheriting mutex to meet real-time requirements.
+    ///
+    /// # Safety
+    ///
+    /// [`Self::lock`] must not return while another thread holds the lock,
+    /// i.e., until the matching call to [`Self::unlock`]. The methods may be
+    /// called on any thread, and the lock is never acquired recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use core::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// struct SpinLock(AtomicBool);
+    ///
+    /// unsafe impl rlsf::RawLock for SpinLock {
+    ///     #[allow(clippy::declare_interior_mutable_const)]
+    ///     const INIT: Self = Self(AtomicBool::new(false));
+    ///
+    ///     fn lock(&self) {
+    ///         while self.0.swap(true, Ordering::Acquire) {
+    ///             core::hint::spin_loop();
+    ///         }
+    ///     }
+    ///
+    ///     fn unlock(&self) {
+    ///         self.0.store(false, Ordering::Release);
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static A: rlsf::GlobalTlsf<(), SpinLock> = rlsf::GlobalTlsf::new();
+    /// ```
+    pub unsafe trait RawLock {
+        /// The initial, unlocked state.
+        const INIT: Self;
+
+        /// Acquire the lock, blocking the current thread until it's
+        /// available.
+        fn lock(&self);
+
+        /// Release the lock acquired by [`Self::lock`].
+        fn unlock(&self);
+    }
+}
+
+if_supported_target! {
+    /// The platform's default [`RawLock`] for [`GlobalTlsf`]: a
+    /// `pthread_mutex_t` on Unix, a no-op on single-threaded WebAssembly,
+    /// and a critical section (provided by the `critical-section` crate)
+    /// elsewhere.
+    pub struct PlatformLock {
+        #[cfg(not(doc))]
+        inner: os::Mutex,
+    }
+}
+
+unsafe impl RawLock for PlatformLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self {
+        #[cfg(not(doc))]
+        inner: ConstDefault::DEFAULT,
+    };
+
+    #[inline]
+    fn lock(&self) {
+        #[cfg(not(doc))]
+        self.inner.lock();
+    }
+
+    #[inline]
+    fn unlock(&self) {
+        #[cfg(not(doc))]
+        self.inner.unlock();
+    }
+}
+
 if_supported_target! {
     /// [`GlobalTlsfOptions`] with all options set to optimize for code size.
     #[derive(Debug)]
@@ -91,102 +296,485 @@ impl GlobalTlsfOptions for SmallGlobalTlsfOptions {
     const COALESCE_POOLS: bool = false;
 }
 
-unsafe impl<Options: GlobalTlsfOptions> Send for GlobalTlsf<Options> {}
-unsafe impl<Options: GlobalTlsfOptions> Sync for GlobalTlsf<Options> {}
+unsafe impl<Options: GlobalTlsfOptions, Lock: RawLock> Send for GlobalTlsf<Options, Lock> {}
+unsafe impl<Options: GlobalTlsfOptions, Lock: RawLock> Sync for GlobalTlsf<Options, Lock> {}
 
-impl<Options: GlobalTlsfOptions> GlobalTlsf<Options> {
+impl<Options: GlobalTlsfOptions, Lock: RawLock> GlobalTlsf<Options, Lock> {
     /// Construct an empty instance of `Self`.
     #[inline]
     pub const fn new() -> Self {
         Self {
             inner: UnsafeCell::new(ConstDefault::DEFAULT),
-            mutex: ConstDefault::DEFAULT,
+            large: UnsafeCell::new(None),
+            mutex: Lock::INIT,
+            oom_hook: AtomicPtr::new(ptr::null_mut()),
             _phantom: PhantomData,
         }
     }
-}
 
-impl<Options: GlobalTlsfOptions> GlobalTlsf<Options> {
+    /// Register a function to be called when an allocation or reallocation
+    /// fails, replacing any previously registered one.
+    ///
+    /// The hook is called without holding the internal lock, so it may
+    /// release memory (e.g., drop caches) by deallocating through `self`. If
+    /// the hook returns `true`, the failed operation will be retried once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #[global_allocator]
+    /// static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+    ///
+    /// A.set_oom_hook(|| {
+    ///     // Drop some caches here and request a retry
+    ///     true
+    /// });
+    /// ```
     #[inline]
-    fn lock_inner(&self) -> impl ops::DerefMut<Target = TheTlsf<Options>> + '_ {
-        struct LockGuard<'a, Options: GlobalTlsfOptions>(&'a GlobalTlsf<Options>);
+    pub fn set_oom_hook(&self, hook: fn() -> bool) {
+        self.oom_hook.store(hook as *mut (), Ordering::Release);
+    }
 
-        impl<Options: GlobalTlsfOptions> ops::Deref for LockGuard<'_, Options> {
-            type Target = TheTlsf<Options>;
+    /// Add a memory pool to the allocator.
+    ///
+    /// This is the only way to provide memory to the allocator on targets
+    /// without an operating system (i.e., when the `critical-section` backend
+    /// is in use). On other targets, the allocator acquires memory from the
+    /// system as needed, and this method can be used to supply additional
+    /// memory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use core::mem::MaybeUninit;
+    ///
+    /// #[global_allocator]
+    /// static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+    ///
+    /// #[cortex_m_rt::entry]
+    /// fn main() -> ! {
+    ///     static mut POOL: [MaybeUninit<u8>; 16384] = [MaybeUninit::uninit(); 16384];
+    ///     // `cortex_m_rt::entry` turns `POOL` into `&'static mut _`
+    ///     A.insert_free_block(POOL);
+    ///     // ...
+    /// }
+    /// ```
+    #[inline]
+    pub fn insert_free_block(&self, block: &'static mut [MaybeUninit<u8>]) {
+        self.lock_inner().insert_free_block(block);
+    }
 
-            #[inline]
-            fn deref(&self) -> &Self::Target {
-                // Safety: Protected by `mutex`
-                unsafe { &*self.0.inner.get() }
-            }
+    /// Commit the physical memory backing the memory pools acquired from the
+    /// system so far. See [`FlexTlsf::prefault`].
+    ///
+    /// This is only effective on Linux and Android with a kernel supporting
+    /// `MADV_POPULATE_WRITE` (Linux 5.14 and later). It does nothing on other
+    /// targets.
+    #[inline]
+    pub fn prefault(&self) {
+        let mut inner = self.lock_inner();
+        inner.prefault();
+        if let Some(large) = inner.large_heap() {
+            large.prefault();
         }
+    }
 
-        impl<Options: GlobalTlsfOptions> ops::DerefMut for LockGuard<'_, Options> {
-            #[inline]
-            fn deref_mut(&mut self) -> &mut Self::Target {
-                // Safety: Protected by `mutex`
-                unsafe { &mut *self.0.inner.get() }
-            }
+    /// Limit the total number of bytes that the allocator may acquire from
+    /// the system. See [`FlexTlsf::set_pool_limit`].
+    ///
+    /// Allocations that would need to grow the memory pools beyond the limit
+    /// fail (and are reported to the OOM hook) even if the system could
+    /// satisfy them.
+    ///
+    /// If [`GlobalTlsfOptions::LARGE_OBJECT_THRESHOLD`] is set, the limit
+    /// applies to the main heap and the large object heap separately.
+    #[inline]
+    pub fn set_pool_limit(&self, max_total_bytes: Option<usize>) {
+        let mut inner = self.lock_inner();
+        inner.set_pool_limit(max_total_bytes);
+        if let Some(large) = inner.large_heap() {
+            large.set_pool_limit(max_total_bytes);
         }
+    }
 
-        impl<Options: GlobalTlsfOptions> Drop for LockGuard<'_, Options> {
-            #[inline]
-            fn drop(&mut self) {
-                self.0.mutex.unlock();
-            }
+    /// Get the limit set by [`Self::set_pool_limit`].
+    #[inline]
+    pub fn pool_limit(&self) -> Option<usize> {
+        self.lock_inner().pool_limit()
+    }
+
+    /// Limit the memory pools to `percent`% of the process's soft address
+    /// space limit (`RLIMIT_AS`) by calling [`Self::set_pool_limit`].
+    ///
+    /// Once `RLIMIT_AS` is reached, every `mmap` fails, including those made
+    /// by code other than the allocator (e.g., for thread stacks). Capping
+    /// the allocator below the limit makes allocations fail early, giving
+    /// the OOM hook a chance to release memory before the whole process runs
+    /// out of address space. `percent` should leave room for the program
+    /// image, stacks, and other mappings, which also count toward the limit.
+    ///
+    /// Returns the new limit, or `None` if the process has no soft address
+    /// space limit, in which case the current limit is left intact. The limit
+    /// is read only when this method is called.
+    ///
+    /// Memory limits imposed by other means, such as cgroups, are not
+    /// detected. Read them by other means and pass the result to
+    /// [`Self::set_pool_limit`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #[global_allocator]
+    /// static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+    ///
+    /// A.set_pool_limit_from_rlimit(75);
+    /// A.set_oom_hook(|| {
+    ///     // Drop some caches here and request a retry
+    ///     true
+    /// });
+    /// ```
+    #[cfg(unix)]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(unix)))]
+    pub fn set_pool_limit_from_rlimit(&self, percent: u8) -> Option<usize> {
+        let limit = crate::utils::address_space_soft_limit()?;
+        let limit = (limit / 100).saturating_mul(percent as usize);
+        self.set_pool_limit(Some(limit));
+        Some(limit)
+    }
+
+    /// Set the policy for failing allocation requests artificially. See
+    /// [`Tlsf::set_fail_policy`].
+    ///
+    /// The policy applies to [`GlobalAlloc::alloc`][alloc::GlobalAlloc::alloc]
+    /// and [`CAlloc::allocate`]. Failed requests are reported to the OOM hook
+    /// like any other allocation failure. Allocations served by the large
+    /// object heap (see [`GlobalTlsfOptions::LARGE_OBJECT_THRESHOLD`]) are
+    /// not subject to the policy.
+    ///
+    /// [`Tlsf::set_fail_policy`]: crate::Tlsf::set_fail_policy
+    #[cfg(feature = "fault-injection")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "fault-injection")))]
+    #[inline]
+    pub fn set_fail_policy(&self, policy: crate::FailPolicy) {
+        self.lock_inner().set_fail_policy(policy);
+    }
+
+    /// Get the allocation counters of the current thread.
+    ///
+    /// The counters are maintained per thread (not per `GlobalTlsf`
+    /// instance) and are never reset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+    ///
+    /// let before = A.thread_stats();
+    /// let ptr = unsafe { std::alloc::GlobalAlloc::alloc(&A, std::alloc::Layout::new::<u64>()) };
+    /// assert_eq!(A.thread_stats().num_allocations, before.num_allocations + 1);
+    /// # unsafe { std::alloc::GlobalAlloc::dealloc(&A, ptr, std::alloc::Layout::new::<u64>()) };
+    /// ```
+    #[cfg(all(feature = "stats", feature = "std"))]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(all(feature = "stats", feature = "std"))))]
+    #[inline]
+    pub fn thread_stats(&self) -> ThreadStats {
+        thread_stats::get()
+    }
+
+    /// Write a human-readable summary of the allocator's state to `w`. See
+    /// [`FlexTlsf::write_report`].
+    ///
+    /// This is meant for diagnosing allocation failures, e.g., from the OOM
+    /// hook (see [`Self::set_oom_hook`]) or the application's allocation
+    /// error handler. The internal lock is held while writing to `w`, so `w`
+    /// must not allocate memory from `self`; otherwise, it will deadlock. A
+    /// fixed-size buffer or a serial port is a good choice for `w`.
+    ///
+    /// If the large object heap (see
+    /// [`GlobalTlsfOptions::LARGE_OBJECT_THRESHOLD`]) has been created, its
+    /// summary follows that of the main heap.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_free_blocks)`).
+    ///
+    /// # Examples
+    ///
+    /// Dumping the allocator's state on allocation failure in a bare-metal
+    /// application. The OOM hook is called for every failed request before
+    /// `alloc` returns a null pointer, so it's also available on the stable
+    /// toolchain, where `#[alloc_error_handler]` can't be defined.
+    ///
+    /// ```rust,ignore
+    /// #![no_std]
+    /// #![no_main]
+    /// extern crate alloc;
+    ///
+    /// use core::fmt::Write;
+    ///
+    /// #[global_allocator]
+    /// static A: rlsf::GlobalTlsf = rlsf::GlobalTlsf::new();
+    ///
+    /// #[cortex_m_rt::entry]
+    /// fn main() -> ! {
+    ///     static mut POOL: [core::mem::MaybeUninit<u8>; 16384] =
+    ///         [core::mem::MaybeUninit::uninit(); 16384];
+    ///     A.insert_free_block(POOL);
+    ///     A.set_oom_hook(|| {
+    ///         // `Uart` writes to a serial port without allocating
+    ///         let _ = writeln!(Uart, "allocation failed");
+    ///         let _ = A.write_report(&mut Uart);
+    ///         // Nothing to release; don't retry
+    ///         false
+    ///     });
+    ///     // ...
+    /// }
+    ///
+    /// // On the nightly toolchain, the layout of the failed request can be
+    /// // reported as well
+    /// #[alloc_error_handler]
+    /// fn on_alloc_error(layout: core::alloc::Layout) -> ! {
+    ///     let _ = writeln!(Uart, "failed to allocate {:?}", layout);
+    ///     panic!("out of memory");
+    /// }
+    /// ```
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    pub fn write_report(&self, w: &mut impl core::fmt::Write) -> core::fmt::Result {
+        let mut inner = self.lock_inner();
+        inner.write_report(w)?;
+        if let Some(large) = inner.large_heap() {
+            writeln!(w, "large object heap:")?;
+            large.write_report(w)?;
+        }
+        Ok(())
+    }
+
+    /// Call `f`. If it fails, give the OOM hook a chance to release memory
+    /// and call `f` again if requested.
+    #[inline]
+    fn retry_on_oom<T>(&self, mut f: impl FnMut() -> Option<T>) -> Option<T> {
+        f().or_else(|| if self.call_oom_hook() { f() } else { None })
+    }
+
+    #[cold]
+    fn call_oom_hook(&self) -> bool {
+        let hook = self.oom_hook.load(Ordering::Acquire);
+        if hook.is_null() {
+            return false;
         }
+        // Safety: `hook` was created from `fn() -> bool` by `set_oom_hook`
+        let hook: fn() -> bool = unsafe { core::mem::transmute(hook) };
+        hook()
+    }
+}
 
+impl<Options: GlobalTlsfOptions, Lock: RawLock> GlobalTlsf<Options, Lock> {
+    /// Acquire the lock.
+    ///
+    /// If the `std` feature is enabled, this aborts the process instead of
+    /// deadlocking if the current thread already holds the lock.
+    #[inline]
+    fn lock_inner(&self) -> LockGuard<'_, Options, Lock> {
+        let entered = reentrancy::enter(self as *const Self as *const ());
         self.mutex.lock();
-        LockGuard(self)
+        LockGuard(self, entered)
+    }
+
+    /// Check if an allocation with the padded layout `padded_layout` belongs
+    /// to the large object heap.
+    #[inline]
+    fn is_large(padded_layout: alloc::Layout) -> bool {
+        matches!(Options::LARGE_OBJECT_THRESHOLD, Some(threshold) if padded_layout.size() >= threshold)
+    }
+
+    /// Get the layout actually used to allocate a memory block for `layout`,
+    /// applying [`GlobalTlsfOptions::CACHE_LINE_ALIGN`].
+    ///
+    /// The result only depends on `layout`, so it can be recomputed when
+    /// deallocating the memory block.
+    #[inline]
+    fn padded_layout(layout: alloc::Layout) -> alloc::Layout {
+        if Options::CACHE_LINE_ALIGN && layout.size() >= Options::CACHE_LINE_ALIGN_THRESHOLD {
+            // If this fails, the layout is too large to be allocated anyway
+            layout
+                .align_to(Options::CACHE_LINE_SIZE)
+                .map(|layout| layout.pad_to_align())
+                .unwrap_or(layout)
+        } else {
+            layout
+        }
+    }
+}
+
+struct LockGuard<'a, Options: GlobalTlsfOptions, Lock: RawLock>(
+    &'a GlobalTlsf<Options, Lock>,
+    reentrancy::Entered,
+);
+
+impl<Options: GlobalTlsfOptions, Lock: RawLock> LockGuard<'_, Options, Lock> {
+    /// Get the large object heap if it has been created.
+    #[inline]
+    fn large_heap(&mut self) -> Option<&mut TheTlsf<Options>> {
+        // Safety: Protected by `mutex`. The large object heap is only
+        //         accessed through `self`.
+        unsafe { (*self.0.large.get()).map(|large| &mut *large.as_ptr()) }
+    }
+
+    /// Get the heap serving the allocations for which `is_large` returned
+    /// `large`. The large object heap is created if it doesn't exist yet;
+    /// returns `None` if it can't be created.
+    #[inline]
+    fn arena(&mut self, large: bool) -> Option<&mut TheTlsf<Options>> {
+        if !large {
+            return Some(&mut **self);
+        }
+        if self.large_heap().is_none() {
+            self.create_large_heap()?;
+        }
+        self.large_heap()
+    }
+
+    #[cold]
+    fn create_large_heap(&mut self) -> Option<()> {
+        let pool_limit = self.pool_limit();
+        let large: NonNull<TheTlsf<Options>> = self
+            .allocate(alloc::Layout::new::<TheTlsf<Options>>())?
+            .cast();
+        let mut heap: TheTlsf<Options> = ConstDefault::DEFAULT;
+        heap.set_pool_limit(pool_limit);
+        // Safety: `large` is a fresh allocation suitable for
+        //         `TheTlsf<Options>`, and it's protected by `mutex`
+        unsafe {
+            large.as_ptr().write(heap);
+            *self.0.large.get() = Some(large);
+        }
+        Some(())
     }
 }
 
-unsafe impl<Options: GlobalTlsfOptions> alloc::GlobalAlloc for GlobalTlsf<Options> {
+impl<Options: GlobalTlsfOptions, Lock: RawLock> ops::Deref for LockGuard<'_, Options, Lock> {
+    type Target = TheTlsf<Options>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // Safety: Protected by `mutex`
+        unsafe { &*self.0.inner.get() }
+    }
+}
+
+impl<Options: GlobalTlsfOptions, Lock: RawLock> ops::DerefMut for LockGuard<'_, Options, Lock> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: Protected by `mutex`
+        unsafe { &mut *self.0.inner.get() }
+    }
+}
+
+impl<Options: GlobalTlsfOptions, Lock: RawLock> Drop for LockGuard<'_, Options, Lock> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.mutex.unlock();
+        reentrancy::exit(self.1);
+    }
+}
+
+impl<Options: GlobalTlsfOptions, Lock: RawLock> Drop for GlobalTlsf<Options, Lock> {
+    fn drop(&mut self) {
+        if let Some(large) = *self.large.get_mut() {
+            // Safety: The large object heap was created by
+            //         `LockGuard::create_large_heap` and is no longer used.
+            //         Its storage is released along with the main heap.
+            unsafe { ptr::drop_in_place(large.as_ptr()) };
+        }
+    }
+}
+
+unsafe impl<Options: GlobalTlsfOptions, Lock: RawLock> alloc::GlobalAlloc
+    for GlobalTlsf<Options, Lock>
+{
     #[inline]
     unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
-        let mut inner = self.lock_inner();
-        inner
-            .allocate(layout)
-            .map(NonNull::as_ptr)
-            .unwrap_or(ptr::null_mut())
+        let padded_layout = Self::padded_layout(layout);
+        let large = Self::is_large(padded_layout);
+        let ptr = self
+            .retry_on_oom(|| self.lock_inner().arena(large)?.allocate(padded_layout))
+            .map(|ptr| {
+                thread_stats::record_allocation(layout.size());
+                ptr.as_ptr()
+            })
+            .unwrap_or(ptr::null_mut());
+        events::allocation(layout.size(), layout.align(), !ptr.is_null());
+        ptr
     }
 
     #[inline]
     unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
-        let mut inner = self.lock_inner();
         // Safety: All allocations are non-null
         let ptr = NonNull::new_unchecked(ptr);
-        // Safety: `ptr` denotes a previous allocation with alignment
-        //         `layout.align()`
-        inner.deallocate(ptr, layout.align());
+        let padded_layout = Self::padded_layout(layout);
+        // The heap already exists because it allocated `ptr`
+        if let Some(arena) = self.lock_inner().arena(Self::is_large(padded_layout)) {
+            // Safety: `ptr` denotes a previous allocation from `arena` with
+            //         alignment `padded_layout.align()`
+            arena.deallocate(ptr, padded_layout.align());
+        }
+        thread_stats::record_deallocation();
+        events::deallocation(Some(layout.size()), Some(layout.align()));
     }
 
     #[inline]
     unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
-        let mut inner = self.lock_inner();
         // Safety: All allocations are non-null
         let ptr = NonNull::new_unchecked(ptr);
         // Safety: `layout.align()` is a power of two, and the size parameter's
         //         validity is upheld by the caller
         let new_layout = alloc::Layout::from_size_align_unchecked(new_size, layout.align());
-        if Options::ENABLE_REALLOCATION {
-            // Safety: `ptr` denotes a previous allocation with alignment
-            //         `layout.align()`
-            inner
-                .reallocate(ptr, new_layout)
-                .map(NonNull::as_ptr)
-                .unwrap_or(ptr::null_mut())
-        } else if let Some(new_ptr) = inner.allocate(new_layout) {
-            // Safety: the previously allocated block cannot overlap the
-            //         newly allocated block.
-            //         The safety contract for `deallocate` must be upheld
-            //         by the caller.
-            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), layout.size().min(new_size));
-            inner.deallocate(ptr, layout.align());
-            new_ptr.as_ptr()
-        } else {
-            ptr::null_mut()
-        }
+
+        let old_layout = Self::padded_layout(layout);
+        let old_align = old_layout.align();
+        let old_large = Self::is_large(old_layout);
+        let new_layout = Self::padded_layout(new_layout);
+        let new_large = Self::is_large(new_layout);
+
+        // `Tlsf::reallocate` can't change the alignment, which
+        // `CACHE_LINE_ALIGN` might do, or move the allocation to another heap
+        let can_reallocate = Options::ENABLE_REALLOCATION
+            && new_layout.align() == old_align
+            && new_large == old_large;
+
+        let new_ptr = self
+            .retry_on_oom(|| {
+                let mut inner = self.lock_inner();
+                if can_reallocate {
+                    // Safety: `ptr` denotes a previous allocation from this heap
+                    //         with alignment `old_align`
+                    inner.arena(old_large)?.reallocate(ptr, new_layout)
+                } else {
+                    let new_ptr = inner.arena(new_large)?.allocate(new_layout)?;
+                    // Safety: the previously allocated block cannot overlap the
+                    //         newly allocated block.
+                    //         The safety contract for `deallocate` must be upheld
+                    //         by the caller.
+                    ptr::copy_nonoverlapping(
+                        ptr.as_ptr(),
+                        new_ptr.as_ptr(),
+                        layout.size().min(new_size),
+                    );
+                    // The heap already exists because it allocated `ptr`
+                    if let Some(arena) = inner.arena(old_large) {
+                        arena.deallocate(ptr, old_align);
+                    }
+                    Some(new_ptr)
+                }
+            })
+            .map(|new_ptr| {
+                thread_stats::record_reallocation(new_size);
+                new_ptr.as_ptr()
+            })
+            .unwrap_or(ptr::null_mut());
+        events::reallocation(Some(layout.size()), new_size, !new_ptr.is_null());
+        new_ptr
     }
 }
 
@@ -195,6 +783,13 @@ unsafe impl<Options: GlobalTlsfOptions> alloc::GlobalAlloc for GlobalTlsf<Option
 ///
 /// Note that this trait might require a less efficient implementation than
 /// [`core::alloc::GlobalAlloc`]. This applies to [`GlobalTlsf`].
+///
+/// # Safety
+///
+/// The implementation must uphold the same requirements as
+/// [`core::alloc::GlobalAlloc`]. In particular, the returned memory blocks
+/// must satisfy the requested layout and must not overlap with any other live
+/// allocations. Implementations must not panic.
 pub unsafe trait CAlloc {
     /// Allocate a memory block.
     ///
@@ -216,6 +811,11 @@ pub unsafe trait CAlloc {
     /// Returns the new starting address of the memory block on success;
     /// `None` otherwise.
     ///
+    /// On success, the contents of the memory block are preserved up to the
+    /// lesser of the old and new sizes, and the old memory block must be
+    /// considered deallocated. On failure, the old memory block is left
+    /// intact.
+    ///
     /// # Safety
     ///
     ///  - `ptr` must denote a memory block previously allocated by calling
@@ -226,6 +826,13 @@ pub unsafe trait CAlloc {
 
     /// Get the actual usable size of a previously allocated memory block.
     ///
+    /// The returned size is measured from `ptr`, not from the start of the
+    /// underlying memory block, so it excludes the block header and any
+    /// padding inserted before `ptr` to satisfy the requested alignment.
+    /// It's always equal to or larger than the size requested at allocation
+    /// time, and the caller may use the whole range
+    /// `ptr..ptr + allocation_usable_size(ptr)`.
+    ///
     /// # Safety
     ///
     ///  - `ptr` must denote a memory block previously allocated by calling
@@ -234,16 +841,24 @@ pub unsafe trait CAlloc {
     unsafe fn allocation_usable_size(&self, ptr: NonNull<u8>) -> usize;
 }
 
-unsafe impl<Options: GlobalTlsfOptions> CAlloc for GlobalTlsf<Options> {
+unsafe impl<Options: GlobalTlsfOptions, Lock: RawLock> CAlloc for GlobalTlsf<Options, Lock> {
     fn allocate(&self, layout: alloc::Layout) -> Option<NonNull<u8>> {
-        let mut inner = self.lock_inner();
-        inner.allocate(layout)
+        let padded_layout = Self::padded_layout(layout);
+        let ptr = self
+            .retry_on_oom(|| self.lock_inner().allocate(padded_layout))
+            .map(|ptr| {
+                thread_stats::record_allocation(layout.size());
+                ptr
+            });
+        events::allocation(layout.size(), layout.align(), ptr.is_some());
+        ptr
     }
 
     unsafe fn deallocate(&self, ptr: NonNull<u8>) {
-        let mut inner = self.lock_inner();
         // Safety: `ptr` denotes a previous allocation
-        inner.deallocate_unknown_align(ptr);
+        self.lock_inner().deallocate_unknown_align(ptr);
+        thread_stats::record_deallocation();
+        events::deallocation(None, None);
     }
 
     unsafe fn reallocate(
@@ -251,24 +866,31 @@ unsafe impl<Options: GlobalTlsfOptions> CAlloc for GlobalTlsf<Options> {
         ptr: NonNull<u8>,
         new_layout: alloc::Layout,
     ) -> Option<NonNull<u8>> {
-        let mut inner = self.lock_inner();
-        if let Some(new_ptr) = inner.allocate(new_layout) {
-            // Safety: `ptr` denotes a previous allocation
-            let old_size = TheTlsf::<Options>::size_of_allocation_unknown_align(ptr);
-            // Safety: the previously allocated block cannot overlap the
-            //         newly allocated block.
-            //         The safety contract for `deallocate` must be upheld
-            //         by the caller.
-            ptr::copy_nonoverlapping(
-                ptr.as_ptr(),
-                new_ptr.as_ptr(),
-                new_layout.size().min(old_size),
-            );
-            inner.deallocate_unknown_align(ptr);
-            Some(new_ptr)
-        } else {
-            None
-        }
+        let padded_layout = Self::padded_layout(new_layout);
+        let new_ptr = self
+            .retry_on_oom(|| {
+                let mut inner = self.lock_inner();
+                let new_ptr = inner.allocate(padded_layout)?;
+                // Safety: `ptr` denotes a previous allocation
+                let old_size = TheTlsf::<Options>::size_of_allocation_unknown_align(ptr);
+                // Safety: the previously allocated block cannot overlap the
+                //         newly allocated block.
+                //         The safety contract for `deallocate` must be upheld
+                //         by the caller.
+                ptr::copy_nonoverlapping(
+                    ptr.as_ptr(),
+                    new_ptr.as_ptr(),
+                    new_layout.size().min(old_size),
+                );
+                inner.deallocate_unknown_align(ptr);
+                Some(new_ptr)
+            })
+            .map(|new_ptr| {
+                thread_stats::record_reallocation(new_layout.size());
+                new_ptr
+            });
+        events::reallocation(None, new_layout.size(), new_ptr.is_some());
+        new_ptr
     }
 
     unsafe fn allocation_usable_size(&self, ptr: NonNull<u8>) -> usize {
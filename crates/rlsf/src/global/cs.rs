@@ -0,0 +1,58 @@
+//! The `GlobalTlsf` backend for bare-metal targets, based on the
+//! `critical-section` crate
+use const_default1::ConstDefault;
+use core::{cell::UnsafeCell, marker::PhantomData};
+
+use super::GlobalTlsfOptions;
+
+pub struct Mutex {
+    /// The state returned by `critical_section::acquire`. Only accessed while
+    /// the critical section is held.
+    restore_state: UnsafeCell<critical_section::RestoreState>,
+}
+
+impl ConstDefault for Mutex {
+    const DEFAULT: Self = Self {
+        restore_state: UnsafeCell::new(critical_section::RestoreState::invalid()),
+    };
+}
+
+impl Mutex {
+    #[inline]
+    pub fn lock(&self) {
+        // Safety: `unlock` releases the critical section on the same thread,
+        //         and `GlobalTlsf` doesn't nest the calls
+        unsafe {
+            let restore_state = critical_section::acquire();
+            *self.restore_state.get() = restore_state;
+        }
+    }
+
+    #[inline]
+    pub fn unlock(&self) {
+        // Safety: `restore_state` was stored by the matching call to `lock`
+        unsafe { critical_section::release(*self.restore_state.get()) };
+    }
+}
+
+/// There's no memory source on bare-metal targets. The application provides
+/// memory pools by [`GlobalTlsf::insert_free_block`].
+///
+/// [`GlobalTlsf::insert_free_block`]: super::GlobalTlsf::insert_free_block
+pub struct Source<Options>(PhantomData<fn() -> Options>);
+
+impl<Options> ConstDefault for Source<Options> {
+    const DEFAULT: Self = Self(PhantomData);
+}
+
+unsafe impl<Options: GlobalTlsfOptions> crate::flex::FlexSource for Source<Options> {
+    #[inline]
+    fn is_exhausted(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn max_total_bytes(&self) -> Option<usize> {
+        Some(0)
+    }
+}
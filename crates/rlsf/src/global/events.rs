@@ -0,0 +1,57 @@
+//! `tracing` events for allocation operations
+//!
+//! The events are emitted after the allocator lock is released. A subscriber
+//! might allocate memory while handling an event, and such allocations must
+//! not emit events themselves, or they would recurse indefinitely. This is
+//! prevented by a per-thread flag.
+use core::cell::Cell;
+
+allocator_thread_local! {
+    /// Indicates whether the current thread is emitting an event.
+    static IN_EVENT: Cell<bool> = Cell::new(false);
+}
+
+#[inline]
+fn emit(f: impl FnOnce()) {
+    if !tracing::level_enabled!(tracing::Level::TRACE) {
+        return;
+    }
+
+    // `try_with` fails if the thread-local storage is unavailable (e.g.,
+    // during thread teardown on some platforms), in which case the event is
+    // not emitted
+    let _ = IN_EVENT.try_with(|in_event| {
+        if in_event.replace(true) {
+            // Called by the subscriber of an outer event
+            return;
+        }
+
+        // Clear the flag even if the subscriber panics
+        struct Guard<'a>(&'a Cell<bool>);
+        impl Drop for Guard<'_> {
+            #[inline]
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+        let _guard = Guard(in_event);
+
+        f();
+    });
+}
+
+#[inline]
+pub(super) fn allocation(size: usize, align: usize, success: bool) {
+    emit(|| tracing::trace!(size, align, success, "alloc"));
+}
+
+/// `size` and `align` are `None` if unknown.
+#[inline]
+pub(super) fn deallocation(size: Option<usize>, align: Option<usize>) {
+    emit(|| tracing::trace!(size, align, "dealloc"));
+}
+
+#[inline]
+pub(super) fn reallocation(old_size: Option<usize>, new_size: usize, success: bool) {
+    emit(|| tracing::trace!(old_size, new_size, success, "realloc"));
+}
@@ -0,0 +1,77 @@
+//! Detection of reentrant lock acquisition
+//!
+//! The lock of [`GlobalTlsf`][1] isn't reentrant. If the thread holding it
+//! tries to allocate again from the same instance (e.g., from a custom
+//! [`RawLock`][2] or a panic raised while the lock is held), it would
+//! deadlock silently. A per-thread record of the lock held turns this into
+//! an abort with a diagnostic message.
+//!
+//! Only the lock acquired last is recorded, so the thread can still acquire
+//! the locks of other instances while holding one (provided that they don't
+//! share the underlying lock, as [`PlatformLock`][3] does on Unix).
+//!
+//! [1]: super::GlobalTlsf
+//! [2]: super::RawLock
+//! [3]: super::PlatformLock
+use core::{cell::Cell, ptr};
+
+allocator_thread_local! {
+    /// The address of the `GlobalTlsf` whose lock the current thread
+    /// acquired last and still holds. Null if none.
+    static HELD_LOCK: Cell<*const ()> = Cell::new(ptr::null());
+}
+
+/// The state saved by [`enter`] and restored by [`exit`].
+#[derive(Clone, Copy)]
+pub(super) struct Entered {
+    prev: *const (),
+}
+
+/// Record that the current thread holds the lock of the instance at
+/// `instance`. Returns `None` if it's already recorded so.
+///
+/// Succeeds if the thread-local storage is unavailable (e.g., during
+/// thread teardown on some platforms), in which case reentrancy goes
+/// undetected.
+#[inline]
+pub(super) fn try_enter(instance: *const ()) -> Option<Entered> {
+    HELD_LOCK
+        .try_with(|held_lock| {
+            let prev = held_lock.get();
+            if prev == instance {
+                None
+            } else {
+                held_lock.set(instance);
+                Some(Entered { prev })
+            }
+        })
+        .unwrap_or(Some(Entered { prev: ptr::null() }))
+}
+
+/// Record that the current thread holds the lock of the instance at
+/// `instance`. Aborts the process if it's already recorded so.
+#[inline]
+pub(super) fn enter(instance: *const ()) -> Entered {
+    if let Some(entered) = try_enter(instance) {
+        entered
+    } else {
+        reentered();
+    }
+}
+
+/// Undo the [`enter`] call that returned `entered`.
+#[inline]
+pub(super) fn exit(entered: Entered) {
+    let _ = HELD_LOCK.try_with(|held_lock| held_lock.set(entered.prev));
+}
+
+#[cold]
+fn reentered() -> ! {
+    // Avoid `eprintln!`, which panics if writing to stderr fails
+    let _ = std::io::Write::write_all(
+        &mut std::io::stderr(),
+        b"rlsf: reentrant call to `GlobalTlsf` while its lock is held by the \
+          same thread; aborting to avoid a deadlock\n",
+    );
+    std::process::abort();
+}
@@ -1,4 +1,5 @@
 use quickcheck_macros::quickcheck;
+use core::sync::atomic::{AtomicBool, AtomicUsize};
 use std::{alloc::Layout, prelude::v1::*};
 
 use super::*;
@@ -11,10 +12,10 @@ struct Alloc {
 }
 
 macro_rules! gen_test {
-    ($mod:ident, $($tt:tt)*) => {
+    ($mod:ident, $options:ty $(, $lock:ty)?) => {
         mod $mod {
             use super::*;
-            type TheTlsf = GlobalTlsf<$($tt)*>;
+            type TheTlsf = GlobalTlsf<$options $(, $lock)?>;
 
             #[quickcheck]
             fn calloc_random(bytecode: Vec<u8>) {
@@ -29,6 +30,230 @@ macro_rules! gen_test {
                 }
             }
 
+            #[test]
+            fn oom_hook() {
+                use core::sync::atomic::{AtomicUsize, Ordering};
+                static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+                let tlsf: TheTlsf = TheTlsf::DEFAULT;
+                let huge = Layout::from_size_align(isize::MAX as usize, 1).unwrap();
+
+                // `isize::MAX` bytes fit in the 4-GiB linear memory of wasm32
+                tlsf.set_pool_limit(Some(1 << 20));
+
+                // No hook is registered
+                assert_eq!(CAlloc::allocate(&tlsf, huge), None);
+
+                tlsf.set_oom_hook(|| {
+                    NUM_CALLS.fetch_add(1, Ordering::Relaxed);
+                    true
+                });
+
+                // The hook is called once, and the allocation is retried once
+                assert_eq!(CAlloc::allocate(&tlsf, huge), None);
+                assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 1);
+                assert!(unsafe { alloc::GlobalAlloc::alloc(&tlsf, huge) }.is_null());
+                assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 2);
+
+                // The hook is not called for successful allocations
+                let ptr = CAlloc::allocate(&tlsf, Layout::new::<u64>()).unwrap();
+                assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 2);
+
+                assert_eq!(unsafe { CAlloc::reallocate(&tlsf, ptr, huge) }, None);
+                assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 3);
+                unsafe { CAlloc::deallocate(&tlsf, ptr) };
+            }
+
+            #[test]
+            fn pool_limit() {
+                let tlsf: TheTlsf = TheTlsf::DEFAULT;
+                assert_eq!(tlsf.pool_limit(), None);
+
+                tlsf.set_pool_limit(Some(1 << 20));
+                assert_eq!(tlsf.pool_limit(), Some(1 << 20));
+
+                // Fits in the limit
+                let small = Layout::from_size_align(4096, 8).unwrap();
+                let ptr = CAlloc::allocate(&tlsf, small).unwrap();
+
+                // Exceeds the limit
+                let large = Layout::from_size_align(4 << 20, 8).unwrap();
+                assert_eq!(CAlloc::allocate(&tlsf, large), None);
+
+                unsafe { CAlloc::deallocate(&tlsf, ptr) };
+            }
+
+            #[cfg(unix)]
+            #[test]
+            fn set_pool_limit_from_rlimit() {
+                let tlsf: TheTlsf = TheTlsf::DEFAULT;
+
+                let limit = tlsf.set_pool_limit_from_rlimit(50);
+                match crate::utils::address_space_soft_limit() {
+                    Some(rlimit) => {
+                        assert_eq!(limit, Some(rlimit / 100 * 50));
+                        assert_eq!(tlsf.pool_limit(), limit);
+                    }
+                    None => {
+                        // No limit to derive from; the current one is kept
+                        assert_eq!(limit, None);
+                        assert_eq!(tlsf.pool_limit(), None);
+                    }
+                }
+            }
+
+            #[cfg(feature = "stats")]
+            #[test]
+            fn write_report_from_oom_hook() {
+                use core::fmt::Write;
+                static TLSF: TheTlsf = TheTlsf::DEFAULT;
+                static REPORT: std::sync::Mutex<String> = std::sync::Mutex::new(String::new());
+
+                // A writer that doesn't allocate
+                struct FixedBuf {
+                    buf: [u8; 1024],
+                    len: usize,
+                }
+
+                impl Write for FixedBuf {
+                    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                        let dst = self.buf.get_mut(self.len..self.len + s.len()).ok_or(core::fmt::Error)?;
+                        dst.copy_from_slice(s.as_bytes());
+                        self.len += s.len();
+                        Ok(())
+                    }
+                }
+
+                let ptr = CAlloc::allocate(&TLSF, Layout::new::<u64>()).unwrap();
+
+                TLSF.set_oom_hook(|| {
+                    // The lock is not held while the hook is running
+                    let mut buf = FixedBuf { buf: [0; 1024], len: 0 };
+                    TLSF.write_report(&mut buf).unwrap();
+                    *REPORT.lock().unwrap() = std::str::from_utf8(&buf.buf[..buf.len]).unwrap().to_owned();
+                    false
+                });
+
+                let huge = Layout::from_size_align(isize::MAX as usize, 1).unwrap();
+                TLSF.set_pool_limit(Some(1 << 20));
+                assert!(unsafe { alloc::GlobalAlloc::alloc(&TLSF, huge) }.is_null());
+
+                let report = REPORT.lock().unwrap().clone();
+                log::trace!("report = {}", report);
+                assert!(report.starts_with("resident: "));
+                assert!(report.contains("capacity: "));
+                assert!(report.contains("fragmentation: "));
+
+                unsafe { CAlloc::deallocate(&TLSF, ptr) };
+            }
+
+            #[cfg(all(feature = "stats", feature = "std", not(target_arch = "wasm32")))]
+            #[test]
+            fn thread_stats() {
+                static TLSF: TheTlsf = TheTlsf::DEFAULT;
+                let tlsf = &TLSF;
+                let layout = Layout::new::<u64>();
+                let before = tlsf.thread_stats();
+
+                let ptr = unsafe { alloc::GlobalAlloc::alloc(tlsf, layout) };
+                assert!(!ptr.is_null());
+                let ptr = unsafe { alloc::GlobalAlloc::realloc(tlsf, ptr, layout, 64) };
+                assert!(!ptr.is_null());
+                unsafe { alloc::GlobalAlloc::dealloc(tlsf, ptr, Layout::from_size_align(64, 8).unwrap()) };
+
+                let ptr = CAlloc::allocate(tlsf, layout).unwrap();
+                unsafe { CAlloc::deallocate(tlsf, ptr) };
+
+                let after = tlsf.thread_stats();
+                assert_eq!(after.num_allocations - before.num_allocations, 2);
+                assert_eq!(after.num_deallocations - before.num_deallocations, 2);
+                assert_eq!(after.num_reallocations - before.num_reallocations, 1);
+                assert_eq!(after.bytes_allocated - before.bytes_allocated, 8 + 64 + 8);
+
+                // Other threads have their own counters
+                let other = std::thread::spawn(move || {
+                    let ptr = CAlloc::allocate(&TLSF, layout).unwrap();
+                    unsafe { CAlloc::deallocate(&TLSF, ptr) };
+                    TLSF.thread_stats()
+                })
+                .join()
+                .unwrap();
+                assert_eq!(other.num_allocations, 1);
+                assert_eq!(tlsf.thread_stats(), after);
+            }
+
+            #[quickcheck]
+            fn calloc_reallocate_preserves_contents(steps: Vec<(u16, u8)>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let tlsf: TheTlsf = TheTlsf::DEFAULT;
+                let fill = |ptr: NonNull<u8>, range: std::ops::Range<usize>| {
+                    for i in range {
+                        unsafe { *ptr.as_ptr().add(i) = (i as u8).reverse_bits() ^ 0x5a };
+                    }
+                };
+
+                let mut size = 1;
+                let mut ptr = CAlloc::allocate(&tlsf, Layout::from_size_align(size, 1).unwrap()).unwrap();
+                fill(ptr, 0..size);
+
+                for (new_size, align_log2) in steps {
+                    // Include sizes larger than a page
+                    let new_size = new_size as usize * 3;
+                    let new_layout = Layout::from_size_align(new_size, 1 << (align_log2 % 8)).unwrap();
+                    log::trace!("realloc {:?} ({} bytes) to {:?}", ptr, size, new_layout);
+
+                    let new_ptr = if let Some(x) = unsafe { CAlloc::reallocate(&tlsf, ptr, new_layout) } {
+                        x
+                    } else {
+                        continue;
+                    };
+                    assert_eq!(new_ptr.as_ptr() as usize % new_layout.align(), 0);
+                    assert!(unsafe { CAlloc::allocation_usable_size(&tlsf, new_ptr) } >= new_size);
+
+                    // The first `min(size, new_size)` bytes must be preserved
+                    for i in 0..size.min(new_size) {
+                        assert_eq!(
+                            unsafe { *new_ptr.as_ptr().add(i) },
+                            (i as u8).reverse_bits() ^ 0x5a,
+                            "byte {} was not preserved",
+                            i,
+                        );
+                    }
+                    if new_size > size {
+                        fill(new_ptr, size..new_size);
+                    }
+
+                    ptr = new_ptr;
+                    size = new_size;
+                }
+
+                unsafe { CAlloc::deallocate(&tlsf, ptr) };
+            }
+
+            #[test]
+            fn realloc_within_block() {
+                let tlsf: TheTlsf = TheTlsf::DEFAULT;
+                let layout = Layout::from_size_align(17, 1).unwrap();
+
+                let ptr = unsafe { alloc::GlobalAlloc::alloc(&tlsf, layout) };
+                assert!(!ptr.is_null());
+                let usable_size =
+                    unsafe { CAlloc::allocation_usable_size(&tlsf, NonNull::new(ptr).unwrap()) };
+                if usize::BITS >= 64 {
+                    assert!(usable_size >= 32);
+                }
+
+                let new_ptr = unsafe { alloc::GlobalAlloc::realloc(&tlsf, ptr, layout, usable_size) };
+                if <$options as GlobalTlsfOptions>::ENABLE_REALLOCATION {
+                    assert_eq!(new_ptr, ptr);
+                }
+                assert!(!new_ptr.is_null());
+
+                let layout = Layout::from_size_align(usable_size, 1).unwrap();
+                unsafe { alloc::GlobalAlloc::dealloc(&tlsf, new_ptr, layout) };
+            }
+
             fn calloc_random_inner(tlsf: &TheTlsf, allocs: &mut Vec<Alloc>, bytecode: Vec<u8>) -> Option<()> {
                 let mut sa = ShadowAllocator::new_filled_with_free();
 
@@ -106,5 +331,297 @@ macro_rules! gen_test {
     };
 }
 
+#[derive(Debug)]
+struct CacheLineGlobalTlsfOptions;
+
+impl GlobalTlsfOptions for CacheLineGlobalTlsfOptions {
+    const CACHE_LINE_ALIGN: bool = true;
+    const CACHE_LINE_SIZE: usize = 128;
+    const CACHE_LINE_ALIGN_THRESHOLD: usize = 64;
+}
+
+/// A user-provided [`RawLock`]. Counts the acquisitions to verify that
+/// `GlobalTlsf` uses it.
+#[derive(Debug)]
+struct SpinLock {
+    locked: AtomicBool,
+    num_acquisitions: AtomicUsize,
+}
+
+unsafe impl RawLock for SpinLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self {
+        locked: AtomicBool::new(false),
+        num_acquisitions: AtomicUsize::new(0),
+    };
+
+    fn lock(&self) {
+        while self.locked.swap(true, Ordering::Acquire) {
+            core::hint::spin_loop();
+        }
+        self.num_acquisitions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
 gen_test!(default_globaltlsf, ());
 gen_test!(small_globaltlsf, SmallGlobalTlsfOptions);
+gen_test!(cache_line_globaltlsf, CacheLineGlobalTlsfOptions);
+gen_test!(spin_lock_globaltlsf, (), SpinLock);
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn custom_lock() {
+    static TLSF: GlobalTlsf<(), SpinLock> = GlobalTlsf::new();
+    let layout = Layout::from_size_align(24, 8).unwrap();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(move || {
+                for _ in 0..1000 {
+                    let ptr = CAlloc::allocate(&TLSF, layout).unwrap();
+                    unsafe { CAlloc::deallocate(&TLSF, ptr) };
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(TLSF.mutex.num_acquisitions.load(Ordering::Relaxed), 8000);
+}
+
+#[test]
+fn cache_line_align() {
+    let tlsf: GlobalTlsf<CacheLineGlobalTlsfOptions> = GlobalTlsf::new();
+    let small = Layout::from_size_align(63, 1).unwrap();
+    let large = Layout::from_size_align(64, 1).unwrap();
+
+    unsafe {
+        let ptrs: Vec<_> = (0..8)
+            .map(|_| alloc::GlobalAlloc::alloc(&tlsf, large))
+            .collect();
+        for &ptr in ptrs.iter() {
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % 128, 0);
+            assert!(CAlloc::allocation_usable_size(&tlsf, NonNull::new(ptr).unwrap()) >= 128);
+        }
+
+        // Cross the threshold in both directions
+        let ptr = alloc::GlobalAlloc::realloc(&tlsf, ptrs[0], large, small.size());
+        assert!(!ptr.is_null());
+        let ptr = alloc::GlobalAlloc::realloc(&tlsf, ptr, small, 200);
+        assert!(!ptr.is_null());
+        assert_eq!(ptr as usize % 128, 0);
+        assert!(CAlloc::allocation_usable_size(&tlsf, NonNull::new(ptr).unwrap()) >= 256);
+        alloc::GlobalAlloc::dealloc(&tlsf, ptr, Layout::from_size_align(200, 1).unwrap());
+
+        for &ptr in ptrs[1..].iter() {
+            alloc::GlobalAlloc::dealloc(&tlsf, ptr, large);
+        }
+
+        // Allocations below the threshold aren't padded
+        let ptr = CAlloc::allocate(&tlsf, small).unwrap();
+        assert!(CAlloc::allocation_usable_size(&tlsf, ptr) < 128);
+        CAlloc::deallocate(&tlsf, ptr);
+    }
+}
+
+#[derive(Debug)]
+struct LargeObjectGlobalTlsfOptions;
+
+impl GlobalTlsfOptions for LargeObjectGlobalTlsfOptions {
+    const LARGE_OBJECT_THRESHOLD: Option<usize> = Some(4096);
+}
+
+#[test]
+fn large_object_heap() {
+    let tlsf: GlobalTlsf<LargeObjectGlobalTlsfOptions> = GlobalTlsf::new();
+    let small = Layout::from_size_align(100, 8).unwrap();
+    let large = Layout::from_size_align(10000, 8).unwrap();
+
+    unsafe {
+        let small_ptr = alloc::GlobalAlloc::alloc(&tlsf, small);
+        assert!(!small_ptr.is_null());
+        // The large object heap is created on demand
+        assert!(tlsf.lock_inner().large_heap().is_none());
+
+        let large_ptrs: Vec<_> = (0..4)
+            .map(|i| {
+                let ptr = alloc::GlobalAlloc::alloc(&tlsf, large);
+                assert!(!ptr.is_null());
+                ptr.write_bytes(i, large.size());
+                ptr
+            })
+            .collect();
+        assert!(tlsf.lock_inner().large_heap().is_some());
+
+        // Cross the threshold in both directions. The contents must be
+        // preserved.
+        let ptr = alloc::GlobalAlloc::realloc(&tlsf, large_ptrs[0], large, 1000);
+        assert!(!ptr.is_null());
+        assert!((0..1000).all(|i| *ptr.add(i) == 0));
+        let ptr = alloc::GlobalAlloc::realloc(
+            &tlsf,
+            ptr,
+            Layout::from_size_align(1000, 8).unwrap(),
+            20000,
+        );
+        assert!(!ptr.is_null());
+        assert!((0..1000).all(|i| *ptr.add(i) == 0));
+        alloc::GlobalAlloc::dealloc(&tlsf, ptr, Layout::from_size_align(20000, 8).unwrap());
+
+        for (i, &ptr) in large_ptrs.iter().enumerate().skip(1) {
+            assert!((0..large.size()).all(|k| *ptr.add(k) == i as u8));
+            alloc::GlobalAlloc::dealloc(&tlsf, ptr, large);
+        }
+        alloc::GlobalAlloc::dealloc(&tlsf, small_ptr, small);
+
+        // `CAlloc` always uses the main heap
+        let ptr = CAlloc::allocate(&tlsf, large).unwrap();
+        CAlloc::deallocate(&tlsf, ptr);
+    }
+}
+
+/// `GlobalTlsf` on WebAssembly extends the last memory pool with
+/// `memory.grow` instead of creating a new one when it can
+#[cfg(target_arch = "wasm32")]
+#[test]
+fn wasm32_grow_pool_in_place() {
+    use core::arch::wasm32;
+
+    let tlsf: GlobalTlsf = GlobalTlsf::new();
+    let small = Layout::from_size_align(64, 8).unwrap();
+    let large = Layout::from_size_align(256 * 1024, 8).unwrap();
+
+    unsafe {
+        let ptr1 = alloc::GlobalAlloc::alloc(&tlsf, small);
+        assert!(!ptr1.is_null());
+        let memory_end = wasm32::memory_size(0) * 65536;
+
+        // This doesn't fit in the first memory pool. A new memory pool would
+        // start at `memory_end`, whereas an extended one has its free space
+        // before `memory_end` merged with the appended part.
+        let ptr2 = alloc::GlobalAlloc::alloc(&tlsf, large);
+        assert!(!ptr2.is_null());
+        assert!((ptr2 as usize) < memory_end);
+
+        alloc::GlobalAlloc::dealloc(&tlsf, ptr2, large);
+        alloc::GlobalAlloc::dealloc(&tlsf, ptr1, small);
+    }
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+#[test]
+fn reentrancy_detection() {
+    // `PlatformLock` is process-wide on Unix, so give each instance its own
+    // lock to nest them
+    let tlsf: GlobalTlsf<(), SpinLock> = GlobalTlsf::new();
+    let tlsf2: GlobalTlsf<(), SpinLock> = GlobalTlsf::new();
+    let instance = &tlsf as *const _ as *const ();
+    let instance2 = &tlsf2 as *const _ as *const ();
+
+    {
+        let _guard = tlsf.lock_inner();
+        // `lock_inner` would abort here
+        assert!(reentrancy::try_enter(instance).is_none());
+
+        // Other instances can be locked while holding the lock
+        {
+            let _guard2 = tlsf2.lock_inner();
+            assert!(reentrancy::try_enter(instance2).is_none());
+        }
+
+        // Releasing the inner lock restores the record of the outer one
+        assert!(reentrancy::try_enter(instance).is_none());
+    }
+
+    // The record is cleared when the lock is released
+    let entered = reentrancy::try_enter(instance).unwrap();
+    reentrancy::exit(entered);
+
+    // The record is per-thread
+    let _guard = tlsf.lock_inner();
+    let instance = instance as usize;
+    std::thread::spawn(move || {
+        let entered = reentrancy::try_enter(instance as *const ()).unwrap();
+        reentrancy::exit(entered);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(all(feature = "tracing", feature = "std"))]
+#[test]
+fn tracing_events() {
+    use std::{
+        fmt::{self, Write},
+        sync::{Arc, Mutex},
+    };
+    use tracing::{field, span, Event, Metadata, Subscriber};
+
+    static TLSF: GlobalTlsf = GlobalTlsf::new();
+
+    /// Records the fields of the events. Allocates memory from `TLSF` while
+    /// handling an event, which must neither deadlock nor emit another event.
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct FieldVisitor<'a>(&'a mut String);
+
+    impl field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target().starts_with("rlsf")
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let layout = Layout::new::<u64>();
+            unsafe {
+                let ptr = alloc::GlobalAlloc::alloc(&TLSF, layout);
+                alloc::GlobalAlloc::dealloc(&TLSF, ptr, layout);
+            }
+
+            let mut fields = String::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.0.lock().unwrap().push(fields);
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    tracing::subscriber::with_default(Recorder(Arc::clone(&events)), || unsafe {
+        let layout = Layout::from_size_align(24, 8).unwrap();
+        let ptr = alloc::GlobalAlloc::alloc(&TLSF, layout);
+        let ptr = alloc::GlobalAlloc::realloc(&TLSF, ptr, layout, 100);
+        let layout = Layout::from_size_align(100, 8).unwrap();
+        alloc::GlobalAlloc::dealloc(&TLSF, ptr, layout);
+
+        let ptr = CAlloc::allocate(&TLSF, layout).unwrap();
+        CAlloc::deallocate(&TLSF, ptr);
+    });
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            " message=alloc size=24 align=8 success=true",
+            " message=realloc old_size=24 new_size=100 success=true",
+            " message=dealloc size=100 align=8",
+            " message=alloc size=100 align=8 success=true",
+            " message=dealloc",
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+//! Per-thread allocation counters
+use core::cell::Cell;
+
+/// Allocation counters of a thread, returned by
+/// [`GlobalTlsf::thread_stats`][1].
+///
+/// The counters are shared by all instances of [`GlobalTlsf`][2] and wrap
+/// around on overflow.
+///
+/// [1]: super::GlobalTlsf::thread_stats
+/// [2]: super::GlobalTlsf
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(all(feature = "stats", feature = "std"))))]
+pub struct ThreadStats {
+    /// The number of successful allocations.
+    pub num_allocations: usize,
+    /// The number of deallocations.
+    pub num_deallocations: usize,
+    /// The number of successful reallocations.
+    pub num_reallocations: usize,
+    /// The total number of bytes requested by successful allocations and
+    /// reallocations.
+    pub bytes_allocated: usize,
+}
+
+allocator_thread_local! {
+    static STATS: Cell<ThreadStats> = Cell::new(ThreadStats {
+        num_allocations: 0,
+        num_deallocations: 0,
+        num_reallocations: 0,
+        bytes_allocated: 0,
+    });
+}
+
+#[inline]
+fn update(f: impl FnOnce(&mut ThreadStats)) {
+    // `try_with` fails if the thread-local storage is unavailable (e.g.,
+    // during thread teardown on some platforms), in which case the event is
+    // not counted
+    let _ = STATS.try_with(|stats| {
+        let mut x = stats.get();
+        f(&mut x);
+        stats.set(x);
+    });
+}
+
+#[inline]
+pub(super) fn get() -> ThreadStats {
+    STATS.try_with(Cell::get).unwrap_or_default()
+}
+
+#[inline]
+pub(super) fn record_allocation(size: usize) {
+    update(|stats| {
+        stats.num_allocations = stats.num_allocations.wrapping_add(1);
+        stats.bytes_allocated = stats.bytes_allocated.wrapping_add(size);
+    });
+}
+
+#[inline]
+pub(super) fn record_deallocation() {
+    update(|stats| stats.num_deallocations = stats.num_deallocations.wrapping_add(1));
+}
+
+#[inline]
+pub(super) fn record_reallocation(new_size: usize) {
+    update(|stats| {
+        stats.num_reallocations = stats.num_reallocations.wrapping_add(1);
+        stats.bytes_allocated = stats.bytes_allocated.wrapping_add(new_size);
+    });
+}
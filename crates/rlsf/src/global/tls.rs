@@ -0,0 +1,23 @@
+//! Thread-local storage usable from the global allocator
+
+/// Declare a thread-local variable with a `const` initializer, like
+/// `std::thread_local!`.
+///
+/// The variable's type must not need to be dropped. Thread-local variables
+/// with destructors can't be accessed in some stages of a thread's lifetime
+/// (e.g., after their destructors ran), whereas the global allocator can be
+/// called at any point, including thread teardown.
+macro_rules! allocator_thread_local {
+    ($(#[$attr:meta])* static $name:ident: $ty:ty = $init:expr;) => {
+        std::thread_local! {
+            $(#[$attr])*
+            #[allow(clippy::declare_interior_mutable_const)] // false positive
+            static $name: $ty = const { $init };
+        }
+
+        const _: () = assert!(
+            !core::mem::needs_drop::<$ty>(),
+            "allocator thread-local variables must not have a destructor"
+        );
+    };
+}
@@ -2,6 +2,7 @@ use const_default1::ConstDefault;
 use core::{
     marker::PhantomData,
     ptr::{null_mut, NonNull},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use super::GlobalTlsfOptions;
@@ -39,29 +40,33 @@ impl<Options> ConstDefault for Source<Options> {
     const DEFAULT: Self = Self(PhantomData);
 }
 
-/// The memory page size minus 1. Set by `Mutex::lock`.
-static mut PAGE_SIZE_M1: usize = 0;
+/// The memory page size minus 1, or `0` if it's not known yet.
+/// Initialized by [`ensure_page_size_m1`].
+///
+/// Any thread may initialize this concurrently. This is benign because they
+/// all store the same value, so `Relaxed` ordering suffices.
+static PAGE_SIZE_M1: AtomicUsize = AtomicUsize::new(0);
+
 #[cold]
 fn init_page_size() -> usize {
-    unsafe {
-        let page_size = (libc::sysconf(libc::_SC_PAGESIZE) as usize).max(ALLOC_UNIT);
-        if !page_size.is_power_of_two() {
-            libc::abort();
-        }
-        PAGE_SIZE_M1 = page_size - 1;
-
-        // Such a small memory page size is quite unusual.
-        if page_size < MIN_ALIGN {
-            libc::abort();
-        }
+    let page_size = (unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize).max(ALLOC_UNIT);
+    if !page_size.is_power_of_two() {
+        unsafe { libc::abort() };
+    }
 
-        PAGE_SIZE_M1
+    // Such a small memory page size is quite unusual.
+    if page_size < MIN_ALIGN {
+        unsafe { libc::abort() };
     }
+
+    let page_size_m1 = page_size - 1;
+    PAGE_SIZE_M1.store(page_size_m1, Ordering::Relaxed);
+    page_size_m1
 }
 
 #[inline]
 fn ensure_page_size_m1() -> usize {
-    let page_size_m1 = unsafe { PAGE_SIZE_M1 };
+    let page_size_m1 = PAGE_SIZE_M1.load(Ordering::Relaxed);
     if page_size_m1 == 0 {
         // `init_page_size` returns the initialized value for
         // code size optimization
@@ -143,6 +148,29 @@ unsafe impl<Options: GlobalTlsfOptions> crate::flex::FlexSource for Source<Optio
         Options::COALESCE_POOLS
     }
 
+    #[inline]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe fn prefault(&mut self, ptr: NonNull<[u8]>) {
+        // Safety: `ptr` is in a writable private anonymous mapping created by
+        //         `alloc` or `realloc_inplace_grow`
+        crate::utils::madvise_populate_write(ptr);
+    }
+
+    #[inline]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    unsafe fn discard(&mut self, ptr: NonNull<[u8]>) {
+        // Safety: `ptr` is in a writable private anonymous mapping created by
+        //         `alloc` or `realloc_inplace_grow`, and `FlexTlsf` doesn't
+        //         need its contents
+        crate::utils::madvise_free(ptr);
+    }
+
+    #[inline]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn supports_discard(&self) -> bool {
+        true
+    }
+
     // Not implementing `dealloc` because there is no safe way to destruct
     // a registered global allocator anyway.
 
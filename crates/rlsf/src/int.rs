@@ -2,6 +2,25 @@
 #![allow(unstable_name_collisions)] // `$ty::BITS`
 use core::{fmt, marker, ops};
 
+/// Use the software implementations of [`BinInteger::trailing_zeros`] and
+/// [`BinInteger::leading_zeros`] (see [`soft`]) instead of the built-in
+/// methods.
+///
+/// This is enabled on targets lacking a count-leading-zeros instruction,
+/// where the built-in methods compile to calls to compiler-rt helpers (e.g.,
+/// `__clzsi2`), which are considerably slower than a table lookup:
+///
+///  - ARM in the Thumb mode without Thumb-2, i.e., ARMv6-M and ARMv8-M
+///    Baseline (detected by the build script from the target name)
+///  - RISC-V without the Zbb extension
+const SOFT_BIT_SCAN: bool = cfg!(any(
+    rlsf_soft_bit_scan,
+    all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        not(target_feature = "zbb")
+    ),
+));
+
 /// Integral types with efficient binary operations.
 pub trait BinInteger:
     Clone
@@ -91,11 +110,19 @@ macro_rules! impl_binary_integer {
             }
             #[inline]
             fn trailing_zeros(&self) -> u32 {
-                (*self).trailing_zeros()
+                if SOFT_BIT_SCAN {
+                    soft::trailing_zeros(*self as u128, Self::BITS)
+                } else {
+                    (*self).trailing_zeros()
+                }
             }
             #[inline]
             fn leading_zeros(&self) -> u32 {
-                (*self).leading_zeros()
+                if SOFT_BIT_SCAN {
+                    soft::leading_zeros(*self as u128, Self::BITS)
+                } else {
+                    (*self).leading_zeros()
+                }
             }
             #[inline]
             fn count_ones(&self) -> u32 {
@@ -106,7 +133,7 @@ macro_rules! impl_binary_integer {
                 if start >= Self::BITS {
                     Self::BITS
                 } else {
-                    (*self & !Self::ones(0..start)).trailing_zeros()
+                    BinInteger::trailing_zeros(&(*self & !Self::ones(0..start)))
                 }
             }
             #[inline]
@@ -165,6 +192,93 @@ impl_binary_integer!(u64);
 impl_binary_integer!(u128);
 impl_binary_integer!(usize);
 
+/// Branchless bit scan based on de Bruijn sequences, used if
+/// [`SOFT_BIT_SCAN`] is set
+mod soft {
+    /// A de Bruijn sequence B(2, 5)
+    const DE_BRUIJN: u32 = 0x077c_b531;
+
+    /// Maps `(x * DE_BRUIJN) >> 27` to `log2(x)` for each power of two `x`
+    const CTZ_TABLE: [u8; 32] = [
+        0, 1, 28, 2, 29, 14, 24, 3, 30, 22, 20, 15, 25, 17, 4, 8, 31, 27, 13, 23, 21, 19, 16, 7,
+        26, 12, 18, 6, 11, 5, 10, 9,
+    ];
+
+    /// A multiplier mapping `2^(k + 1) - 1` to a distinct value in the
+    /// upper five bits for each `k` in `0..32`
+    const CLZ_MULTIPLIER: u32 = 0x07c4_acdd;
+
+    /// Maps `((2^(k + 1) - 1) * CLZ_MULTIPLIER) >> 27` to `31 - k`
+    const CLZ_TABLE: [u8; 32] = [
+        31, 22, 30, 21, 18, 10, 29, 2, 20, 17, 15, 13, 9, 6, 28, 1, 23, 19, 11, 3, 16, 14, 7, 24,
+        12, 4, 8, 25, 5, 26, 27, 0,
+    ];
+
+    #[inline]
+    pub(super) fn trailing_zeros32(x: u32) -> u32 {
+        // Isolate the least significant set bit
+        let i = (x & x.wrapping_neg()).wrapping_mul(DE_BRUIJN) >> 27;
+        if x == 0 {
+            32
+        } else {
+            CTZ_TABLE[i as usize] as u32
+        }
+    }
+
+    #[inline]
+    pub(super) fn leading_zeros32(mut x: u32) -> u32 {
+        // Set all bits below the most significant set bit
+        x |= x >> 1;
+        x |= x >> 2;
+        x |= x >> 4;
+        x |= x >> 8;
+        x |= x >> 16;
+        let i = x.wrapping_mul(CLZ_MULTIPLIER) >> 27;
+        if x == 0 {
+            32
+        } else {
+            CLZ_TABLE[i as usize] as u32
+        }
+    }
+
+    /// Count the trailing zeros in the lower `bits` bits of `x`, 32 bits at
+    /// a time. `bits` is a constant in practice, so the loop is unrolled,
+    /// and the 128-bit shifts are reduced to the type's native width.
+    #[inline]
+    pub(super) fn trailing_zeros(x: u128, bits: u32) -> u32 {
+        let mut i = 0;
+        while i < bits {
+            let mut word = (x >> i) as u32;
+            if bits - i < 32 {
+                // Stop at `bits`
+                word |= 1 << (bits - i);
+            }
+            if word != 0 {
+                return i + trailing_zeros32(word);
+            }
+            i += 32;
+        }
+        bits
+    }
+
+    /// Count the leading zeros in the lower `bits` bits of `x`, 32 bits at
+    /// a time. See [`trailing_zeros`].
+    #[inline]
+    pub(super) fn leading_zeros(x: u128, bits: u32) -> u32 {
+        let mut i = bits;
+        while i > 0 {
+            let width = i.min(32);
+            i -= width;
+            // Discard the bits above `bits`
+            let word = (x >> i) as u32 & (u32::MAX >> (32 - width));
+            if word != 0 {
+                return bits - i - width + leading_zeros32(word) - (32 - width);
+            }
+        }
+        bits
+    }
+}
+
 /// Implements [the sealed trait pattern], which protects [`BinInteger`] against
 /// downstream implementations.
 ///
@@ -172,3 +286,6 @@ impl_binary_integer!(usize);
 mod private {
     pub trait Sealed {}
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,170 @@
+//! Checks that the bit positions used by [`BinInteger`] follow the numeric
+//! value of the integer (bit `i` has the weight `2^i`) and not its in-memory
+//! representation. The reference model reads the bits out of the
+//! little-endian byte representation, so these tests also hold on
+//! big-endian targets only if no operation depends on the native byte order.
+//!
+//! The operations are checked exhaustively for `u8` and on random inputs for
+//! the wider types.
+use quickcheck_macros::quickcheck;
+
+use super::*;
+
+macro_rules! gen_test {
+    ($mod:ident, $ty:ty $(, exhaustive: $exhaustive_ty:ty)?) => {
+        mod $mod {
+            use super::*;
+
+            const BITS: u32 = <$ty as BinInteger>::BITS;
+
+            /// Get bit `i` of `x` from its little-endian byte representation
+            fn model_get_bit(x: $ty, i: u32) -> bool {
+                let bytes = x.to_le_bytes();
+                i < BITS && (bytes[i as usize / 8] >> (i % 8)) & 1 != 0
+            }
+
+            fn check_get_bit(x: $ty, i: u32) {
+                assert_eq!(BinInteger::get_bit(&x, i), model_get_bit(x, i), "{:?}", (x, i));
+            }
+
+            fn check_set_clear_bit(x: $ty, i: u32) {
+                let mut y = x;
+                BinInteger::set_bit(&mut y, i);
+                let mut z = x;
+                BinInteger::clear_bit(&mut z, i);
+
+                for k in 0..BITS {
+                    if k == i {
+                        assert!(model_get_bit(y, k), "{:?}", (x, i));
+                        assert!(!model_get_bit(z, k), "{:?}", (x, i));
+                    } else {
+                        assert_eq!(model_get_bit(y, k), model_get_bit(x, k), "{:?}", (x, i));
+                        assert_eq!(model_get_bit(z, k), model_get_bit(x, k), "{:?}", (x, i));
+                    }
+                }
+            }
+
+            fn check_bit_scan_forward(x: $ty, start: u32) {
+                let expected = (start..BITS).find(|&k| model_get_bit(x, k)).unwrap_or(BITS);
+                assert_eq!(BinInteger::bit_scan_forward(&x, start), expected, "{:?}", (x, start));
+            }
+
+            fn check_trailing_leading_zeros_count_ones(x: $ty) {
+                let trailing = (0..BITS).find(|&k| model_get_bit(x, k)).unwrap_or(BITS);
+                let leading = (0..BITS)
+                    .rev()
+                    .find(|&k| model_get_bit(x, k))
+                    .map_or(BITS, |k| BITS - 1 - k);
+                let ones = (0..BITS).filter(|&k| model_get_bit(x, k)).count() as u32;
+                assert_eq!(BinInteger::trailing_zeros(&x), trailing, "{:?}", x);
+                assert_eq!(BinInteger::leading_zeros(&x), leading, "{:?}", x);
+                assert_eq!(BinInteger::count_ones(&x), ones, "{:?}", x);
+
+                // `soft` is only used on some targets; test it everywhere
+                assert_eq!(soft::trailing_zeros(x as u128, BITS), trailing, "{:?}", x);
+                assert_eq!(soft::leading_zeros(x as u128, BITS), leading, "{:?}", x);
+            }
+
+            fn check_ones_extract_u32(x: $ty, start: u32, end: u32) {
+                let ones = <$ty as BinInteger>::ones(start..end);
+                for k in 0..BITS {
+                    assert_eq!(model_get_bit(ones, k), (start..end).contains(&k));
+                }
+
+                let extracted = BinInteger::extract_u32(&x, start..end);
+                for k in 0..32 {
+                    assert_eq!(
+                        (extracted >> k) & 1 != 0,
+                        start + k < end && model_get_bit(x, start + k),
+                        "{:?}",
+                        (x, start, end),
+                    );
+                }
+            }
+
+            #[quickcheck]
+            fn get_bit(x: $ty, i: u32) {
+                check_get_bit(x, i % BITS);
+            }
+
+            #[quickcheck]
+            fn set_clear_bit(x: $ty, i: u32) {
+                check_set_clear_bit(x, i % BITS);
+            }
+
+            #[quickcheck]
+            fn bit_scan_forward(x: $ty, start: u32) {
+                check_bit_scan_forward(x, start % (BITS + 1));
+            }
+
+            #[quickcheck]
+            fn trailing_leading_zeros(x: $ty) {
+                check_trailing_leading_zeros_count_ones(x);
+            }
+
+            #[quickcheck]
+            fn ones_extract_u32(x: $ty, start: u32, len: u32) {
+                let start = start % BITS;
+                let end = (start + len % 33).min(BITS);
+                check_ones_extract_u32(x, start, end);
+            }
+
+            $(
+                /// Every value of the type. `$exhaustive_ty` is the next wider
+                /// type so that the range can include `<$ty>::MAX`.
+                fn all_values() -> impl Iterator<Item = $ty> {
+                    (0..=<$ty>::MAX as $exhaustive_ty).map(|x| x as $ty)
+                }
+
+                #[test]
+                fn exhaustive_bit_ops() {
+                    for x in all_values() {
+                        // Include out-of-range bit positions, which
+                        // `get_bit`, `set_bit`, and `clear_bit` accept
+                        for i in 0..BITS + 2 {
+                            check_get_bit(x, i);
+                            check_set_clear_bit(x, i);
+                        }
+                        for start in 0..=BITS + 1 {
+                            check_bit_scan_forward(x, start);
+                        }
+                        check_trailing_leading_zeros_count_ones(x);
+                    }
+                }
+
+                #[test]
+                fn exhaustive_ones_extract_u32() {
+                    for x in all_values() {
+                        for start in 0..BITS {
+                            for end in start..=(start + 32).min(BITS) {
+                                check_ones_extract_u32(x, start, end);
+                            }
+                        }
+                    }
+                }
+            )?
+        }
+    };
+}
+
+#[quickcheck]
+fn soft_bit_scan32(x: u32, shift: u32) {
+    // Include values with few set bits
+    for x in [
+        x,
+        x >> (shift % 32),
+        x << (shift % 32),
+        1 << (shift % 32),
+        0,
+    ] {
+        assert_eq!(soft::trailing_zeros32(x), x.trailing_zeros(), "{:?}", x);
+        assert_eq!(soft::leading_zeros32(x), x.leading_zeros(), "{:?}", x);
+    }
+}
+
+gen_test!(test_u8, u8, exhaustive: u16);
+gen_test!(test_u16, u16);
+gen_test!(test_u32, u32);
+gen_test!(test_u64, u64);
+gen_test!(test_u128, u128);
+gen_test!(test_usize, usize);
@@ -6,16 +6,43 @@
 #[doc = include_str!("../CHANGELOG.md")]
 pub mod _changelog_ {}
 
+#[cfg(feature = "alloc")]
+mod boxed;
+#[cfg(feature = "capi")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "capi")))]
+pub mod capi;
+#[cfg(feature = "allocator-api2")]
+mod cell;
+#[cfg(feature = "fault-injection")]
+mod fault;
 mod flex;
 pub mod int;
+#[cfg(target_has_atomic = "ptr")]
+mod owned;
+mod scoped;
+mod tagged;
 mod tlsf;
 mod utils;
 pub use self::{
     flex::*,
-    tlsf::{Tlsf, GRANULARITY},
+    scoped::*,
+    tagged::*,
+    tlsf::{Stats, Tlsf, GRANULARITY, POOL_OVERHEAD},
 };
+#[cfg(feature = "alloc")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "alloc")))]
+pub use boxed::BoxedTlsf;
+#[cfg(feature = "allocator-api2")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "allocator-api2")))]
+pub use cell::TlsfCell;
+#[cfg(feature = "fault-injection")]
+pub use fault::FailPolicy;
+#[cfg(target_has_atomic = "ptr")]
+pub use owned::*;
+#[cfg(feature = "stats")]
+pub use tlsf::CoalesceStats;
 #[cfg(feature = "unstable")]
-pub use tlsf::BlockInfo;
+pub use tlsf::{AllocationInfo, BlockInfo, DrainAllocations};
 
 /// Attaches `#[cfg(...)]` and `#[doc(cfg(...))]` to a given item definition
 /// to conditionally compile it only when we have a `GlobalTlsf` implementation
@@ -27,6 +54,7 @@ macro_rules! if_supported_target {
         #[cfg(any(
             all(target_arch = "wasm32", not(target_feature = "atomics")),
             unix,
+            feature = "critical-section",
             doc,
         ))]
         #[cfg_attr(
@@ -34,6 +62,7 @@ macro_rules! if_supported_target {
             doc(cfg(any(
                 all(target_arch = "wasm32", not(target_feature = "atomics")),
                 unix,
+                feature = "critical-section",
                 // no `doc` here
             )))
         )]
@@ -47,5 +76,8 @@ if_supported_target! { pub use self::global::*; }
 #[cfg(any(test, feature = "std"))]
 extern crate std;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[cfg(test)]
 mod tests;
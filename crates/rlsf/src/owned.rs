@@ -0,0 +1,248 @@
+//! Single-owner allocation with cross-thread deallocation layered on [`Tlsf`]
+use core::{
+    alloc::Layout,
+    ptr::{self, NonNull},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::{int::BinInteger, Tlsf};
+
+/// A wrapper of [`Tlsf`] owned by one thread, which allocates and
+/// deallocates memory blocks without locking. Other threads can deallocate
+/// memory blocks through a [`RemoteFreeQueue`].
+///
+/// Memory blocks pushed to the `RemoteFreeQueue` are returned to the memory
+/// pools when the owner calls [`Self::allocate`], [`Self::reallocate`], or
+/// [`Self::drain_remote_frees`]. Neither side ever waits for the other:
+/// [`RemoteFreeQueue::deallocate`] is lock-free, and the owner takes all
+/// pending memory blocks by a single atomic operation.
+///
+/// This suits applications where one thread makes almost all allocations and
+/// other threads occasionally release them.
+///
+/// # Examples
+///
+/// ```
+/// use rlsf::{OwnedTlsf, RemoteFreeQueue};
+/// use std::{mem::MaybeUninit, alloc::Layout, thread};
+///
+/// static QUEUE: RemoteFreeQueue = RemoteFreeQueue::new();
+///
+/// let mut pool = [MaybeUninit::uninit(); 65536];
+/// // Safety: `QUEUE` isn't associated with other `OwnedTlsf`s
+/// let mut tlsf: OwnedTlsf<'_, '_, u16, u16, 12, 16> = unsafe { OwnedTlsf::new(&QUEUE) };
+/// tlsf.tlsf_mut().insert_free_block(&mut pool);
+///
+/// let layout = Layout::new::<u64>();
+/// let ptr = tlsf.allocate(layout).unwrap();
+///
+/// // Release the memory block on another thread
+/// let addr = ptr.as_ptr() as usize;
+/// thread::spawn(move || unsafe {
+///     QUEUE.deallocate(std::ptr::NonNull::new(addr as *mut u8).unwrap());
+/// }).join().unwrap();
+///
+/// // The owner reclaims the memory block
+/// tlsf.drain_remote_frees();
+/// ```
+#[derive(Debug)]
+pub struct OwnedTlsf<'pool, 'queue, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> {
+    tlsf: Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>,
+    queue: &'queue RemoteFreeQueue,
+}
+
+/// A lock-free queue of memory blocks to be deallocated by an [`OwnedTlsf`].
+///
+/// This is an intrusive list; each memory block stores a link to the next
+/// one in its payload.
+#[derive(Debug)]
+pub struct RemoteFreeQueue {
+    /// The most recently pushed memory block
+    head: AtomicPtr<RemoteFreeNode>,
+}
+
+/// Placed at the beginning of each memory block in a [`RemoteFreeQueue`].
+///
+/// A memory block allocated by `Tlsf` can always accommodate this: its
+/// payload is at least `GRANULARITY / 2` (two pointers) bytes long and aligned
+/// to `GRANULARITY / 2` bytes.
+struct RemoteFreeNode {
+    next: *mut RemoteFreeNode,
+}
+
+impl Default for RemoteFreeQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RemoteFreeQueue {
+    /// Construct an empty queue.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Deallocate a memory block allocated by the [`OwnedTlsf`] associated
+    /// with `self`. The memory block will be returned to the memory pools
+    /// later by the `OwnedTlsf`'s owner.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method is lock-free but not wait-free.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via the
+    ///    `OwnedTlsf` associated with `self`.
+    ///  - The memory block must not be used after calling this method.
+    pub unsafe fn deallocate(&self, ptr: NonNull<u8>) {
+        let node = ptr.cast::<RemoteFreeNode>().as_ptr();
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // Safety: `node` is an unused memory block large enough to contain
+            //         `RemoteFreeNode`
+            (*node).next = head;
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(new_head) => head = new_head,
+            }
+        }
+    }
+
+    /// Check if there are no pending memory blocks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Relaxed).is_null()
+    }
+}
+
+impl<
+        'pool,
+        'queue,
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+    > OwnedTlsf<'pool, 'queue, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    /// Construct an empty pool that receives memory blocks deallocated by
+    /// other threads from `queue`.
+    ///
+    /// # Safety
+    ///
+    /// `queue` must not be associated with other instances of `OwnedTlsf`,
+    /// which could otherwise receive memory blocks they don't own.
+    #[inline]
+    pub const unsafe fn new(queue: &'queue RemoteFreeQueue) -> Self {
+        Self {
+            tlsf: Tlsf::new(),
+            queue,
+        }
+    }
+
+    /// Get the [`RemoteFreeQueue`] associated with `self`.
+    #[inline]
+    pub fn queue(&self) -> &'queue RemoteFreeQueue {
+        self.queue
+    }
+
+    /// Borrow the underlying [`Tlsf`].
+    #[inline]
+    pub fn tlsf(&self) -> &Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &self.tlsf
+    }
+
+    /// Mutably borrow the underlying [`Tlsf`], e.g., to insert memory pools.
+    #[inline]
+    pub fn tlsf_mut(&mut self) -> &mut Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &mut self.tlsf
+    }
+
+    /// Return the memory blocks in the [`RemoteFreeQueue`] to the memory
+    /// pools.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(number_of_pending_memory_blocks)`).
+    pub fn drain_remote_frees(&mut self) {
+        if self.queue.is_empty() {
+            return;
+        }
+
+        let mut node = self.queue.head.swap(ptr::null_mut(), Ordering::Acquire);
+        while let Some(ptr) = NonNull::new(node) {
+            // Safety: `ptr` was pushed by `RemoteFreeQueue::deallocate`, whose
+            //         caller guarantees that it was allocated via `self`
+            unsafe {
+                node = ptr.as_ref().next;
+                self.tlsf.deallocate_unknown_align(ptr.cast());
+            }
+        }
+    }
+
+    /// Attempt to allocate a block of memory after calling
+    /// [`Self::drain_remote_frees`].
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise.
+    ///
+    /// # Time Complexity
+    ///
+    /// See [`Self::drain_remote_frees`] and [`Tlsf::allocate`].
+    #[inline]
+    pub fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        self.drain_remote_frees();
+        self.tlsf.allocate(layout)
+    }
+
+    /// Deallocate a previously allocated memory block on the owner thread.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `align`.
+    #[inline]
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+        self.tlsf.deallocate(ptr, align)
+    }
+
+    /// Shrink or grow a previously allocated memory block after calling
+    /// [`Self::drain_remote_frees`].
+    ///
+    /// Returns the new starting address of the memory block on success;
+    /// `None` otherwise.
+    ///
+    /// # Time Complexity
+    ///
+    /// See [`Self::drain_remote_frees`] and [`Tlsf::reallocate`].
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `new_layout`.
+    #[inline]
+    pub unsafe fn reallocate(
+        &mut self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        self.drain_remote_frees();
+        self.tlsf.reallocate(ptr, new_layout)
+    }
+}
+
+#[cfg(test)]
+mod tests;
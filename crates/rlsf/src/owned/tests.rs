@@ -0,0 +1,92 @@
+use std::{mem::MaybeUninit, prelude::v1::*, thread, vec};
+
+use super::*;
+
+type TheTlsf<'pool, 'queue> = OwnedTlsf<'pool, 'queue, u16, u16, 12, 16>;
+
+#[repr(align(64))]
+struct Align<T>(T);
+
+#[test]
+fn remote_frees_are_reclaimed() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let queue = RemoteFreeQueue::new();
+    let mut pool = Align([MaybeUninit::uninit(); 4096]);
+    let mut tlsf: TheTlsf = unsafe { OwnedTlsf::new(&queue) };
+    tlsf.tlsf_mut().insert_free_block(&mut pool.0);
+
+    // Use up the memory pool with various alignments
+    let mut ptrs: Vec<_> = (0..)
+        .map(|i| Layout::from_size_align(1 << (i % 6), 1 << (i % 7)).unwrap())
+        .map_while(|layout| tlsf.allocate(layout))
+        .collect();
+    let layout = Layout::from_size_align(1, 1).unwrap();
+    ptrs.extend(core::iter::from_fn(|| tlsf.allocate(layout)));
+    log::trace!("ptrs.len() = {}", ptrs.len());
+    assert!(!ptrs.is_empty());
+
+    for &ptr in ptrs.iter() {
+        unsafe { queue.deallocate(ptr) };
+    }
+    assert!(!queue.is_empty());
+
+    // The underlying `Tlsf` doesn't see the pending memory blocks until
+    // they are drained
+    assert_eq!(tlsf.tlsf_mut().allocate(layout), None);
+
+    // `allocate` drains the queue
+    let ptr = tlsf.allocate(layout).unwrap();
+    assert!(queue.is_empty());
+    unsafe { tlsf.deallocate(ptr, 1) };
+
+    // All memory blocks should have been returned, so the pool can serve a
+    // large allocation again
+    assert!(tlsf
+        .allocate(Layout::from_size_align(2048, 1).unwrap())
+        .is_some());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn cross_thread() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let queue: &'static RemoteFreeQueue = Box::leak(Box::new(RemoteFreeQueue::new()));
+    let pool: &'static mut [MaybeUninit<u8>] =
+        Box::leak(vec![MaybeUninit::uninit(); 65536].into_boxed_slice());
+    let mut tlsf: TheTlsf = unsafe { OwnedTlsf::new(queue) };
+    tlsf.tlsf_mut().insert_free_block(pool);
+
+    let layout = Layout::from_size_align(48, 8).unwrap();
+    for _ in 0..4 {
+        // Allocate on this thread and deallocate on other threads
+        let addrs: Vec<usize> = (0..256)
+            .map(|_| tlsf.allocate(layout).unwrap().as_ptr() as usize)
+            .collect();
+
+        let threads: Vec<_> = addrs
+            .chunks(64)
+            .map(|chunk| chunk.to_vec())
+            .map(|chunk| {
+                thread::spawn(move || {
+                    for addr in chunk {
+                        unsafe { queue.deallocate(NonNull::new(addr as *mut u8).unwrap()) };
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        tlsf.drain_remote_frees();
+        assert!(queue.is_empty());
+    }
+
+    // All memory blocks should have been returned
+    assert!(tlsf
+        .allocate(Layout::from_size_align(32768, 1).unwrap())
+        .is_some());
+}
@@ -0,0 +1,194 @@
+//! Region-based allocation layered on [`Tlsf`]
+use core::{alloc::Layout, ptr::NonNull};
+
+use crate::{int::BinInteger, Tlsf};
+
+/// A wrapper of [`Tlsf`] that can free every allocation made after a
+/// [checkpoint](Self::checkpoint) at once.
+///
+/// Allocations made by [`Self::allocate_scoped`] ("scoped allocations") are
+/// tracked by an intrusive singly-linked list and released in bulk by
+/// [`Self::reset_to`]. Long-lived objects can still be allocated and
+/// deallocated individually through the underlying [`Tlsf`]
+/// ([`Self::tlsf_mut`]), and they share the same memory pools with the
+/// scoped allocations.
+///
+/// Each scoped allocation is preceded by a small header, which is why scoped
+/// allocations must not be passed to [`Tlsf::deallocate`] or
+/// [`Tlsf::reallocate`].
+///
+/// # Examples
+///
+/// ```
+/// use rlsf::ScopedTlsf;
+/// use std::{mem::MaybeUninit, alloc::Layout};
+///
+/// let mut pool = [MaybeUninit::uninit(); 65536];
+/// let mut tlsf: ScopedTlsf<'_, u16, u16, 12, 16> = ScopedTlsf::new();
+/// tlsf.tlsf_mut().insert_free_block(&mut pool);
+///
+/// // A long-lived object
+/// let long_lived = tlsf.tlsf_mut().allocate(Layout::new::<u64>()).unwrap();
+///
+/// let checkpoint = tlsf.checkpoint();
+/// for _ in 0..100 {
+///     tlsf.allocate_scoped(Layout::new::<[u64; 4]>()).unwrap();
+/// }
+///
+/// // Free the 100 allocations made above
+/// tlsf.reset_to(checkpoint);
+/// assert_eq!(tlsf.num_scoped_allocations(), 0);
+///
+/// unsafe { tlsf.tlsf_mut().deallocate(long_lived, Layout::new::<u64>().align()) };
+/// ```
+#[derive(Debug)]
+pub struct ScopedTlsf<'pool, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> {
+    tlsf: Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>,
+    /// The most recent scoped allocation.
+    last_scoped: Option<NonNull<ScopedAllocHdr>>,
+    /// The number of the elements in the list starting at `last_scoped`.
+    num_scoped: usize,
+}
+
+// Safety: The scoped allocation headers are logically owned by `ScopedTlsf`
+//         in the same way as memory block headers are owned by `Tlsf`
+unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Send
+    for ScopedTlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+}
+
+unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Sync
+    for ScopedTlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+}
+
+/// The header placed at the beginning of each scoped allocation.
+#[derive(Debug)]
+struct ScopedAllocHdr {
+    /// The previous scoped allocation. Forms a singly-linked list.
+    prev: Option<NonNull<ScopedAllocHdr>>,
+    /// The alignment of the underlying allocation.
+    align: usize,
+}
+
+/// A point in time of a [`ScopedTlsf`] to which it can be reset. Returned by
+/// [`ScopedTlsf::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    num_scoped: usize,
+}
+
+impl<FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize> Default
+    for ScopedTlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize>
+    ScopedTlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    /// Construct an empty pool.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            tlsf: Tlsf::new(),
+            last_scoped: None,
+            num_scoped: 0,
+        }
+    }
+
+    /// Borrow the underlying [`Tlsf`].
+    #[inline]
+    pub fn tlsf(&self) -> &Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &self.tlsf
+    }
+
+    /// Mutably borrow the underlying [`Tlsf`]. This can be used to insert
+    /// memory pools and to make allocations that outlive checkpoints.
+    #[inline]
+    pub fn tlsf_mut(&mut self) -> &mut Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &mut self.tlsf
+    }
+
+    /// Get the number of live scoped allocations.
+    #[inline]
+    pub fn num_scoped_allocations(&self) -> usize {
+        self.num_scoped
+    }
+
+    /// Record the current set of scoped allocations.
+    #[inline]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            num_scoped: self.num_scoped,
+        }
+    }
+
+    /// Attempt to allocate a block of memory that will be released by
+    /// [`Self::reset_to`].
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    pub fn allocate_scoped(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let (full_layout, offset) = Layout::new::<ScopedAllocHdr>().extend(layout).ok()?;
+        let hdr = self.tlsf.allocate(full_layout)?.cast::<ScopedAllocHdr>();
+
+        // Safety: `hdr` points to a fresh allocation, which is large enough and
+        //         suitably aligned to contain `ScopedAllocHdr`
+        unsafe {
+            hdr.as_ptr().write(ScopedAllocHdr {
+                prev: self.last_scoped,
+                align: full_layout.align(),
+            })
+        };
+        self.last_scoped = Some(hdr);
+        self.num_scoped += 1;
+
+        // Safety: `offset` is within the allocation
+        Some(unsafe { NonNull::new_unchecked(hdr.cast::<u8>().as_ptr().add(offset)) })
+    }
+
+    /// Deallocate every scoped allocation made after `checkpoint` was
+    /// created.
+    ///
+    /// Scoped allocations that have already been released by a previous call
+    /// to this method are not released again, so this method does nothing if
+    /// `checkpoint` is newer than the last reset.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(n)`, where `n` is the
+    /// number of released allocations).
+    pub fn reset_to(&mut self, checkpoint: Checkpoint) {
+        while self.num_scoped > checkpoint.num_scoped {
+            let hdr = self.last_scoped.unwrap_or_else(|| {
+                debug_assert!(false, "`num_scoped` is out of sync");
+                // Safety: It's unreachable
+                unsafe { crate::utils::unreachable_unchecked() }
+            });
+
+            // Safety: `hdr` is a live scoped allocation we made
+            unsafe {
+                let ScopedAllocHdr { prev, align } = hdr.as_ptr().read();
+                self.tlsf.deallocate(hdr.cast(), align);
+                self.last_scoped = prev;
+            }
+            self.num_scoped -= 1;
+        }
+    }
+
+    /// Deallocate all scoped allocations.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.reset_to(Checkpoint { num_scoped: 0 });
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,116 @@
+use quickcheck_macros::quickcheck;
+use std::{mem::MaybeUninit, prelude::v1::*};
+
+use super::*;
+use crate::tests::ShadowAllocator;
+
+type TheTlsf<'pool> = ScopedTlsf<'pool, u16, u16, 12, 16>;
+
+#[test]
+fn reset_restores_free_space() {
+    let mut pool = [MaybeUninit::uninit(); 4096];
+    let mut tlsf: TheTlsf<'_> = ScopedTlsf::new();
+    tlsf.tlsf_mut().insert_free_block(&mut pool);
+
+    // Fill the pool
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let mut num_allocs = 0;
+    while tlsf.allocate_scoped(layout).is_some() {
+        num_allocs += 1;
+    }
+    assert!(num_allocs > 0);
+    assert_eq!(tlsf.num_scoped_allocations(), num_allocs);
+
+    tlsf.reset();
+    assert_eq!(tlsf.num_scoped_allocations(), 0);
+
+    // The same number of allocations should succeed again
+    for _ in 0..num_allocs {
+        tlsf.allocate_scoped(layout).unwrap();
+    }
+    assert!(tlsf.allocate_scoped(layout).is_none());
+}
+
+#[test]
+fn nested_checkpoints() {
+    let mut pool = [MaybeUninit::uninit(); 65536];
+    let mut tlsf: TheTlsf<'_> = ScopedTlsf::new();
+    tlsf.tlsf_mut().insert_free_block(&mut pool);
+
+    let layout = Layout::new::<u64>();
+    tlsf.allocate_scoped(layout).unwrap();
+    let cp1 = tlsf.checkpoint();
+    tlsf.allocate_scoped(layout).unwrap();
+    tlsf.allocate_scoped(layout).unwrap();
+    let cp2 = tlsf.checkpoint();
+    tlsf.allocate_scoped(layout).unwrap();
+    assert_eq!(tlsf.num_scoped_allocations(), 4);
+
+    tlsf.reset_to(cp2);
+    assert_eq!(tlsf.num_scoped_allocations(), 3);
+
+    tlsf.reset_to(cp1);
+    assert_eq!(tlsf.num_scoped_allocations(), 1);
+
+    // `cp2` is newer than the current state
+    tlsf.reset_to(cp2);
+    assert_eq!(tlsf.num_scoped_allocations(), 1);
+
+    tlsf.reset();
+    assert_eq!(tlsf.num_scoped_allocations(), 0);
+}
+
+#[quickcheck]
+fn long_lived_allocations_survive_reset(bytecode: Vec<u8>) {
+    let mut pool = [MaybeUninit::uninit(); 65536];
+    let mut sa = ShadowAllocator::new();
+    sa.insert_free_block(&pool[..] as *const [MaybeUninit<u8>]);
+
+    let mut tlsf: TheTlsf<'_> = ScopedTlsf::new();
+    tlsf.tlsf_mut().insert_free_block(&mut pool);
+
+    let mut long_lived = Vec::new();
+    let mut scoped = Vec::new();
+    let mut checkpoints = Vec::new();
+
+    for (i, chunk) in bytecode.chunks_exact(2).enumerate() {
+        let len = chunk[1] as usize + 1;
+        match chunk[0] % 4 {
+            0 => {
+                let layout = Layout::from_size_align(len, 1).unwrap();
+                if let Some(ptr) = tlsf.tlsf_mut().allocate(layout) {
+                    sa.allocate(layout, ptr);
+                    unsafe { ptr.as_ptr().write_bytes(i as u8, len) };
+                    long_lived.push((ptr, len, i as u8));
+                }
+            }
+            1 => {
+                let layout = Layout::from_size_align(len, 8).unwrap();
+                if let Some(ptr) = tlsf.allocate_scoped(layout) {
+                    sa.allocate(layout, ptr);
+                    scoped.push((ptr, layout));
+                }
+            }
+            2 => checkpoints.push((tlsf.checkpoint(), scoped.len())),
+            _ => {
+                if let Some((checkpoint, num_scoped)) = checkpoints.pop() {
+                    tlsf.reset_to(checkpoint);
+                    for (ptr, layout) in scoped.drain(num_scoped..) {
+                        sa.deallocate(layout, ptr);
+                    }
+                }
+            }
+        }
+    }
+
+    tlsf.reset();
+    for (ptr, layout) in scoped.drain(..) {
+        sa.deallocate(layout, ptr);
+    }
+
+    for (ptr, len, value) in long_lived {
+        let data = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), len) };
+        assert!(data.iter().all(|&x| x == value));
+        unsafe { tlsf.tlsf_mut().deallocate(ptr, 1) };
+    }
+}
@@ -0,0 +1,257 @@
+//! Allocation tagging layered on [`Tlsf`]
+use core::{alloc::Layout, marker::PhantomData, mem::MaybeUninit, num::NonZeroUsize, ptr::NonNull};
+
+use crate::{int::BinInteger, Tlsf, GRANULARITY};
+
+/// A wrapper of [`Tlsf`] that attaches a small value (a *tag*, e.g., a call
+/// site ID) to each allocation.
+///
+/// The tag of an allocation can be retrieved by [`Self::tag_of`] or, with the
+/// `unstable` feature, by enumerating the allocations in a memory pool with
+/// `Self::iter_allocations`. This is useful for attributing the blocks in a
+/// heap dump (e.g., leaked ones) to their origins.
+///
+/// The tag is stored in the last bytes of each memory block, so each
+/// allocation consumes `size_of::<Tag>()` extra bytes. `Tag`'s alignment
+/// must not exceed [`GRANULARITY`].
+///
+/// # Examples
+///
+/// ```
+/// use rlsf::TaggedTlsf;
+/// use std::{mem::MaybeUninit, alloc::Layout};
+///
+/// let mut pool = [MaybeUninit::uninit(); 65536];
+/// let mut tlsf: TaggedTlsf<'_, u32, u16, u16, 12, 16> = TaggedTlsf::new();
+/// tlsf.insert_free_block(&mut pool);
+///
+/// let layout = Layout::new::<[u64; 4]>();
+/// let ptr = tlsf.allocate_tagged(layout, 42).unwrap();
+/// assert_eq!(unsafe { TaggedTlsf::<'_, u32, u16, u16, 12, 16>::tag_of(ptr, layout.align()) }, 42);
+///
+/// unsafe { tlsf.deallocate(ptr, layout.align()) };
+/// ```
+#[derive(Debug)]
+pub struct TaggedTlsf<'pool, Tag, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> {
+    tlsf: Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>,
+    _phantom: PhantomData<fn(Tag) -> Tag>,
+}
+
+impl<
+        Tag: Copy,
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+    > Default for TaggedTlsf<'_, Tag, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<
+        'pool,
+        Tag: Copy,
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+    > TaggedTlsf<'pool, Tag, FLBitmap, SLBitmap, FLLEN, SLLEN>
+{
+    /// Evaluates to `()`; fails to compile if `Tag` can't be stored at the
+    /// end of a memory block.
+    const TAG_FITS: () = if core::mem::align_of::<Tag>() > GRANULARITY {
+        panic!("`Tag`'s alignment must not exceed `GRANULARITY`")
+    };
+
+    /// Construct an empty pool.
+    #[inline]
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::TAG_FITS;
+        Self {
+            tlsf: Tlsf::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Borrow the underlying [`Tlsf`].
+    #[inline]
+    pub fn tlsf(&self) -> &Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &self.tlsf
+    }
+
+    /// Mutably borrow the underlying [`Tlsf`].
+    ///
+    /// # Safety
+    ///
+    /// The caller must not make allocations through the returned reference
+    /// because they would lack tags.
+    #[inline]
+    pub unsafe fn tlsf_mut(&mut self) -> &mut Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN> {
+        &mut self.tlsf
+    }
+
+    /// Create a new memory pool at the location specified by a slice. See
+    /// [`Tlsf::insert_free_block`].
+    #[inline]
+    pub fn insert_free_block(&mut self, block: &'pool mut [MaybeUninit<u8>]) -> impl Send + Sync {
+        self.tlsf.insert_free_block(block)
+    }
+
+    /// Create a new memory pool at the location specified by a slice pointer.
+    /// See [`Tlsf::insert_free_block_ptr`].
+    ///
+    /// # Safety
+    ///
+    /// See [`Tlsf::insert_free_block_ptr`].
+    #[inline]
+    pub unsafe fn insert_free_block_ptr(&mut self, block: NonNull<[u8]>) -> Option<NonZeroUsize> {
+        self.tlsf.insert_free_block_ptr(block)
+    }
+
+    /// Calculate the layout of the underlying allocation for a given
+    /// allocation layout.
+    #[inline]
+    fn inner_layout(layout: Layout) -> Option<Layout> {
+        // The tag is placed at the end of the memory block, which is at least
+        // this many bytes away from the end of the payload
+        let size = layout.size().checked_add(core::mem::size_of::<Tag>())?;
+        Layout::from_size_align(size, layout.align()).ok()
+    }
+
+    /// Get a pointer to the tag of an allocation whose memory block ends at
+    /// `block_end`.
+    #[inline]
+    fn tag_ptr(block_end: *mut u8) -> *mut Tag {
+        // `block_end` is aligned to `GRANULARITY` bytes, so the result is
+        // suitably aligned for `Tag`
+        block_end.wrapping_sub(core::mem::size_of::<Tag>()).cast()
+    }
+
+    /// Get a pointer to the tag of an allocation.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::tag_of`].
+    #[inline]
+    unsafe fn tag_ptr_for_allocation(ptr: NonNull<u8>, align: usize) -> *mut Tag {
+        let len = Tlsf::<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>::size_of_allocation(ptr, align);
+        Self::tag_ptr(ptr.as_ptr().wrapping_add(len))
+    }
+
+    /// Attempt to allocate a block of memory with a tag attached.
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    pub fn allocate_tagged(&mut self, layout: Layout, tag: Tag) -> Option<NonNull<u8>> {
+        let ptr = self.tlsf.allocate(Self::inner_layout(layout)?)?;
+
+        // Safety: `ptr` was just allocated with alignment `layout.align()`.
+        //         The tag doesn't overlap with the payload because of
+        //         `inner_layout`.
+        unsafe { Self::tag_ptr_for_allocation(ptr, layout.align()).write(tag) };
+
+        Some(ptr)
+    }
+
+    /// Get the tag of an allocation.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via some
+    ///    instance of `Self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `align`.
+    ///  - The call must happen-before the deallocation or reallocation of the
+    ///    memory block.
+    #[inline]
+    pub unsafe fn tag_of(ptr: NonNull<u8>, align: usize) -> Tag {
+        Self::tag_ptr_for_allocation(ptr, align).read()
+    }
+
+    /// Deallocate a previously allocated memory block.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `align`.
+    #[inline]
+    pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
+        self.tlsf.deallocate(ptr, align)
+    }
+
+    /// Shrink or grow a previously allocated memory block, keeping its tag.
+    ///
+    /// Returns the new starting address of the memory block on success;
+    /// `None` otherwise.
+    ///
+    /// # Time Complexity
+    ///
+    /// Unlike other methods, this method will complete in linear time
+    /// (`O(old_size)`).
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `new_layout`.
+    pub unsafe fn reallocate(
+        &mut self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        let new_inner_layout = Self::inner_layout(new_layout)?;
+
+        // The tag's location will change, so read it first
+        let tag = Self::tag_of(ptr, new_layout.align());
+
+        let new_ptr = self.tlsf.reallocate(ptr, new_inner_layout)?;
+        Self::tag_ptr_for_allocation(new_ptr, new_layout.align()).write(tag);
+
+        Some(new_ptr)
+    }
+
+    /// Enumerate the allocations in the specified memory pool with their
+    /// tags.
+    ///
+    /// Each allocation is represented by the [`BlockInfo`] of the memory
+    /// block containing it.
+    ///
+    /// # Safety
+    ///
+    /// See [`Tlsf::iter_blocks`].
+    ///
+    /// [`BlockInfo`]: crate::BlockInfo
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub unsafe fn iter_allocations(
+        &self,
+        pool: NonNull<[u8]>,
+    ) -> impl Iterator<Item = (crate::BlockInfo<'_>, Tag)> + Send + '_ {
+        self.tlsf
+            .iter_blocks(pool)
+            .filter(|block_info| block_info.is_occupied())
+            .map(|block_info| {
+                let block = block_info.as_ptr();
+                let block_end = (block.as_ptr() as *mut u8).wrapping_add(block_info.size());
+                // Safety: Every occupied block was allocated by
+                //         `allocate_tagged` or `reallocate`, which placed a tag
+                //         at the end
+                (block_info, Self::tag_ptr(block_end).read())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,120 @@
+use quickcheck_macros::quickcheck;
+use std::{mem::MaybeUninit, prelude::v1::*};
+
+use super::*;
+use crate::{tests::ShadowAllocator, utils::nonnull_slice_from_raw_parts};
+
+#[repr(align(64))]
+struct Align<T>(T);
+
+type TheTlsf<'pool, Tag> = TaggedTlsf<'pool, Tag, u16, u16, 12, 16>;
+
+#[test]
+fn zero_sized_tag() {
+    let mut pool = [MaybeUninit::uninit(); 1024];
+    let mut tlsf: TheTlsf<'_, ()> = TaggedTlsf::new();
+    tlsf.insert_free_block(&mut pool);
+
+    let ptr = tlsf.allocate_tagged(Layout::new::<u64>(), ()).unwrap();
+    unsafe { TheTlsf::<'_, ()>::tag_of(ptr, 8) };
+    unsafe { tlsf.deallocate(ptr, 8) };
+}
+
+#[quickcheck]
+fn random(bytecode: Vec<u8>) {
+    random_inner(bytecode);
+}
+
+fn random_inner(bytecode: Vec<u8>) -> Option<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut pool = Align([MaybeUninit::uninit(); 65536]);
+    let mut sa = ShadowAllocator::new();
+    sa.insert_free_block(&pool.0[..] as *const [MaybeUninit<u8>]);
+
+    let mut tlsf: TheTlsf<'_, u64> = TaggedTlsf::new();
+    let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+    let pool_len =
+        unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, pool.0.len())) }
+            .unwrap()
+            .get();
+    #[cfg(not(feature = "unstable"))]
+    let _ = pool_len;
+
+    struct Alloc {
+        ptr: NonNull<u8>,
+        layout: Layout,
+        tag: u64,
+    }
+    let mut allocs: Vec<Alloc> = Vec::new();
+    let mut next_tag = 0u64;
+
+    let mut it = bytecode.iter().cloned();
+    loop {
+        match it.next()? % 4 {
+            0 | 1 => {
+                let len = it.next()? as usize * 4;
+                let align = 1 << (it.next()? % 7);
+                let layout = Layout::from_size_align(len, align).unwrap();
+                next_tag += 1;
+                if let Some(ptr) = tlsf.allocate_tagged(layout, next_tag) {
+                    log::trace!("alloc {:?} → {:?} (tag {})", layout, ptr, next_tag);
+                    sa.allocate(layout, ptr);
+                    unsafe { ptr.as_ptr().write_bytes(0xff, len) };
+                    allocs.push(Alloc {
+                        ptr,
+                        layout,
+                        tag: next_tag,
+                    });
+                }
+            }
+            2 => {
+                let alloc_i = it.next()?;
+                if !allocs.is_empty() {
+                    let alloc = allocs.swap_remove(alloc_i as usize % allocs.len());
+                    log::trace!("dealloc {:?}", alloc.ptr);
+                    sa.deallocate(alloc.layout, alloc.ptr);
+                    unsafe { tlsf.deallocate(alloc.ptr, alloc.layout.align()) };
+                }
+            }
+            3 => {
+                let alloc_i = it.next()?;
+                let len = it.next()? as usize * 4;
+                if !allocs.is_empty() {
+                    let alloc_i = alloc_i as usize % allocs.len();
+                    let alloc = &mut allocs[alloc_i];
+                    let new_layout = Layout::from_size_align(len, alloc.layout.align()).unwrap();
+                    if let Some(ptr) = unsafe { tlsf.reallocate(alloc.ptr, new_layout) } {
+                        log::trace!("realloc {:?} → {:?}", alloc.ptr, ptr);
+                        sa.deallocate(alloc.layout, alloc.ptr);
+                        alloc.ptr = ptr;
+                        alloc.layout = new_layout;
+                        sa.allocate(alloc.layout, alloc.ptr);
+                        unsafe { ptr.as_ptr().write_bytes(0xff, len) };
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        // The tags must survive the payload writes
+        for alloc in allocs.iter() {
+            assert_eq!(
+                unsafe { TheTlsf::<'_, u64>::tag_of(alloc.ptr, alloc.layout.align()) },
+                alloc.tag
+            );
+        }
+
+        #[cfg(feature = "unstable")]
+        {
+            let pool = nonnull_slice_from_raw_parts(pool_ptr, pool_len);
+            let mut tags: Vec<u64> = unsafe { tlsf.iter_allocations(pool) }
+                .map(|(_, tag)| tag)
+                .collect();
+            let mut expected_tags: Vec<u64> = allocs.iter().map(|alloc| alloc.tag).collect();
+            tags.sort_unstable();
+            expected_tags.sort_unstable();
+            assert_eq!(tags, expected_tags);
+        }
+    }
+}
@@ -2,8 +2,7 @@
 use const_default1::ConstDefault;
 use core::{
     alloc::Layout,
-    debug_assert, debug_assert_eq, fmt,
-    hint::unreachable_unchecked,
+    debug_assert, debug_assert_eq,
     marker::PhantomData,
     mem::{self, MaybeUninit},
     num::NonZeroUsize,
@@ -12,7 +11,9 @@ use core::{
 
 use crate::{
     int::BinInteger,
-    utils::{nonnull_slice_from_raw_parts, nonnull_slice_len, nonnull_slice_start},
+    utils::{
+        nonnull_slice_from_raw_parts, nonnull_slice_len, nonnull_slice_start, unreachable_unchecked,
+    },
 };
 
 #[doc = svgbobdoc::transform!(
@@ -64,26 +65,216 @@ use crate::{
 ///
 /// The maximum block size is `(GRANULARITY << FLLEN) - GRANULARITY`.
 ///
+/// The maximum block size covers the entire address space when `FLLEN` is
+/// `usize::BITS - GRANULARITY.trailing_zeros()` (e.g., `59` on a 64-bit
+/// target). Larger values of `FLLEN` only add unused free lists, so
+/// `FLBitmap` never needs more bits than `usize` has.
+///
+/// # Free List Order
+///
+/// By default (`FIFO = false`), each free list is LIFO: the most recently
+/// freed block of a size class is reused first, which tends to be better for
+/// cache locality.
+///
+/// If `FIFO` is `true`, each free list is FIFO: the least recently freed
+/// block is reused first. This delays the reuse of freed memory, which can
+/// make use-after-free bugs harder to exploit and helps some workloads with
+/// fragmentation. The tail of a free list is stored in the head block's
+/// header, so this doesn't increase the size of `Tlsf`, and all operations
+/// remain constant-time. Deallocation becomes slightly slower because it
+/// has to look up the head of a free list when unlinking a neighboring free
+/// block.
+///
+/// ```rust
+/// use rlsf::Tlsf;
+/// type FifoTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16, true>;
+/// ```
+///
+/// # Best Fit
+///
+/// By default (`BEST_FIT = false`), [`Tlsf::allocate`] takes the first block
+/// of the first non-empty free list whose blocks are all large enough. This
+/// is a good fit but not necessarily the best fit: the chosen block might be
+/// much larger than needed while a later block in the same free list, or a
+/// block in the free list just below, fits more tightly.
+///
+/// If `BEST_FIT` is `true`, `allocate` scans these two free lists and takes
+/// the smallest block that is large enough for the allocation at any
+/// alignment padding. This reduces
+/// fragmentation, but the time taken by an allocation becomes proportional
+/// to the lengths of the scanned free lists, so it's not suitable for
+/// real-time systems. The methods based on `allocate`, such as
+/// [`Tlsf::reallocate`] and [`Tlsf::allocate_from_end`], use the same search.
+///
+/// ```rust
+/// use rlsf::Tlsf;
+/// type BestFitTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16, false, true>;
+/// ```
+///
+/// # Coalescing on Free
+///
+/// By default (`COALESCE = true`), [`Tlsf::deallocate`] merges the
+/// deallocated memory block with its free neighbors.
+///
+/// Merging is the main cost of deallocation. If `COALESCE` is `false`,
+/// deallocation leaves the freed memory blocks as they are, which makes it
+/// faster at the cost of fragmentation: they can only be reused for
+/// allocations that fit in each of them. This suits workloads that
+/// repeatedly allocate and deallocate objects of the same size. The
+/// fragmentation can be resolved on demand by [`Tlsf::coalesce_all`].
+/// [`Tlsf::reallocate`] and the other methods still make use of free
+/// neighbors when resizing memory blocks.
+///
+/// ```rust
+/// use rlsf::Tlsf;
+/// type NoCoalesceTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16, false, false, false>;
+/// ```
+///
+/// # Memory Access
+///
+/// `Tlsf` stores its bookkeeping data (block headers) inside memory pools.
+/// Memory pools are expected to behave like ordinary RAM:
+///
+///  - Block headers consist of `usize`-sized fields (sizes and pointers),
+///    which are read and written by ordinary (non-volatile) loads and stores
+///    at their natural alignment. The compiler is free to merge, split,
+///    reorder, or elide these accesses.
+///
+///  - Allocation and deallocation never touch the payload of a memory block.
+///    [`Self::reallocate`] copies the payload by [`core::ptr::copy`] or
+///    [`core::ptr::copy_nonoverlapping`], whose access widths are
+///    unspecified.
+///
+///  - Reads must not have side effects, and memory must keep the last value
+///    written to it.
+///
+/// Consequently, memory regions that only tolerate accesses of a specific
+/// width or that are shared with other bus masters without coherency (e.g.,
+/// memory-mapped peripheral buffers) are not suitable as memory pools.
+///
+/// # Read-Only or Executable Payloads
+///
+/// Block headers are stored right before the payloads, and allocating or
+/// deallocating a memory block writes to the headers of its neighbors. For
+/// this reason, a memory pool can't contain payloads that must not be
+/// written after allocation, such as code in a JIT code buffer that is
+/// remapped as read-only and executable.
+///
+/// `Tlsf` has no mode to keep block headers out-of-line. Instead, such a
+/// region can be managed through a *shadow pool*, an ordinary writable
+/// memory region of the same size. `Tlsf` allocates from the shadow pool,
+/// and each allocation's offset from the start of the shadow pool is used to
+/// locate the payload in the real region. The real region is never accessed
+/// by `Tlsf`. The offsets preserve alignment if both regions are aligned to
+/// the largest alignment requested. With demand-paged memory, the pages of
+/// the shadow pool that no block header is written to are never committed.
+///
+/// ```rust
+/// use rlsf::Tlsf;
+/// use std::{mem::MaybeUninit, alloc::Layout};
+///
+/// #[repr(align(4096))]
+/// struct Region([MaybeUninit<u8>; 4096]);
+///
+/// // The region that would be remapped as read-only and executable
+/// let code = Region([MaybeUninit::uninit(); 4096]);
+///
+/// let mut shadow = Region([MaybeUninit::uninit(); 4096]);
+/// let shadow_start = shadow.0.as_ptr() as usize;
+/// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+/// tlsf.insert_free_block(&mut shadow.0);
+///
+/// let layout = Layout::from_size_align(100, 64).unwrap();
+/// let shadow_ptr = tlsf.allocate(layout).unwrap();
+/// let offset = shadow_ptr.as_ptr() as usize - shadow_start;
+/// let code_ptr = code.0.as_ptr().wrapping_add(offset);
+/// assert_eq!(code_ptr as usize % 64, 0);
+///
+/// // Deallocate through the shadow pointer
+/// unsafe { tlsf.deallocate(shadow_ptr, layout.align()) };
+/// ```
+///
+/// # Large Alignments
+///
+/// Free lists are indexed by size only. [`Self::allocate`] serves a request
+/// with alignment `align` from a free list whose blocks are large enough to
+/// contain the worst-case padding (up to `align - GRANULARITY / 2` bytes),
+/// so frequent large-alignment requests tend to take larger blocks than
+/// necessary.
+///
+/// `Tlsf` doesn't maintain an additional index of free blocks by their
+/// address alignment. A free block can be as small as `GRANULARITY` bytes,
+/// which its header already fills up, so linking it into a second set of
+/// free lists would require increasing the minimum block size (and thus the
+/// overhead of every allocation). For such workloads, consider:
+///
+///  - [`Self::allocate_probing`], which looks at a few blocks from the free
+///    list that `allocate` skips, taking the actual padding into account.
+///
+///  - A separate `Tlsf` for large-alignment allocations, whose memory pool
+///    is aligned to the largest alignment requested. If its requests are
+///    multiples of the alignment in size, the free blocks remain aligned
+///    and need no padding.
+///
 )]
 #[derive(Debug)]
-pub struct Tlsf<'pool, FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> {
+pub struct Tlsf<
+    'pool,
+    FLBitmap,
+    SLBitmap,
+    const FLLEN: usize,
+    const SLLEN: usize,
+    const FIFO: bool = false,
+    const BEST_FIT: bool = false,
+    const COALESCE: bool = true,
+> {
     fl_bitmap: FLBitmap,
     /// `sl_bitmap[fl].get_bit(sl)` is set iff `first_free[fl][sl].is_some()`
     sl_bitmap: [SLBitmap; FLLEN],
     first_free: [[Option<NonNull<FreeBlockHdr>>; SLLEN]; FLLEN],
+    #[cfg(feature = "fault-injection")]
+    fault_injector: crate::fault::FaultInjector,
+    /// The limit set by [`Self::set_max_alloc_size`]
+    max_alloc_size: Option<usize>,
+    /// The flag set by [`Self::set_max_alloc_size_panics`]
+    max_alloc_size_panics: bool,
+    /// The top of the stack of memory blocks deallocated by
+    /// [`Self::deallocate_deferred`], linked by [`DeferredFreeLink`]
+    deferred_frees: Option<NonNull<UsedBlockHdr>>,
+    /// The total length of the memory pools
+    pool_capacity: usize,
+    /// The number of live allocations, checked by [`Self::is_empty`]
+    #[cfg(feature = "stats")]
+    num_allocations: usize,
+    #[cfg(feature = "stats")]
+    coalesce_stats: CoalesceStats,
     _phantom: PhantomData<&'pool ()>,
 }
 
 // Safety: All memory block headers directly or indirectly referenced by a
 //         particular instance of `Tlsf` are logically owned by that `Tlsf` and
 //         have no interior mutability, so these are safe.
-unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Send
-    for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+unsafe impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Send for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
 {
 }
 
-unsafe impl<FLBitmap, SLBitmap, const FLLEN: usize, const SLLEN: usize> Sync
-    for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+unsafe impl<
+        FLBitmap,
+        SLBitmap,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Sync for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
 {
 }
 
@@ -95,6 +286,63 @@ pub const GRANULARITY: usize = core::mem::size_of::<usize>() * 4;
 
 const GRANULARITY_LOG2: u32 = GRANULARITY.trailing_zeros();
 
+/// The number of bytes consumed by bookkeeping in each memory pool, excluding
+/// any bytes lost to aligning the pool's starting and ending addresses.
+///
+/// Every memory pool ends with a sentinel block, a permanently-used block
+/// that marks the end of the pool. The sentinel is only [`GRANULARITY`]` / 2`
+/// bytes long in principle, but it occupies a whole [`GRANULARITY`] because
+/// all blocks must be aligned to [`GRANULARITY`]. [`FlexTlsf`] stores its
+/// per-pool footer in the other half, so it doesn't add to this overhead.
+///
+/// [`FlexTlsf`]: crate::FlexTlsf
+pub const POOL_OVERHEAD: usize = GRANULARITY;
+
+/// Counts of deallocations by how the deallocated memory block was merged
+/// with its neighbors. Returned by [`Tlsf::coalesce_stats`].
+///
+/// A high proportion of merging deallocations indicates that memory blocks
+/// tend to be released near each other, which keeps free space contiguous.
+#[cfg(feature = "stats")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CoalesceStats {
+    /// The number of deallocations that merged the block with the following
+    /// free block only.
+    pub forward: u64,
+    /// The number of deallocations that merged the block with the preceding
+    /// free block only.
+    pub backward: u64,
+    /// The number of deallocations that merged the block with both of its
+    /// neighbors.
+    pub both: u64,
+    /// The number of deallocations that didn't merge the block with any
+    /// neighbor.
+    pub none: u64,
+}
+
+/// A snapshot of the memory pools' state. Returned by [`Tlsf::stats`].
+///
+/// All sizes include the memory block headers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The total length of the memory pools.
+    pub total_bytes: usize,
+    /// The number of bytes not in free blocks, i.e., `total_bytes -
+    /// free_bytes`. This includes the allocations' payloads, headers, and
+    /// alignment padding, as well as the sentinel block at the end of each
+    /// memory pool ([`POOL_OVERHEAD`]).
+    pub used_bytes: usize,
+    /// The total size of the free blocks.
+    pub free_bytes: usize,
+    /// The size of the largest free block, or `0` if there's none.
+    pub largest_free_block: usize,
+    /// The number of free blocks.
+    pub num_free_blocks: usize,
+}
+
 /// The header of a memory block.
 // The header is actually aligned at `size_of::<usize>() * 4`-byte boundaries
 // but the alignment is set to a half value here not to introduce a padding at
@@ -110,7 +358,7 @@ struct BlockHdr {
     ///  - `bit[0]` ([`SIZE_USED`]) indicates whether the block is a used memory
     ///    block or not.
     ///
-    ///  - `bit[1]` ([`SIZE_LAST_IN_POOL`]) indicates whether the block is the
+    ///  - `bit[1]` ([`SIZE_SENTINEL`]) indicates whether the block is the
     ///    last one of the pool or not.
     ///
     ///  - `bit[GRANULARITY_LOG2..]` ([`SIZE_SIZE_MASK`]) represents the size.
@@ -143,10 +391,43 @@ impl BlockHdr {
             "`self` must not be a sentinel"
         );
 
-        // Safety: Since `self.size & SIZE_LAST_IN_POOL` is not lying, the
+        // Safety: Since `self.size & SIZE_SENTINEL` is not lying, the
         //         next block should exist at a non-null location.
         NonNull::new_unchecked((self as *const _ as *mut u8).add(self.size & SIZE_SIZE_MASK)).cast()
     }
+
+    /// Get the size of the block. Returns `None` if the size field is
+    /// implausible, i.e., it's zero or has unknown flags set.
+    ///
+    /// This is suitable for walking memory blocks that might be corrupted,
+    /// which could otherwise make the walk loop indefinitely or go astray.
+    #[cfg(any(feature = "unstable", feature = "debug-checks"))]
+    #[inline]
+    fn checked_size(&self) -> Option<usize> {
+        let size = self.size & SIZE_SIZE_MASK;
+        let flags = self.size & !SIZE_SIZE_MASK;
+        let flags_valid = flags == 0 || flags == SIZE_USED || flags == SIZE_USED | SIZE_SENTINEL;
+        if size == 0 || !flags_valid {
+            None
+        } else {
+            Some(size)
+        }
+    }
+
+    /// Get the next block. Returns `None` if `self` is a sentinel block, its
+    /// size field is implausible (see [`Self::checked_size`]), or the next
+    /// block would be past the end of the address space.
+    ///
+    /// This is a checked version of [`Self::next_phys_block`].
+    #[cfg(feature = "debug-checks")]
+    #[inline]
+    fn try_next_phys_block(&self) -> Option<NonNull<BlockHdr>> {
+        if (self.size & SIZE_SENTINEL) != 0 {
+            return None;
+        }
+        let next = (self as *const _ as usize).checked_add(self.checked_size()?)?;
+        NonNull::new(next as *mut BlockHdr)
+    }
 }
 
 /// The header of a free memory block.
@@ -189,22 +470,73 @@ impl UsedBlockPad {
     }
 }
 
-impl<FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize> Default
-    for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+/// In a used memory block pushed to the stack of deferred frees by
+/// [`Tlsf::deallocate_deferred`], the block header is followed by this
+/// structure. A memory block is at least `GRANULARITY` bytes long, so there's
+/// always room for it.
+#[derive(Debug)]
+#[repr(C)]
+struct DeferredFreeLink {
+    next: Option<NonNull<UsedBlockHdr>>,
+}
+
+impl DeferredFreeLink {
+    #[inline]
+    fn get_for_block(block: NonNull<UsedBlockHdr>) -> *mut Self {
+        block.as_ptr().wrapping_add(1).cast()
+    }
+}
+
+/// The first-level list counts of the source and destination of
+/// [`Tlsf::rebind`]
+struct RebindFllen<const FLLEN: usize, const FLLEN2: usize>;
+
+impl<const FLLEN: usize, const FLLEN2: usize> RebindFllen<FLLEN, FLLEN2> {
+    /// Evaluates to `()`; fails to compile if the destination can't
+    /// represent the source's block sizes.
+    const VALID: () = if FLLEN2 < FLLEN {
+        panic!("the new `Tlsf` must support the existing block sizes")
+    };
+}
+
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Default for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize>
-    ConstDefault for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN>
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > ConstDefault for Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
 {
     const DEFAULT: Self = Self::new();
 }
 
-impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, const SLLEN: usize>
-    Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN>
+impl<
+        'pool,
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
 {
     /// Construct an empty pool.
     #[inline]
@@ -213,6 +545,21 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
             fl_bitmap: FLBitmap::ZERO,
             sl_bitmap: [SLBitmap::ZERO; FLLEN],
             first_free: [[None; SLLEN]; FLLEN],
+            #[cfg(feature = "fault-injection")]
+            fault_injector: crate::fault::FaultInjector::new(),
+            max_alloc_size: None,
+            max_alloc_size_panics: false,
+            deferred_frees: None,
+            pool_capacity: 0,
+            #[cfg(feature = "stats")]
+            num_allocations: 0,
+            #[cfg(feature = "stats")]
+            coalesce_stats: CoalesceStats {
+                forward: 0,
+                backward: 0,
+                both: 0,
+                none: 0,
+            },
             _phantom: {
                 let () = Self::VALID;
                 PhantomData
@@ -220,6 +567,171 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         }
     }
 
+    /// Move the memory pools of `self` to a `Tlsf` with different type
+    /// parameters.
+    ///
+    /// All free blocks are re-linked to the free lists of the new `Tlsf`.
+    /// The existing allocations remain valid and can be deallocated or
+    /// reallocated through the new `Tlsf`. This can also be used to switch
+    /// the [free list order](#free-list-order), enable the
+    /// [best-fit search](#best-fit), or disable
+    /// [coalescing on free](#coalescing-on-free). The deferred frees are
+    /// [flushed](Self::flush_deferred_frees) first.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_free_blocks +
+    /// number_of_deferred_frees)`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    /// let ptr = tlsf.allocate(Layout::new::<u64>()).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = tlsf.rebind();
+    /// unsafe { tlsf.deallocate(ptr, 8) };
+    /// assert!(tlsf.allocate(Layout::new::<[u64; 64]>()).is_some());
+    /// ```
+    ///
+    /// `FLLEN2` must not be smaller than `FLLEN`, or the new `Tlsf` might not
+    /// be able to represent the existing memory blocks:
+    ///
+    /// ```rust,compile_fail
+    /// use rlsf::Tlsf;
+    /// let tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let _: Tlsf<'_, u8, u16, 8, 16> = tlsf.rebind();
+    /// ```
+    pub fn rebind<
+        FLBitmap2: BinInteger,
+        SLBitmap2: BinInteger,
+        const FLLEN2: usize,
+        const SLLEN2: usize,
+        const FIFO2: bool,
+        const BEST_FIT2: bool,
+        const COALESCE2: bool,
+    >(
+        mut self,
+    ) -> Tlsf<'pool, FLBitmap2, SLBitmap2, FLLEN2, SLLEN2, FIFO2, BEST_FIT2, COALESCE2> {
+        #[allow(clippy::let_unit_value)]
+        let () = RebindFllen::<FLLEN, FLLEN2>::VALID;
+
+        self.flush_deferred_frees();
+
+        let mut new = Tlsf::new();
+
+        // Safety: All free blocks in the free lists are owned by `self`.
+        //         `FLLEN2 >= FLLEN` ensures that the new free lists can
+        //         contain the blocks.
+        unsafe { new.link_free_blocks_from(&self.first_free) };
+
+        #[cfg(feature = "fault-injection")]
+        {
+            new.fault_injector = self.fault_injector;
+        }
+        new.max_alloc_size = self.max_alloc_size;
+        new.max_alloc_size_panics = self.max_alloc_size_panics;
+        new.pool_capacity = self.pool_capacity;
+        #[cfg(feature = "stats")]
+        {
+            new.num_allocations = self.num_allocations;
+            new.coalesce_stats = self.coalesce_stats;
+        }
+
+        new
+    }
+
+    /// Take over the memory pools of another `Tlsf`.
+    ///
+    /// All free blocks of `other` are re-linked to the free lists of `self`.
+    /// The existing allocations made by `other` remain valid and can be
+    /// deallocated or reallocated through `self` as if they were allocated
+    /// by `self`. The settings of `self`, such as the one made by
+    /// [`Self::set_max_alloc_size`], are kept, and those of `other` are
+    /// discarded.
+    ///
+    /// The memory pools are not merged with each other even if they are
+    /// physically adjacent. Use [`Self::rebind`] first if `other` has
+    /// different type parameters. The deferred frees of `other` are
+    /// [flushed](Self::flush_deferred_frees) first.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(number_of_free_blocks_in_other + number_of_deferred_frees_in_other)`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool0 = [MaybeUninit::uninit(); 4096];
+    /// let mut pool1 = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf0: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let mut tlsf1: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf0.insert_free_block(&mut pool0);
+    /// tlsf1.insert_free_block(&mut pool1);
+    /// let ptr = tlsf1.allocate(Layout::new::<u64>()).unwrap();
+    ///
+    /// tlsf0.absorb(tlsf1);
+    /// unsafe { tlsf0.deallocate(ptr, 8) };
+    /// ```
+    pub fn absorb(&mut self, mut other: Self) {
+        other.flush_deferred_frees();
+
+        // Safety: All free blocks in the free lists are owned by `other`,
+        //         which has the same type parameters as `self`
+        unsafe { self.link_free_blocks_from(&other.first_free) };
+
+        self.pool_capacity = self.pool_capacity.wrapping_add(other.pool_capacity);
+        #[cfg(feature = "stats")]
+        {
+            self.num_allocations = self.num_allocations.wrapping_add(other.num_allocations);
+        }
+    }
+
+    /// Link the free blocks in the free lists `first_free` of another `Tlsf`
+    /// to the free lists of `self`.
+    ///
+    /// # Safety
+    ///
+    /// The free blocks must be owned by the other `Tlsf`, which must not use
+    /// them anymore. Their sizes must be representable by `self`.
+    unsafe fn link_free_blocks_from<const SLLEN2: usize>(
+        &mut self,
+        first_free: &[[Option<NonNull<FreeBlockHdr>>; SLLEN2]],
+    ) {
+        for first_free in first_free.iter().flatten() {
+            let mut next_free = *first_free;
+            while let Some(block) = next_free {
+                next_free = block.as_ref().next_free;
+                let size = block.as_ref().common.size;
+                debug_assert_eq!(size, size & SIZE_SIZE_MASK);
+                self.link_free_block(block, size);
+            }
+        }
+    }
+
+    /// Forget all memory pools and return to the initial state. The settings,
+    /// such as the one made by `set_fail_policy`, are preserved.
+    pub(crate) fn forget_pools(&mut self) {
+        self.fl_bitmap = FLBitmap::ZERO;
+        self.sl_bitmap = [SLBitmap::ZERO; FLLEN];
+        self.first_free = [[None; SLLEN]; FLLEN];
+        self.deferred_frees = None;
+        self.pool_capacity = 0;
+        #[cfg(feature = "stats")]
+        {
+            self.num_allocations = 0;
+        }
+    }
+
     // For testing
     #[allow(dead_code)]
     const FLLEN: usize = FLLEN;
@@ -245,7 +757,7 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     /// The maximum size of each memory pool region. This is constrained by
     /// the maximum block size of the segregated list to contain the initial
     /// free memory block.
-    const MAX_POOL_SIZE: Option<usize> = {
+    pub(crate) const MAX_POOL_SIZE: Option<usize> = {
         let shift = GRANULARITY_LOG2 + FLLEN as u32;
         if shift < usize::BITS {
             Some(1 << shift)
@@ -261,12 +773,17 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         panic!("`SLLEN` is not power of two")
     };
 
+    /// The first-level index below which every second-level list holds
+    /// exactly one block size. This is zero if `GRANULARITY_LOG2 >= SLI`, in
+    /// which case `fl < EXACT_FL_END` is folded to `false` at compile time.
+    const EXACT_FL_END: u32 = Self::SLI.saturating_sub(GRANULARITY_LOG2);
+
     /// Find the free block list to store a free block of the specified size.
     #[inline]
     fn map_floor(size: usize) -> Option<(usize, usize)> {
         debug_assert!(size >= GRANULARITY);
         debug_assert!(size % GRANULARITY == 0);
-        let fl = usize::BITS - GRANULARITY_LOG2 - 1 - size.leading_zeros();
+        let fl = usize::BITS - GRANULARITY_LOG2 - 1 - BinInteger::leading_zeros(&size);
 
         // The shift amount can be negative, and rotation lets us handle both
         // cases without branching. Underflowed digits can be simply masked out
@@ -290,7 +807,7 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     fn map_ceil(size: usize) -> Option<(usize, usize)> {
         debug_assert!(size >= GRANULARITY);
         debug_assert!(size % GRANULARITY == 0);
-        let mut fl = usize::BITS - GRANULARITY_LOG2 - 1 - size.leading_zeros();
+        let mut fl = usize::BITS - GRANULARITY_LOG2 - 1 - BinInteger::leading_zeros(&size);
 
         // The shift amount can be negative, and rotation lets us handle both
         // cases without branching.
@@ -340,9 +857,9 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
             return None;
         }
 
-        let fl = usize::BITS - GRANULARITY_LOG2 - 1 - size.leading_zeros();
+        let fl = usize::BITS - GRANULARITY_LOG2 - 1 - BinInteger::leading_zeros(&size);
 
-        let list_min_size = if GRANULARITY_LOG2 < Self::SLI && fl < Self::SLI - GRANULARITY_LOG2 {
+        let list_min_size = if fl < Self::EXACT_FL_END {
             size
         } else {
             let shift = fl + GRANULARITY_LOG2 - Self::SLI;
@@ -372,16 +889,35 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
             // Safety: It's unreachable
             unreachable_unchecked()
         });
-        let first_free = &mut self.first_free[fl][sl];
-        let next_free = mem::replace(first_free, Some(block));
-        block.as_mut().next_free = next_free;
-        block.as_mut().prev_free = None;
-        if let Some(mut next_free) = next_free {
-            next_free.as_mut().prev_free = Some(block);
+        // Safety: `map_floor` returns `fl < FLLEN` and `sl < SLLEN`
+        let first_free = self.first_free.get_unchecked_mut(fl).get_unchecked_mut(sl);
+        if FIFO {
+            // Append `block` to the tail, which is found at `head.prev_free`
+            block.as_mut().next_free = None;
+            if let Some(mut head) = *first_free {
+                let mut tail = head.as_ref().prev_free.unwrap_or_else(|| {
+                    debug_assert!(false, "the head of a FIFO free list must have a tail");
+                    // Safety: It's unreachable
+                    unreachable_unchecked()
+                });
+                tail.as_mut().next_free = Some(block);
+                block.as_mut().prev_free = Some(tail);
+                head.as_mut().prev_free = Some(block);
+            } else {
+                block.as_mut().prev_free = Some(block);
+                *first_free = Some(block);
+            }
+        } else {
+            let next_free = mem::replace(first_free, Some(block));
+            block.as_mut().next_free = next_free;
+            block.as_mut().prev_free = None;
+            if let Some(mut next_free) = next_free {
+                next_free.as_mut().prev_free = Some(block);
+            }
         }
 
         self.fl_bitmap.set_bit(fl as u32);
-        self.sl_bitmap[fl].set_bit(sl as u32);
+        self.sl_bitmap.get_unchecked_mut(fl).set_bit(sl as u32);
     }
 
     /// Remove the specified free block from the corresponding free block list.
@@ -393,6 +929,10 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     ///
     #[cfg_attr(target_arch = "wasm32", inline(never))]
     unsafe fn unlink_free_block(&mut self, mut block: NonNull<FreeBlockHdr>, size: usize) {
+        if FIFO {
+            return self.unlink_free_block_fifo(block, size);
+        }
+
         let next_free = block.as_mut().next_free;
         let prev_free = block.as_mut().prev_free;
 
@@ -408,18 +948,75 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
                 // Safety: It's unreachable
                 unreachable_unchecked()
             });
-            let first_free = &mut self.first_free[fl][sl];
+            // Safety: `map_floor` returns `fl < FLLEN` and `sl < SLLEN`
+            let first_free = self.first_free.get_unchecked_mut(fl).get_unchecked_mut(sl);
 
             debug_assert_eq!(*first_free, Some(block));
             *first_free = next_free;
 
             if next_free.is_none() {
                 // The free list is now empty - update the bitmap
-                self.sl_bitmap[fl].clear_bit(sl as u32);
-                if self.sl_bitmap[fl] == SLBitmap::ZERO {
+                let sl_bitmap = self.sl_bitmap.get_unchecked_mut(fl);
+                sl_bitmap.clear_bit(sl as u32);
+                if *sl_bitmap == SLBitmap::ZERO {
+                    self.fl_bitmap.clear_bit(fl as u32);
+                }
+            }
+        }
+    }
+
+    /// [`Self::unlink_free_block`] for `FIFO == true`.
+    ///
+    /// In a FIFO free list, the head's `prev_free` points to the tail instead
+    /// of being `None`, so the head can't be identified by `prev_free` alone.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::unlink_free_block`].
+    #[cfg_attr(target_arch = "wasm32", inline(never))]
+    unsafe fn unlink_free_block_fifo(&mut self, mut block: NonNull<FreeBlockHdr>, size: usize) {
+        let (fl, sl) = Self::map_floor(size).unwrap_or_else(|| {
+            debug_assert!(false, "could not map size {}", size);
+            // Safety: It's unreachable
+            unreachable_unchecked()
+        });
+        // Safety: `map_floor` returns `fl < FLLEN` and `sl < SLLEN`
+        let first_free = self.first_free.get_unchecked_mut(fl).get_unchecked_mut(sl);
+        let mut head = first_free.unwrap_or_else(|| {
+            debug_assert!(false, "the free list is empty");
+            // Safety: It's unreachable
+            unreachable_unchecked()
+        });
+
+        let next_free = block.as_mut().next_free;
+        let prev_free = block.as_mut().prev_free.unwrap_or_else(|| {
+            debug_assert!(false, "a block in a FIFO free list must have `prev_free`");
+            // Safety: It's unreachable
+            unreachable_unchecked()
+        });
+
+        if head == block {
+            // `prev_free` is the tail
+            *first_free = next_free;
+            if let Some(mut next_free) = next_free {
+                next_free.as_mut().prev_free = Some(prev_free);
+            } else {
+                // The free list is now empty - update the bitmap
+                let sl_bitmap = self.sl_bitmap.get_unchecked_mut(fl);
+                sl_bitmap.clear_bit(sl as u32);
+                if *sl_bitmap == SLBitmap::ZERO {
                     self.fl_bitmap.clear_bit(fl as u32);
                 }
             }
+        } else {
+            let mut prev_free = prev_free;
+            prev_free.as_mut().next_free = next_free;
+            if let Some(mut next_free) = next_free {
+                next_free.as_mut().prev_free = Some(prev_free);
+            } else {
+                // `block` was the tail
+                head.as_mut().prev_free = Some(prev_free);
+            }
         }
     }
 
@@ -432,6 +1029,21 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     /// This method does nothing and returns `None` if the given memory block is
     /// too small.
     ///
+    /// The created memory pool is never merged with existing ones, even if
+    /// they are physically adjacent, because `Tlsf` doesn't keep track of
+    /// memory pools. A free block can't span multiple memory pools, so this
+    /// limits the maximum allocation size. If `block` immediately follows an
+    /// existing memory pool, use [`Self::append_free_block_ptr`] instead to
+    /// extend that memory pool.
+    ///
+    /// Conversely, an allocation never spans the boundary between memory
+    /// pools created by separate calls to this method. Each memory pool ends
+    /// with a sentinel block, which is never deallocated and thus keeps the
+    /// memory pool's last free block from being coalesced with the next
+    /// memory pool. This makes it safe to insert physically adjacent memory
+    /// regions that must be kept apart, e.g., memory banks that a DMA
+    /// transfer must not cross.
+    ///
     /// # Time Complexity
     ///
     /// This method will complete in linear time (`O(block.len())`) because
@@ -454,11 +1066,48 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     ///
     /// The memory block will be considered owned by `self`. The memory block
     /// must outlive `self`.
+    pub unsafe fn insert_free_block_ptr(&mut self, block: NonNull<[u8]>) -> Option<NonZeroUsize> {
+        self.insert_free_block_ptr_with_free_bytes(block)
+            .map(|(pool_len, _)| pool_len)
+    }
+
+    /// [`Self::insert_free_block_ptr`] that additionally returns the number of
+    /// bytes made available to the allocator, i.e., the total size of the
+    /// free blocks created in the new memory pool.
     ///
-    /// # Panics
+    /// The second element of the returned tuple is the first element minus
+    /// the bytes lost to aligning `block`'s starting and ending addresses and
+    /// [`POOL_OVERHEAD`] for each chunk that `block` was divided into to meet
+    /// the maximum block size requirement. Summing it over all memory pools
+    /// gives the allocator's capacity without replicating these calculations.
     ///
-    /// This method never panics.
-    pub unsafe fn insert_free_block_ptr(&mut self, block: NonNull<[u8]>) -> Option<NonZeroUsize> {
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(block.len())`) because
+    /// it might need to divide the memory block to meet the maximum block size
+    /// requirement (`(GRANULARITY << FLLEN) - GRANULARITY`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{Tlsf, POOL_OVERHEAD};
+    /// use std::{mem::MaybeUninit, ptr::NonNull};
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let mut tlsf: Tlsf<u8, u8, 8, 8> = Tlsf::new();
+    /// let (pool_len, free_bytes) = unsafe {
+    ///     tlsf.insert_free_block_ptr_with_free_bytes(NonNull::new(POOL.as_mut_ptr()).unwrap())
+    /// }
+    /// .unwrap();
+    /// assert!(free_bytes <= pool_len.get() - POOL_OVERHEAD);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::insert_free_block_ptr`].
+    pub unsafe fn insert_free_block_ptr_with_free_bytes(
+        &mut self,
+        block: NonNull<[u8]>,
+    ) -> Option<(NonZeroUsize, usize)> {
         let len = nonnull_slice_len(block);
 
         // Round up the starting address
@@ -477,24 +1126,86 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         };
 
         // Safety: The slice being created here
-        let pool_len = self.insert_free_block_ptr_aligned(NonNull::new_unchecked(
-            core::ptr::slice_from_raw_parts_mut(start as *mut u8, len),
-        ))?;
+        let (pool_len, free_bytes) = self.insert_free_block_ptr_aligned_inner(
+            NonNull::new_unchecked(core::ptr::slice_from_raw_parts_mut(start as *mut u8, len)),
+        )?;
 
         // Safety: The sum should not wrap around because it represents the size
         //         of a memory pool on memory
-        Some(NonZeroUsize::new_unchecked(
-            pool_len.get() + start.wrapping_sub(unaligned_start),
+        Some((
+            NonZeroUsize::new_unchecked(pool_len.get() + start.wrapping_sub(unaligned_start)),
+            free_bytes,
         ))
     }
 
-    /// [`insert_free_block_ptr`] with a well-aligned slice passed by `block`.
-    pub(crate) unsafe fn insert_free_block_ptr_aligned(
+    /// [`Self::insert_free_block_ptr`] with a well-aligned slice passed by
+    /// `block`. This method skips the rounding of `block`'s starting and
+    /// ending addresses.
+    ///
+    /// Returns the actual number of bytes (counted from the beginning of
+    /// `block`) used to create the memory pool. This method does nothing and
+    /// returns `None` if the given memory block is too small.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(block.len())`) because
+    /// it might need to divide the memory block to meet the maximum block size
+    /// requirement (`(GRANULARITY << FLLEN) - GRANULARITY`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{Tlsf, GRANULARITY};
+    /// use std::{mem::MaybeUninit, ptr::NonNull};
+    ///
+    /// #[repr(align(64))]
+    /// struct Pool([MaybeUninit<u8>; 1024]);
+    /// static mut POOL: Pool = Pool([MaybeUninit::uninit(); 1024]);
+    ///
+    /// assert!(GRANULARITY <= 64);
+    /// let mut tlsf: Tlsf<u8, u8, 8, 8> = Tlsf::new();
+    /// unsafe {
+    ///     let block = std::ptr::slice_from_raw_parts_mut(POOL.0.as_mut_ptr().cast::<u8>(), 1024);
+    ///     tlsf.insert_free_block_ptr_aligned(NonNull::new(block).unwrap());
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The memory block will be considered owned by `self`. The memory block
+    /// must outlive `self`.
+    ///
+    /// `block`'s starting address and length must be multiples of
+    /// [`GRANULARITY`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics in debug builds if `block` violates the above
+    /// alignment requirement. Otherwise, it never panics.
+    pub unsafe fn insert_free_block_ptr_aligned(
         &mut self,
         block: NonNull<[u8]>,
     ) -> Option<NonZeroUsize> {
+        self.insert_free_block_ptr_aligned_inner(block)
+            .map(|(pool_len, _)| pool_len)
+    }
+
+    /// [`Self::insert_free_block_ptr_aligned`] that additionally returns the
+    /// total size of the created free blocks.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::insert_free_block_ptr_aligned`].
+    unsafe fn insert_free_block_ptr_aligned_inner(
+        &mut self,
+        block: NonNull<[u8]>,
+    ) -> Option<(NonZeroUsize, usize)> {
         let start = block.as_ptr() as *mut u8 as usize;
         let mut size = nonnull_slice_len(block);
+        let mut free_bytes = 0;
+
+        debug_assert_eq!(start % GRANULARITY, 0, "the block is misaligned");
+        debug_assert_eq!(size % GRANULARITY, 0, "the block length is misaligned");
 
         let mut cursor = start;
 
@@ -513,7 +1224,7 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
 
             // Initialize the new free block
             block.as_mut().common = BlockHdr {
-                size: chunk_size - GRANULARITY,
+                size: chunk_size - POOL_OVERHEAD,
                 prev_phys_block: None,
             };
 
@@ -525,12 +1236,13 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
                 .cast::<UsedBlockHdr>();
 
             sentinel_block.as_mut().common = BlockHdr {
-                size: GRANULARITY | SIZE_USED | SIZE_SENTINEL,
+                size: POOL_OVERHEAD | SIZE_USED | SIZE_SENTINEL,
                 prev_phys_block: Some(block.cast()),
             };
 
             // Link the free block to the corresponding free list
-            self.link_free_block(block, chunk_size - GRANULARITY);
+            self.link_free_block(block, chunk_size - POOL_OVERHEAD);
+            free_bytes += chunk_size - POOL_OVERHEAD;
 
             // `cursor` can reach `usize::MAX + 1`, but in such a case, this
             // iteration must be the last one
@@ -539,7 +1251,9 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
             cursor = cursor.wrapping_add(chunk_size);
         }
 
-        NonZeroUsize::new(cursor.wrapping_sub(start))
+        self.pool_capacity = self.pool_capacity.wrapping_add(cursor.wrapping_sub(start));
+
+        NonZeroUsize::new(cursor.wrapping_sub(start)).map(|pool_len| (pool_len, free_bytes))
     }
 
     /// Extend an existing memory pool by incorporating the specified memory
@@ -606,13 +1320,17 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     /// `block`'s starting address must match an existing memory pool's
     /// ending address. See the above example for how to obtain one.
     ///
-    /// # Panics
-    ///
-    /// This method never panics.
+    /// The part of `block` extending past the end of the address space, if
+    /// any, is ignored.
     pub unsafe fn append_free_block_ptr(&mut self, block: NonNull<[u8]>) -> usize {
-        // Round down the length
+        // Clamp the length so that `end` (calculated later) doesn't wrap
+        // around past `usize::MAX + 1`. `start.wrapping_neg()` is the distance
+        // to the end of the address space because `start` is non-null.
         let start = nonnull_slice_start(block);
-        let len = nonnull_slice_len(block) & !(GRANULARITY - 1);
+        let len = nonnull_slice_len(block).min((start.as_ptr() as usize).wrapping_neg());
+
+        // Round down the length
+        let len = len & !(GRANULARITY - 1);
 
         if Self::MAX_POOL_SIZE.is_some() {
             // If `MAX_POOL_SIZE` is `Some(_)`, it's dangerous to coalesce
@@ -691,13 +1409,22 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         first_block.as_mut().common.prev_phys_block = last_nonassimilated_block;
 
         // Exclude the assimilated part from the returned value
-        pool_len - (original_start as usize).wrapping_sub(start as usize)
+        let assimilated_len = (original_start as usize).wrapping_sub(start as usize);
+
+        // The assimilated part was already counted as a part of the preceding
+        // memory pool
+        self.pool_capacity = self.pool_capacity.wrapping_sub(assimilated_len);
+
+        pool_len - assimilated_len
     }
 
     /// Create a new memory pool at the location specified by a slice.
     ///
     /// This method does nothing if the given memory block is too small.
     ///
+    /// Like [`Self::insert_free_block_ptr`], this method doesn't merge the
+    /// created memory pool with existing ones.
+    ///
     /// (The return type is yet to be determined.)
     ///
     /// # Time Complexity
@@ -725,15 +1452,13 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     /// drop(pool); // dropping the memory block first is not allowed
     /// drop(tlsf);
     /// ```
-    ///
-    /// # Panics
-    ///
-    /// This method never panics.
     #[inline]
     pub fn insert_free_block(&mut self, block: &'pool mut [MaybeUninit<u8>]) -> impl Send + Sync {
+        let len = block.len();
+        let block = nonnull_slice_from_raw_parts(NonNull::from(block).cast::<u8>(), len);
         // Safety: `block` is a mutable reference, which guarantees the absence
         // of aliasing references. Being `'pool` means it will outlive `self`.
-        unsafe { self.insert_free_block_ptr(NonNull::new(block as *mut [_] as _).unwrap()) };
+        unsafe { self.insert_free_block_ptr(block) };
     }
 
     /// Calculate the minimum size of a `GRANULARITY`-byte aligned memory pool
@@ -761,16 +1486,183 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         list_min_size.checked_add(GRANULARITY)
     }
 
-    /// Attempt to allocate a block of memory.
+    /// Calculate the minimum size of a `GRANULARITY`-byte aligned memory pool
+    /// that is guaranteed to be able to contain all of the specified
+    /// allocations, made in the given order by [`Self::allocate`] without
+    /// any deallocation in between.
     ///
-    /// Returns the starting address of the allocated memory block on success;
-    /// `None` otherwise.
+    /// The result assumes that the memory pool is the only one in a `Tlsf`
+    /// with no prior allocations, and that its length is a multiple of
+    /// `GRANULARITY`. Each allocation is accounted for with the worst-case
+    /// alignment padding, so the result may be larger than what's actually
+    /// needed.
+    ///
+    /// Returns `Some(0)` if `layouts` is empty. Returns `None` if no single
+    /// memory pool can contain the allocations.
     ///
     /// # Time Complexity
     ///
-    /// This method will complete in constant time.
+    /// This method will complete in linear time (`O(layouts.len())`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// type MyTlsf<'pool> = Tlsf<'pool, u16, u16, 12, 16>;
+    ///
+    /// let layouts = [
+    ///     Layout::new::<[u64; 16]>(),
+    ///     Layout::from_size_align(100, 64).unwrap(),
+    ///     Layout::new::<u8>(),
+    /// ];
+    /// let pool_len = MyTlsf::pool_size_for_allocations(&layouts).unwrap();
+    /// assert!(pool_len <= 512);
+    ///
+    /// #[repr(align(64))]
+    /// struct Pool([MaybeUninit<u8>; 512]);
+    /// let mut pool = Pool([MaybeUninit::uninit(); 512]);
+    ///
+    /// let mut tlsf: MyTlsf<'_> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool.0[..pool_len]);
+    /// for layout in layouts {
+    ///     assert!(tlsf.allocate(layout).is_some());
+    /// }
+    /// ```
+    pub fn pool_size_for_allocations(layouts: &[Layout]) -> Option<usize> {
+        if layouts.is_empty() {
+            return Some(0);
+        }
+
+        // Without deallocation, each allocation is made from the beginning
+        // of the only free block, and the rest remains as a new free block.
+        // The allocation succeeds if the free block belongs to the segregated
+        // list searched by `allocate` or a later one. The used block occupies
+        // at most `search_size` bytes (see `Tlsf::allocate`).
+        let mut consumed: usize = 0;
+        let mut free_block_size: usize = 0;
+        for layout in layouts {
+            let max_overhead =
+                layout.align().saturating_sub(GRANULARITY / 2) + mem::size_of::<UsedBlockHdr>();
+            let search_size = layout.size().checked_add(max_overhead)?;
+            let search_size = search_size.checked_add(GRANULARITY - 1)? & !(GRANULARITY - 1);
+            let list_min_size = Self::map_ceil_and_unmap(search_size)?;
+
+            free_block_size = free_block_size.max(consumed.checked_add(list_min_size)?);
+            consumed = consumed.checked_add(search_size)?;
+        }
+
+        // Add the sentinel block size
+        let pool_size = free_block_size.checked_add(POOL_OVERHEAD)?;
+
+        if let Some(max_pool_size) = Self::MAX_POOL_SIZE {
+            if pool_size > max_pool_size {
+                return None;
+            }
+        }
+
+        Some(pool_size)
+    }
+
+    /// Attempt to allocate a block of memory.
+    ///
+    /// Returns the starting address of the allocated memory block on success;
+    /// `None` otherwise.
+    ///
+    /// `layout.align()` must be a nonzero power of two. [`Layout`] upholds
+    /// this unless it was created by [`Layout::from_size_align_unchecked`]
+    /// in violation of its safety requirements, so code constructing a
+    /// `Layout` from untrusted values (e.g., through FFI) should use
+    /// [`Layout::from_size_align`] instead.
+    ///
+    /// A zero-sized allocation is served like any other: it occupies a
+    /// memory block of the minimum size, and the returned pointer is
+    /// suitably aligned, distinct from the other live allocations, and must
+    /// be deallocated. A dangling pointer is never returned because
+    /// [`Self::deallocate`] reads the block header preceding the pointer.
+    /// An implementation of an allocator interface that prefers returning a
+    /// dangling pointer for zero-sized requests must handle them by itself.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics unless [`Self::set_max_alloc_size_panics`]
+    /// is enabled, in which case it panics if `layout.size()` exceeds
+    /// [`Self::max_alloc_size`]. In debug builds, it also panics if
+    /// `layout.align()` is not a nonzero power of two.
+    #[inline]
     pub fn allocate(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        if self.exceeds_max_alloc_size(layout.size()) {
+            return None;
+        }
+
+        #[cfg(feature = "fault-injection")]
+        if self.should_inject_failure(layout) {
+            return None;
+        }
+
+        self.allocate_inner(layout)
+    }
+
+    /// Attempt to allocate a memory block suitable for storing a value of
+    /// type `T`. This is [`Self::allocate`] with [`Layout::new::<T>()`]
+    /// and a typed result.
+    ///
+    /// The memory block is uninitialized. Use [`Self::deallocate_as`] to
+    /// deallocate it, which derives the alignment from `T` as well.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// #[repr(align(64))]
+    /// struct Node {
+    ///     next: Option<std::ptr::NonNull<Node>>,
+    /// }
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let node = tlsf.allocate_as::<Node>().unwrap();
+    /// assert_eq!(node.as_ptr() as usize % 64, 0);
+    /// unsafe {
+    ///     node.as_ptr().write(Node { next: None });
+    ///     tlsf.deallocate_as(node);
+    /// }
+    /// ```
+    #[inline]
+    pub fn allocate_as<T>(&mut self) -> Option<NonNull<T>> {
+        self.allocate(Layout::new::<T>()).map(NonNull::cast)
+    }
+
+    /// [`Self::allocate`] without consulting the [`FailPolicy`].
+    ///
+    /// [`FailPolicy`]: crate::FailPolicy
+    pub(crate) fn allocate_inner(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        // The mask computations below assume this
+        debug_assert!(
+            layout.align().is_power_of_two(),
+            "`layout.align()` must be a nonzero power of two"
+        );
+
         unsafe {
+            if BEST_FIT {
+                let (block, size) = self.search_best_fit_free_block(layout)?;
+                self.unlink_free_block(block, size);
+                return Some(self.allocate_from_free_block(block, size, layout));
+            }
+
             // The extra bytes consumed by the header and padding.
             //
             // After choosing a free block, we need to adjust the payload's location
@@ -794,7 +1686,6 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
                 // Safety: It's unreachable
                 unreachable_unchecked()
             });
-            let mut next_phys_block = block.as_ref().common.next_phys_block();
             let size_and_flags = block.as_ref().common.size;
             let size = size_and_flags /* size_and_flags & SIZE_SIZE_MASK */;
             debug_assert_eq!(size, size_and_flags & SIZE_SIZE_MASK);
@@ -802,10 +1693,11 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
             debug_assert!(size >= search_size);
 
             // Unlink the free block. We are not using `unlink_free_block` because
-            // we already know `(fl, sl)` and that `block.prev_free` is `None`.
+            // we already know `(fl, sl)` and that `block` is the head (whose
+            // `prev_free` is `None`, or the tail if `FIFO`).
             *first_free = block.as_ref().next_free;
             if let Some(mut next_free) = *first_free {
-                next_free.as_mut().prev_free = None;
+                next_free.as_mut().prev_free = if FIFO { block.as_ref().prev_free } else { None };
             } else {
                 // The free list is now empty - update the bitmap
                 let sl_bitmap = self.sl_bitmap.get_unchecked_mut(fl);
@@ -815,79 +1707,1123 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
                 }
             }
 
-            // Decide the starting address of the payload
-            let unaligned_ptr = block.as_ptr() as *mut u8 as usize + mem::size_of::<UsedBlockHdr>();
-            let ptr = NonNull::new_unchecked(
-                (unaligned_ptr.wrapping_add(layout.align() - 1) & !(layout.align() - 1)) as *mut u8,
-            );
+            let ptr = self.allocate_from_free_block(block, size, layout);
+            debug_assert!(ptr.as_ptr() as usize - block.as_ptr() as usize <= max_overhead);
 
-            if layout.align() < GRANULARITY {
-                debug_assert_eq!(unaligned_ptr, ptr.as_ptr() as usize);
-            } else {
-                debug_assert_ne!(unaligned_ptr, ptr.as_ptr() as usize);
+            Some(ptr)
+        }
+    }
+
+    /// [`Self::allocate`] with an additional search pass that reduces
+    /// over-allocation for requests with large alignments.
+    ///
+    /// `allocate` looks for a free block that can contain the allocation
+    /// wherever the free block is located, assuming the worst-case padding
+    /// needed to align the payload. As a result, it skips the free list
+    /// containing the blocks of roughly the requested size, some of which
+    /// might be large enough when the actual padding is considered. This
+    /// method examines up to `MAX_PROBES` free blocks from that list and uses
+    /// the first one that fits, before falling back to `allocate`.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time (`O(MAX_PROBES)`).
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::allocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let ptr = tlsf.allocate_probing::<4>(Layout::from_size_align(4000, 256).unwrap());
+    /// assert!(ptr.is_some());
+    /// ```
+    pub fn allocate_probing<const MAX_PROBES: usize>(
+        &mut self,
+        layout: Layout,
+    ) -> Option<NonNull<u8>> {
+        if self.exceeds_max_alloc_size(layout.size()) {
+            return None;
+        }
+
+        #[cfg(feature = "fault-injection")]
+        if self.should_inject_failure(layout) {
+            return None;
+        }
+
+        if let Some((block, size)) = self.probe_free_block_for_allocation(layout, MAX_PROBES) {
+            // Safety: `block` is a free block of size `size` owned by `self`,
+            //         and it can contain the allocation
+            unsafe {
+                self.unlink_free_block(block, size);
+                return Some(self.allocate_from_free_block(block, size, layout));
             }
+        }
+
+        self.allocate_inner(layout)
+    }
+
+    /// [`Self::allocate`] with the memory block's size rounded up to the
+    /// minimum size of the size class (the free list) it falls into.
+    ///
+    /// `allocate` splits a free block at the exact size needed by the
+    /// allocation, so the used block and the remainder can have arbitrary
+    /// sizes (in multiples of `GRANULARITY`). With this method, used blocks
+    /// have regular sizes instead: when one is deallocated, the resulting
+    /// free block is more likely to be reused for another allocation of a
+    /// similar size. This comes at the cost of over-allocating by up to
+    /// `1 / SLLEN` of the requested size. Allocations with alignments of
+    /// `GRANULARITY` or larger still include padding, which makes their
+    /// blocks somewhat larger than their size classes.
+    ///
+    /// The returned memory block can be passed to any method that takes an
+    /// allocation made by `allocate`.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::allocate`].
+    /// The limit applies to `layout.size()` before rounding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let ptr = tlsf.allocate_class_rounded(Layout::from_size_align(1000, 8).unwrap()).unwrap();
+    /// unsafe { tlsf.deallocate(ptr, 8) };
+    /// ```
+    pub fn allocate_class_rounded(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        if self.exceeds_max_alloc_size(layout.size()) {
+            return None;
+        }
 
-            // Calculate the actual overhead and the final block size of the
-            // used block being created here
-            let overhead = ptr.as_ptr() as usize - block.as_ptr() as usize;
-            debug_assert!(overhead <= max_overhead);
+        #[cfg(feature = "fault-injection")]
+        if self.should_inject_failure(layout) {
+            return None;
+        }
 
-            let new_size = overhead + layout.size();
-            let new_size = (new_size + GRANULARITY - 1) & !(GRANULARITY - 1);
-            debug_assert!(new_size <= search_size);
+        // The block size needed by the allocation, excluding the padding
+        let block_size = layout.size().checked_add(mem::size_of::<UsedBlockHdr>())?;
+        let block_size = block_size.checked_add(GRANULARITY - 1)? & !(GRANULARITY - 1);
 
-            if new_size == size {
-                // The allocation completely fills this free block.
-                // Updating `next_phys_block.prev_phys_block` is unnecessary in this
-                // case because it's still supposed to point to `block`.
-            } else {
-                // The allocation partially fills this free block. Create a new
-                // free block header at `block + new_size..block + size`
-                // of length (`new_free_block_size`).
-                let mut new_free_block: NonNull<FreeBlockHdr> =
-                    NonNull::new_unchecked(block.cast::<u8>().as_ptr().add(new_size)).cast();
-                let new_free_block_size = size - new_size;
+        // Round it up to the size class boundary and let the payload take up
+        // the extra room
+        let block_size = Self::map_ceil_and_unmap(block_size)?;
+        let size = block_size - mem::size_of::<UsedBlockHdr>();
+        debug_assert!(size >= layout.size());
 
-                // Update `next_phys_block.prev_phys_block` to point to this new
-                // free block
-                // Invariant: No two adjacent free blocks
-                debug_assert!((next_phys_block.as_ref().size & SIZE_USED) != 0);
-                next_phys_block.as_mut().prev_phys_block = Some(new_free_block.cast());
+        self.allocate_inner(Layout::from_size_align(size, layout.align()).ok()?)
+    }
 
-                // Create the new free block header
-                new_free_block.as_mut().common = BlockHdr {
-                    size: new_free_block_size,
-                    prev_phys_block: Some(block.cast()),
-                };
-                self.link_free_block(new_free_block, new_free_block_size);
+    /// [`Self::allocate`] that places the allocation at the end of the chosen
+    /// free block instead of the beginning.
+    ///
+    /// The leftover of the free block remains at the lower addresses. Using
+    /// this method for short-lived allocations and `allocate` for long-lived
+    /// ones keeps them apart, so that long-lived allocations are packed
+    /// together and the holes left by short-lived ones are more likely to
+    /// coalesce into large free blocks.
+    ///
+    /// The returned memory block can be passed to any method that takes an
+    /// allocation made by `allocate`.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::allocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let layout = Layout::from_size_align(1000, 8).unwrap();
+    /// let long_lived = tlsf.allocate(layout).unwrap();
+    /// let short_lived = tlsf.allocate_from_end(layout).unwrap();
+    /// assert!(short_lived > long_lived);
+    ///
+    /// unsafe { tlsf.deallocate(short_lived, 8) };
+    /// # unsafe { tlsf.deallocate(long_lived, 8) };
+    /// ```
+    pub fn allocate_from_end(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        if self.exceeds_max_alloc_size(layout.size()) {
+            return None;
+        }
+
+        #[cfg(feature = "fault-injection")]
+        if self.should_inject_failure(layout) {
+            return None;
+        }
+
+        // This chooses the free block in the same way as `allocate`
+        let (block, size, _, _) = self.search_free_block_for_allocation(layout)?;
+
+        // Safety: `block` is a free block of size `size` owned by `self`, and
+        //         it can contain the allocation
+        unsafe {
+            self.unlink_free_block(block, size);
+            Some(self.allocate_from_free_block_end(block, size, layout))
+        }
+    }
+
+    /// Attempt to allocate a block of memory whose starting address `p`
+    /// satisfies `(p - offset) % align == 0`, i.e., is `offset` bytes past an
+    /// `align`-byte boundary.
+    ///
+    /// This is useful for hardware buffers that must start at a specific
+    /// position relative to an alignment boundary, which can't be expressed
+    /// by [`Layout`]. The allocation is made by over-allocating `offset %
+    /// align` bytes with [`Self::allocate`] and moving the payload forward.
+    ///
+    /// Returns `None` if the allocation fails or `align` is not a power of
+    /// two. The returned memory block must be deallocated by
+    /// [`Self::deallocate_with_offset`] with the same `align` and `offset`.
+    /// It can't be passed to the other methods that take an allocation, such
+    /// as [`Self::reallocate`].
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::allocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// // 256 bytes starting at offset 16 within a 64-byte cache line
+    /// let ptr = tlsf.allocate_with_offset(256, 64, 16).unwrap();
+    /// assert_eq!(ptr.as_ptr() as usize % 64, 16);
+    ///
+    /// unsafe { tlsf.deallocate_with_offset(ptr, 64, 16) };
+    /// ```
+    pub fn allocate_with_offset(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: usize,
+    ) -> Option<NonNull<u8>> {
+        let offset = offset & align.wrapping_sub(1);
+        let layout = Layout::from_size_align(size.checked_add(offset)?, align).ok()?;
+        let ptr = self.allocate(layout)?;
+        // Safety: `ptr + offset` is inside the allocation, so it's not null
+        Some(unsafe { NonNull::new_unchecked(ptr.as_ptr().add(offset)) })
+    }
+
+    /// Turn the end of an unlinked free block into a used block storing the
+    /// specified allocation, and return the leftover at the beginning to the
+    /// free lists.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be a free block of size `size` owned by `self` and
+    /// already unlinked from the free lists. `size` must be at least as
+    /// large as the search size calculated by [`Self::allocate`] for the
+    /// allocation.
+    #[inline]
+    unsafe fn allocate_from_free_block_end(
+        &mut self,
+        block: NonNull<FreeBlockHdr>,
+        size: usize,
+        layout: Layout,
+    ) -> NonNull<u8> {
+        let block_start = block.as_ptr() as usize;
+        let block_end = block_start + size;
+
+        // Decide the starting addresses of the used block and the payload.
+        // The used block extends to the end of `block`.
+        let (used_block_start, ptr) = if layout.align() < GRANULARITY {
+            // The payload immediately follows the header (see
+            // `used_block_hdr_for_allocation`).
+            // `size >= layout.size() + GRANULARITY / 2` ensures that
+            // `used_block_start >= block_start`.
+            let used_block_start =
+                (block_end - layout.size() - GRANULARITY / 2) & !(GRANULARITY - 1);
+            (used_block_start, used_block_start + GRANULARITY / 2)
+        } else {
+            // The payload is preceded by a `UsedBlockPad`. `ptr` is aligned
+            // to `GRANULARITY`, so the header is placed `GRANULARITY` bytes
+            // before it. `size >= layout.size() + layout.align()` ensures
+            // that `ptr > block_start` and hence
+            // `used_block_start >= block_start`.
+            let ptr = (block_end - layout.size()) & !(layout.align() - 1);
+            (ptr - GRANULARITY, ptr)
+        };
+        debug_assert!(used_block_start >= block_start);
+        debug_assert!(ptr + layout.size() <= block_end);
+
+        let ptr = NonNull::new_unchecked(ptr as *mut u8);
+        let mut used_block = NonNull::new_unchecked(used_block_start as *mut UsedBlockHdr);
+        let new_size = block_end - used_block_start;
+
+        if new_size == size {
+            // The allocation completely fills this free block.
+            // `used_block == block`, so `prev_phys_block` is already set.
+        } else {
+            // The allocation partially fills this free block. Shrink `block`
+            // to `block..used_block` and create a new used block header
+            // at `used_block`.
+            let mut block = block;
+            let new_free_block_size = size - new_size;
+            block.as_mut().common.size = new_free_block_size;
+            self.link_free_block(block, new_free_block_size);
+
+            used_block.as_mut().common.prev_phys_block = Some(block.cast());
+
+            // Update `next_phys_block.prev_phys_block` to point to the used
+            // block
+            // `next_phys_block` is usually a used block, but it might be a free
+            // block if `COALESCE` is `false` or was `false` before `rebind`
+            let mut next_phys_block = NonNull::new_unchecked(block_end as *mut BlockHdr);
+            next_phys_block.as_mut().prev_phys_block = Some(used_block.cast());
+        }
+
+        used_block.as_mut().common.size = new_size | SIZE_USED;
+
+        // Place a `UsedBlockPad` (used by `used_block_hdr_for_allocation`)
+        if layout.align() >= GRANULARITY {
+            (*UsedBlockPad::get_for_allocation(ptr)).block_hdr = used_block;
+        }
+
+        #[cfg(feature = "stats")]
+        {
+            self.num_allocations = self.num_allocations.wrapping_add(1);
+        }
+
+        ptr
+    }
+
+    /// Examine up to `max_probes` free blocks from the free list that
+    /// [`Self::allocate`] skips, and return the first one that can contain
+    /// the specified allocation along with its size.
+    fn probe_free_block_for_allocation(
+        &self,
+        layout: Layout,
+        max_probes: usize,
+    ) -> Option<(NonNull<FreeBlockHdr>, usize)> {
+        // This follows the same steps as `allocate`. See there for details.
+        let max_overhead =
+            layout.align().saturating_sub(GRANULARITY / 2) + mem::size_of::<UsedBlockHdr>();
+
+        let search_size = layout.size().checked_add(max_overhead)?;
+        let search_size = search_size.checked_add(GRANULARITY - 1)? & !(GRANULARITY - 1);
+
+        // `map_ceil(search_size)` skips this list unless `search_size` is its
+        // minimum block size
+        let (fl, sl) = Self::map_floor(search_size)?;
+
+        // Safety: `map_floor` returns `fl < FLLEN` and `sl < SLLEN`
+        let mut next_free = unsafe { *self.first_free.get_unchecked(fl).get_unchecked(sl) };
+        for _ in 0..max_probes {
+            let block = next_free?;
+            // Safety: `block` is a free block owned by `self`
+            let size = unsafe { block.as_ref().common.size };
+            debug_assert_eq!(size, size & SIZE_SIZE_MASK);
+
+            if Self::fits_in_free_block(block, size, layout) {
+                return Some((block, size));
             }
 
-            // Turn `block` into a used memory block and initialize the used block
-            // header. `prev_phys_block` is already set.
-            let mut block = block.cast::<UsedBlockHdr>();
-            block.as_mut().common.size = new_size | SIZE_USED;
+            // Safety: `block` is a free block owned by `self`
+            next_free = unsafe { block.as_ref().next_free };
+        }
+
+        None
+    }
+
+    /// Check if the free block `block` of size `size` can contain the
+    /// specified allocation, considering the actual padding needed at the
+    /// block's location.
+    #[inline]
+    fn fits_in_free_block(block: NonNull<FreeBlockHdr>, size: usize, layout: Layout) -> bool {
+        let unaligned_ptr = block.as_ptr() as usize + mem::size_of::<UsedBlockHdr>();
+        let ptr = unaligned_ptr.wrapping_add(layout.align() - 1) & !(layout.align() - 1);
+        let overhead = ptr.wrapping_sub(block.as_ptr() as usize);
+
+        // `size` is a multiple of `GRANULARITY`, so rounding up the left-hand
+        // side doesn't change the result
+        overhead
+            .checked_add(layout.size())
+            .map_or(false, |new_size| new_size <= size)
+    }
+
+    /// Turn an unlinked free block into a used block storing the specified
+    /// allocation, and return the leftover to the free lists.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be a free block of size `size` owned by `self` and
+    /// already unlinked from the free lists. `block` must be able to contain
+    /// the allocation, i.e., [`Self::fits_in_free_block`] must return `true`.
+    #[inline]
+    unsafe fn allocate_from_free_block(
+        &mut self,
+        block: NonNull<FreeBlockHdr>,
+        size: usize,
+        layout: Layout,
+    ) -> NonNull<u8> {
+        let mut next_phys_block = block.as_ref().common.next_phys_block();
+
+        // Decide the starting address of the payload
+        let unaligned_ptr = block.as_ptr() as *mut u8 as usize + mem::size_of::<UsedBlockHdr>();
+        let ptr = NonNull::new_unchecked(
+            (unaligned_ptr.wrapping_add(layout.align() - 1) & !(layout.align() - 1)) as *mut u8,
+        );
+
+        if layout.align() < GRANULARITY {
+            debug_assert_eq!(unaligned_ptr, ptr.as_ptr() as usize);
+        } else {
+            debug_assert_ne!(unaligned_ptr, ptr.as_ptr() as usize);
+        }
+
+        // Calculate the actual overhead and the final block size of the
+        // used block being created here
+        let overhead = ptr.as_ptr() as usize - block.as_ptr() as usize;
+
+        let new_size = overhead + layout.size();
+        let new_size = (new_size + GRANULARITY - 1) & !(GRANULARITY - 1);
+        debug_assert!(new_size <= size);
+
+        if new_size == size {
+            // The allocation completely fills this free block.
+            // Updating `next_phys_block.prev_phys_block` is unnecessary in this
+            // case because it's still supposed to point to `block`.
+        } else {
+            // The allocation partially fills this free block. Create a new
+            // free block header at `block + new_size..block + size`
+            // of length (`new_free_block_size`).
+            let mut new_free_block: NonNull<FreeBlockHdr> =
+                NonNull::new_unchecked(block.cast::<u8>().as_ptr().add(new_size)).cast();
+            let new_free_block_size = size - new_size;
+
+            // Update `next_phys_block.prev_phys_block` to point to this new
+            // free block
+            // `next_phys_block` is usually a used block, but it might be a free
+            // block if `COALESCE` is `false` or was `false` before `rebind`
+            next_phys_block.as_mut().prev_phys_block = Some(new_free_block.cast());
+
+            // Create the new free block header
+            new_free_block.as_mut().common = BlockHdr {
+                size: new_free_block_size,
+                prev_phys_block: Some(block.cast()),
+            };
+            self.link_free_block(new_free_block, new_free_block_size);
+        }
+
+        // Turn `block` into a used memory block and initialize the used block
+        // header. `prev_phys_block` is already set.
+        let mut block = block.cast::<UsedBlockHdr>();
+        block.as_mut().common.size = new_size | SIZE_USED;
+
+        // Place a `UsedBlockPad` (used by `used_block_hdr_for_allocation`)
+        if layout.align() >= GRANULARITY {
+            (*UsedBlockPad::get_for_allocation(ptr)).block_hdr = block;
+        }
+
+        #[cfg(feature = "stats")]
+        {
+            self.num_allocations = self.num_allocations.wrapping_add(1);
+        }
+
+        ptr
+    }
+
+    /// Check if [`Self::allocate`] would serve the specified allocation by
+    /// using up a whole free block, i.e., without splitting the free block
+    /// and leaving a remainder.
+    ///
+    /// Returns `false` if the allocation would fail.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    #[inline]
+    pub fn would_fill_exactly(&self, layout: Layout) -> bool {
+        self.allocation_remainder(layout) == Some(0)
+    }
+
+    /// Find the free block [`Self::allocate`] would use to serve the
+    /// specified allocation, without actually allocating it.
+    ///
+    /// Returns the free block's starting address, its size (including the
+    /// block header), and the indices `(fl, sl)` of the segregated free list
+    /// it was taken from. Returns `None` if the allocation would fail.
+    ///
+    /// This method doesn't consult the [`FailPolicy`] (if the
+    /// `fault-injection` feature is enabled).
+    ///
+    /// [`FailPolicy`]: crate::FailPolicy
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics. Allocations exceeding
+    /// [`Self::max_alloc_size`] are reported as failing even if
+    /// [`Self::set_max_alloc_size_panics`] is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let layout = Layout::from_size_align(100, 8).unwrap();
+    /// let (block, size, _fl, _sl) = tlsf.preview_allocation(layout).unwrap();
+    /// assert!(size >= 100);
+    ///
+    /// // `allocate` places the allocation in the previewed block
+    /// let ptr = tlsf.allocate(layout).unwrap();
+    /// assert!((ptr.as_ptr() as usize - block.as_ptr() as usize) < size);
+    /// ```
+    pub fn preview_allocation(&self, layout: Layout) -> Option<(NonNull<u8>, usize, usize, usize)> {
+        if matches!(self.max_alloc_size, Some(max_alloc_size) if layout.size() > max_alloc_size) {
+            return None;
+        }
+
+        let (block, size, fl, sl) = self.search_free_block_for_allocation(layout)?;
+        Some((block.cast(), size, fl, sl))
+    }
+
+    /// Attempt to allocate a block of memory that satisfies `min_layout`,
+    /// preferably with enough room for `preferred_layout`. This is useful for
+    /// a growable buffer because the extra room makes later in-place
+    /// reallocation more likely to succeed.
+    ///
+    /// The allocation is aligned to `max(min_layout.align(),
+    /// preferred_layout.align())`, which must be passed to the methods that
+    /// take the allocation's alignment, such as [`Self::deallocate`].
+    ///
+    /// Returns the allocated memory block on success; `None` otherwise. The
+    /// returned slice's length is the actual usable size, which is at least
+    /// `min_layout.size()` and might be larger than `preferred_layout.size()`.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 65536];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let block = tlsf.allocate_with_headroom(
+    ///     Layout::from_size_align(100, 8).unwrap(),
+    ///     Layout::from_size_align(1000, 8).unwrap(),
+    /// ).unwrap();
+    /// let len = unsafe { &*(block.as_ptr() as *const [MaybeUninit<u8>]) }.len();
+    /// assert!(len >= 1000);
+    ///
+    /// unsafe { tlsf.deallocate(block.cast(), 8) };
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This method panics if [`Self::allocate`] would panic for `min_layout`.
+    /// If `preferred_layout.size()` exceeds [`Self::max_alloc_size`], the
+    /// headroom is just not provided.
+    pub fn allocate_with_headroom(
+        &mut self,
+        min_layout: Layout,
+        preferred_layout: Layout,
+    ) -> Option<NonNull<[u8]>> {
+        let align = min_layout.align().max(preferred_layout.align());
+        let preferred_size = preferred_layout.size().max(min_layout.size());
+
+        let min_layout = Layout::from_size_align(min_layout.size(), align).ok()?;
+        let preferred_layout = Layout::from_size_align(preferred_size, align)
+            .ok()
+            .filter(|_| {
+                self.max_alloc_size
+                    .map_or(true, |max| preferred_size <= max)
+            });
+
+        if self.exceeds_max_alloc_size(min_layout.size()) {
+            return None;
+        }
+
+        #[cfg(feature = "fault-injection")]
+        if self.should_inject_failure(min_layout) {
+            return None;
+        }
+
+        let ptr = preferred_layout
+            .and_then(|layout| self.allocate_inner(layout))
+            .or_else(|| self.allocate_inner(min_layout))?;
+
+        // Safety: `ptr` was just allocated with alignment `align`
+        let len = unsafe { Self::size_of_allocation(ptr, align) };
+
+        Some(nonnull_slice_from_raw_parts(ptr, len))
+    }
+
+    /// Write a human-readable summary of the memory pools' state to `w`.
+    ///
+    /// The summary consists of a few lines reporting the total capacity of
+    /// the memory pools, the number of free bytes, the size of the largest
+    /// free block, the number of free blocks, and the fragmentation
+    /// percentage, which is the percentage of free bytes that are not in the
+    /// largest free block. All sizes include the memory block headers.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time (`O(number_of_free_blocks)`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let mut report = String::new();
+    /// tlsf.write_report(&mut report).unwrap();
+    /// println!("{}", report);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    pub fn write_report(&self, w: &mut impl core::fmt::Write) -> core::fmt::Result {
+        let stats = self.stats();
+
+        let fragmentation_percent = if stats.free_bytes == 0 {
+            0
+        } else {
+            (stats.free_bytes - stats.largest_free_block) as u64 * 100 / stats.free_bytes as u64
+        };
+
+        writeln!(w, "capacity: {} bytes", stats.total_bytes)?;
+        writeln!(w, "free: {} bytes", stats.free_bytes)?;
+        writeln!(w, "largest free block: {} bytes", stats.largest_free_block)?;
+        writeln!(w, "free blocks: {}", stats.num_free_blocks)?;
+        writeln!(w, "fragmentation: {}%", fragmentation_percent)
+    }
+
+    /// Take a snapshot of the memory pools' state.
+    ///
+    /// The free blocks are found by walking the free lists, so the memory
+    /// pools don't have to be specified.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(FLLEN * SLLEN + number_of_free_blocks)`), not in constant time,
+    /// because it visits every free list and every free block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{Tlsf, POOL_OVERHEAD};
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let stats = tlsf.stats();
+    /// assert_eq!(stats.num_free_blocks, 1);
+    /// assert_eq!(stats.used_bytes, POOL_OVERHEAD);
+    ///
+    /// let ptr = tlsf.allocate(Layout::new::<[u8; 100]>()).unwrap();
+    /// let stats = tlsf.stats();
+    /// assert!(stats.used_bytes >= POOL_OVERHEAD + 100);
+    /// assert_eq!(stats.used_bytes + stats.free_bytes, stats.total_bytes);
+    /// # unsafe { tlsf.deallocate(ptr, 1) };
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut free_bytes = 0usize;
+        let mut num_free_blocks = 0usize;
+
+        for first_free in self.first_free.iter().flatten() {
+            let mut next_free = *first_free;
+            while let Some(block) = next_free {
+                // Safety: All free blocks in the free lists are owned by `self`
+                let block = unsafe { block.as_ref() };
+                let size = block.common.size;
+                debug_assert_eq!(size, size & SIZE_SIZE_MASK);
+
+                free_bytes = free_bytes.wrapping_add(size);
+                num_free_blocks += 1;
+                next_free = block.next_free;
+            }
+        }
+
+        Stats {
+            total_bytes: self.pool_capacity,
+            used_bytes: self.pool_capacity.wrapping_sub(free_bytes),
+            free_bytes,
+            largest_free_block: self.largest_free_block(),
+            num_free_blocks,
+        }
+    }
+
+    /// Get the size of the largest free block, or `0` if there's none.
+    ///
+    /// The largest free block is in the highest non-empty free list, so only
+    /// that list is walked.
+    fn largest_free_block(&self) -> usize {
+        if self.fl_bitmap == FLBitmap::ZERO {
+            return 0;
+        }
+        let fl = FLBitmap::BITS - 1 - self.fl_bitmap.leading_zeros();
+        let sl_bitmap = self.sl_bitmap[fl as usize];
+        debug_assert!(sl_bitmap != SLBitmap::ZERO);
+        let sl = SLBitmap::BITS - 1 - sl_bitmap.leading_zeros();
+
+        let mut largest_free_block = 0;
+        let mut next_free = self.first_free[fl as usize][sl as usize];
+        while let Some(block) = next_free {
+            // Safety: All free blocks in the free lists are owned by `self`
+            let block = unsafe { block.as_ref() };
+            largest_free_block = largest_free_block.max(block.common.size);
+            next_free = block.next_free;
+        }
+        largest_free_block
+    }
+
+    /// Get the counts of deallocations classified by how the deallocated
+    /// memory blocks were merged with their neighbors.
+    ///
+    /// The counts accumulate over the lifetime of `self` and are carried over
+    /// by [`Self::rebind`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let ptr1 = tlsf.allocate(layout).unwrap();
+    /// let ptr2 = tlsf.allocate(layout).unwrap();
+    /// unsafe { tlsf.deallocate(ptr1, layout.align()) };
+    /// unsafe { tlsf.deallocate(ptr2, layout.align()) };
+    ///
+    /// // `ptr1`'s block had no free neighbor. `ptr2`'s block was merged
+    /// // with `ptr1`'s block and the trailing free space.
+    /// let stats = tlsf.coalesce_stats();
+    /// assert_eq!(stats.forward, 0);
+    /// assert_eq!(stats.backward, 0);
+    /// assert_eq!(stats.both, 1);
+    /// assert_eq!(stats.none, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    #[inline]
+    pub fn coalesce_stats(&self) -> CoalesceStats {
+        self.coalesce_stats
+    }
+
+    /// Check if there are no live allocations.
+    ///
+    /// This is the precondition for reclaiming the memory pools, e.g., by
+    /// dropping `self` or calling [`FlexTlsf::reset`], without invalidating
+    /// any allocations. Memory blocks passed to [`Self::deallocate_deferred`]
+    /// count as live until they are [flushed](Self::flush_deferred_frees).
+    ///
+    /// The allocations taken over by [`Self::absorb`] count as allocations
+    /// of `self`.
+    ///
+    /// [`FlexTlsf::reset`]: crate::FlexTlsf::reset
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time. The number of live
+    /// allocations is maintained when the `stats` feature is enabled.
+    /// Without the feature, [`Self::is_pool_empty`] can check each memory
+    /// pool instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    /// assert!(tlsf.is_empty());
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let ptr = tlsf.allocate(layout).unwrap();
+    /// assert!(!tlsf.is_empty());
+    ///
+    /// unsafe { tlsf.deallocate(ptr, layout.align()) };
+    /// assert!(tlsf.is_empty());
+    /// ```
+    #[cfg(feature = "stats")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "stats")))]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.num_allocations == 0
+    }
+
+    /// Check if there are no live allocations in the specified memory pool,
+    /// i.e., all of its memory blocks are free.
+    ///
+    /// Unlike [`Self::is_empty`], this method doesn't require the `stats`
+    /// feature and examines only one memory pool. Memory blocks passed to
+    /// [`Self::deallocate_deferred`] count as live until they are
+    /// [flushed](Self::flush_deferred_frees).
+    ///
+    /// If [`Self::insert_free_block_ptr`] divided the memory pool into
+    /// multiple chunks to meet the maximum block size requirement, all of
+    /// them are examined.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(number_of_blocks_in_pool)`). If the memory pool is empty, and
+    /// [coalescing on free](#coalescing-on-free) is enabled, each chunk
+    /// consists of one free block, so this is proportional to the number of
+    /// chunks.
+    ///
+    /// # Safety
+    ///
+    /// `pool` must precisely represent a memory pool that belongs to `self`.
+    /// See [`Self::iter_blocks`] for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::{NonNull, slice_from_raw_parts_mut}};
+    ///
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let pool_ptr = NonNull::new(unsafe { POOL.as_mut_ptr() }).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let pool_len = unsafe { tlsf.insert_free_block_ptr(pool_ptr) }.unwrap().get();
+    /// let pool_ptr = NonNull::new(
+    ///     slice_from_raw_parts_mut(pool_ptr.as_ptr() as *mut u8, pool_len)
+    /// ).unwrap();
+    /// assert!(unsafe { tlsf.is_pool_empty(pool_ptr) });
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let ptr = tlsf.allocate(layout).unwrap();
+    /// assert!(!unsafe { tlsf.is_pool_empty(pool_ptr) });
+    ///
+    /// unsafe { tlsf.deallocate(ptr, layout.align()) };
+    /// assert!(unsafe { tlsf.is_pool_empty(pool_ptr) });
+    /// ```
+    pub unsafe fn is_pool_empty(&self, pool: NonNull<[u8]>) -> bool {
+        let len = nonnull_slice_len(pool);
+
+        // Round up the starting address in the same way as `iter_blocks` does
+        let unaligned_start = pool.as_ptr() as *mut u8 as usize;
+        let start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+        let len = len.saturating_sub(start.wrapping_sub(unaligned_start));
+        if len == 0 {
+            return true;
+        }
+
+        let mut block = NonNull::new_unchecked(start as *mut BlockHdr);
+
+        loop {
+            let size_and_flags = block.as_ref().size;
+            if (size_and_flags & SIZE_SENTINEL) != 0 {
+                // `insert_free_block_ptr` places chunks back to back, so
+                // the next one (if any) starts right after the sentinel block
+                let next = (block.as_ptr() as usize).wrapping_add(size_and_flags & SIZE_SIZE_MASK);
+                if next.wrapping_sub(start) >= len {
+                    return true;
+                }
+                block = NonNull::new_unchecked(next as *mut BlockHdr);
+            } else if (size_and_flags & SIZE_USED) != 0 {
+                return false;
+            } else {
+                block = block.as_ref().next_phys_block();
+            }
+        }
+    }
+
+    /// Limit the size of allocation requests. `None` means no limit, which is
+    /// the default.
+    ///
+    /// Allocation requests larger than the limit fail without touching the
+    /// memory pools, or panic if [`Self::set_max_alloc_size_panics`] is
+    /// enabled. This serves as a guardrail against requesting an absurd size
+    /// because of a bug (e.g., a length field read from corrupted data).
+    /// The limit applies to [`Self::allocate`], its variants, and
+    /// [`Self::reallocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    /// tlsf.set_max_alloc_size(Some(256));
+    ///
+    /// assert!(tlsf.allocate(Layout::from_size_align(256, 1).unwrap()).is_some());
+    /// assert!(tlsf.allocate(Layout::from_size_align(257, 1).unwrap()).is_none());
+    /// ```
+    #[inline]
+    pub fn set_max_alloc_size(&mut self, max_alloc_size: Option<usize>) {
+        self.max_alloc_size = max_alloc_size;
+    }
+
+    /// Get the limit set by [`Self::set_max_alloc_size`].
+    #[inline]
+    pub fn max_alloc_size(&self) -> Option<usize> {
+        self.max_alloc_size
+    }
+
+    /// Specify whether allocation requests exceeding
+    /// [`Self::max_alloc_size`] should panic instead of failing. The default
+    /// is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use rlsf::Tlsf;
+    /// use std::alloc::Layout;
+    ///
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.set_max_alloc_size(Some(256));
+    /// tlsf.set_max_alloc_size_panics(true);
+    ///
+    /// tlsf.allocate(Layout::from_size_align(257, 1).unwrap());
+    /// ```
+    #[inline]
+    pub fn set_max_alloc_size_panics(&mut self, panics: bool) {
+        self.max_alloc_size_panics = panics;
+    }
+
+    /// Return `true` if an allocation request of `size` bytes should fail
+    /// because of [`Self::max_alloc_size`]. Panics instead if
+    /// [`Self::set_max_alloc_size_panics`] is enabled.
+    #[inline]
+    fn exceeds_max_alloc_size(&self, size: usize) -> bool {
+        match self.max_alloc_size {
+            Some(max_alloc_size) if size > max_alloc_size => {
+                if self.max_alloc_size_panics {
+                    max_alloc_size_exceeded(size, max_alloc_size);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set the policy for failing allocation requests artificially. This is
+    /// useful for testing how the application handles allocation failures.
+    ///
+    /// The policy applies to [`Self::allocate`] and
+    /// [`Self::allocate_with_headroom`]. The latter is evaluated based on its
+    /// `min_layout`. Setting a policy resets the count of allocation requests
+    /// used by [`FailPolicy::Nth`] and [`FailPolicy::AfterNth`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{FailPolicy, Tlsf};
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u8, u8, 8, 8> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// tlsf.set_fail_policy(FailPolicy::Nth(1));
+    /// assert!(tlsf.allocate(Layout::new::<u64>()).is_some());
+    /// assert!(tlsf.allocate(Layout::new::<u64>()).is_none());
+    /// assert!(tlsf.allocate(Layout::new::<u64>()).is_some());
+    /// ```
+    ///
+    /// [`FailPolicy`]: crate::FailPolicy
+    /// [`FailPolicy::Nth`]: crate::FailPolicy::Nth
+    /// [`FailPolicy::AfterNth`]: crate::FailPolicy::AfterNth
+    #[cfg(feature = "fault-injection")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "fault-injection")))]
+    #[inline]
+    pub fn set_fail_policy(&mut self, policy: crate::FailPolicy) {
+        self.fault_injector.set_policy(policy);
+    }
+
+    /// Record an allocation request and return `true` if it should fail
+    /// according to the [`FailPolicy`] set by [`Self::set_fail_policy`].
+    ///
+    /// [`FailPolicy`]: crate::FailPolicy
+    #[cfg(feature = "fault-injection")]
+    #[inline]
+    pub(crate) fn should_inject_failure(&mut self, layout: Layout) -> bool {
+        self.fault_injector.should_fail(layout)
+    }
+
+    /// Get the size of the free block [`Self::allocate`] would create from the
+    /// leftover of the chosen free block when serving the specified
+    /// allocation. Returns `None` if the allocation would fail.
+    fn allocation_remainder(&self, layout: Layout) -> Option<usize> {
+        let (block, size, _, _) = self.search_free_block_for_allocation(layout)?;
+
+        let unaligned_ptr = block.as_ptr() as usize + mem::size_of::<UsedBlockHdr>();
+        let ptr = unaligned_ptr.wrapping_add(layout.align() - 1) & !(layout.align() - 1);
+        let overhead = ptr - block.as_ptr() as usize;
+
+        let new_size = overhead + layout.size();
+        let new_size = (new_size + GRANULARITY - 1) & !(GRANULARITY - 1);
+        debug_assert!(new_size <= size);
+
+        Some(size - new_size)
+    }
+
+    /// Find the free block [`Self::allocate`] would take to serve the
+    /// specified allocation. Returns the free block, its size, and the
+    /// indices of the free list containing it.
+    fn search_free_block_for_allocation(
+        &self,
+        layout: Layout,
+    ) -> Option<(NonNull<FreeBlockHdr>, usize, usize, usize)> {
+        if BEST_FIT {
+            let (block, size) = self.search_best_fit_free_block(layout)?;
+            // The free list containing `block`
+            let (fl, sl) = Self::map_floor(size)?;
+            return Some((block, size, fl, sl));
+        }
+
+        // This follows the same steps as `allocate`. See there for details.
+        let max_overhead =
+            layout.align().saturating_sub(GRANULARITY / 2) + mem::size_of::<UsedBlockHdr>();
+
+        let search_size = layout.size().checked_add(max_overhead)?;
+        let search_size = search_size.checked_add(GRANULARITY - 1)? & !(GRANULARITY - 1);
+        let (fl, sl) = self.search_suitable_free_block_list_for_allocation(search_size)?;
+
+        // Safety: `search_suitable_free_block_list_for_allocation` returns
+        //         `fl < FLLEN` and `sl < SLLEN`
+        let block = unsafe { *self.first_free.get_unchecked(fl).get_unchecked(sl) }?;
+        // Safety: `block` is a free block owned by `self`
+        let size = unsafe { block.as_ref().common.size };
+        debug_assert_eq!(size, size & SIZE_SIZE_MASK);
+        debug_assert!(size >= search_size);
+
+        Some((block, size, fl, sl))
+    }
+
+    /// Search for a non-empty free block list for allocation.
+    #[inline]
+    /// Find the smallest free block that is at least as large as the search
+    /// size of the allocation. Used by [`Self::allocate`] if `BEST_FIT` is
+    /// `true`.
+    ///
+    /// Returns the free block and its size.
+    fn search_best_fit_free_block(&self, layout: Layout) -> Option<(NonNull<FreeBlockHdr>, usize)> {
+        // This follows the same steps as `allocate`. See there for details.
+        let max_overhead =
+            layout.align().saturating_sub(GRANULARITY / 2) + mem::size_of::<UsedBlockHdr>();
+
+        let search_size = layout.size().checked_add(max_overhead)?;
+        let search_size = search_size.checked_add(GRANULARITY - 1)? & !(GRANULARITY - 1);
+
+        // `map_ceil(search_size)` skips this list unless `search_size` is its
+        // minimum block size. A large enough block in this list is smaller
+        // than any block in the lists above.
+        let (fl, sl) = Self::map_floor(search_size)?;
+        if let Some(found) = self.smallest_free_block_in_list(fl, sl, search_size) {
+            return Some(found);
+        }
+
+        // Every block in this list is large enough, and the lists above only
+        // contain larger blocks
+        let (fl, sl) = self.search_suitable_free_block_list_for_allocation(search_size)?;
+        self.smallest_free_block_in_list(fl, sl, search_size)
+    }
+
+    /// Find the smallest free block of at least `min_size` bytes in the free
+    /// list `(fl, sl)`.
+    ///
+    /// Returns the free block and its size.
+    fn smallest_free_block_in_list(
+        &self,
+        fl: usize,
+        sl: usize,
+        min_size: usize,
+    ) -> Option<(NonNull<FreeBlockHdr>, usize)> {
+        let mut best: Option<(NonNull<FreeBlockHdr>, usize)> = None;
+
+        let mut next_free = self.first_free[fl][sl];
+        while let Some(block) = next_free {
+            // Safety: `block` is a free block owned by `self`
+            let size = unsafe { block.as_ref().common.size };
+            debug_assert_eq!(size, size & SIZE_SIZE_MASK);
 
-            // Place a `UsedBlockPad` (used by `used_block_hdr_for_allocation`)
-            if layout.align() >= GRANULARITY {
-                (*UsedBlockPad::get_for_allocation(ptr)).block_hdr = block;
+            if size >= min_size && best.map_or(true, |(_, best_size)| size < best_size) {
+                best = Some((block, size));
             }
 
-            Some(ptr)
+            // Safety: `block` is a free block owned by `self`
+            next_free = unsafe { block.as_ref().next_free };
         }
+
+        best
     }
 
-    /// Search for a non-empty free block list for allocation.
-    #[inline]
     fn search_suitable_free_block_list_for_allocation(
         &self,
         min_size: usize,
     ) -> Option<(usize, usize)> {
         let (mut fl, mut sl) = Self::map_ceil(min_size)?;
 
+        // Safety: `map_ceil` returns `fl < FLLEN`
+        let sl_bitmap = unsafe { self.sl_bitmap.get_unchecked(fl) };
+
         // Search in range `(fl, sl..SLLEN)`
-        sl = self.sl_bitmap[fl].bit_scan_forward(sl as u32) as usize;
+        sl = sl_bitmap.bit_scan_forward(sl as u32) as usize;
         if sl < SLLEN {
-            debug_assert!(self.sl_bitmap[fl].get_bit(sl as u32));
+            debug_assert!(sl_bitmap.get_bit(sl as u32));
 
             return Some((fl, sl));
         }
@@ -897,13 +2833,16 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         if fl < FLLEN {
             debug_assert!(self.fl_bitmap.get_bit(fl as u32));
 
-            sl = self.sl_bitmap[fl].trailing_zeros() as usize;
+            // Safety: `fl < FLLEN`
+            let sl_bitmap = unsafe { self.sl_bitmap.get_unchecked(fl) };
+
+            sl = sl_bitmap.trailing_zeros() as usize;
             if sl >= SLLEN {
                 debug_assert!(false, "bitmap contradiction");
                 unsafe { unreachable_unchecked() };
             }
 
-            debug_assert!(self.sl_bitmap[fl].get_bit(sl as u32));
+            debug_assert!(sl_bitmap.get_bit(sl as u32));
             Some((fl, sl))
         } else {
             None
@@ -926,10 +2865,19 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         ptr: NonNull<u8>,
         align: usize,
     ) -> NonNull<UsedBlockHdr> {
+        debug_assert!(
+            align.is_power_of_two(),
+            "`align` must be a nonzero power of two"
+        );
+
         if align >= GRANULARITY {
             // Read the header pointer
             (*UsedBlockPad::get_for_allocation(ptr)).block_hdr
         } else {
+            // `align` is a power of two less than `GRANULARITY`, so it's at most
+            // `GRANULARITY / 2`. The header starts at a `GRANULARITY`-aligned
+            // address, so the payload following the header is already aligned
+            // and no padding is inserted (even if `align == GRANULARITY / 2`).
             NonNull::new_unchecked(ptr.as_ptr().sub(GRANULARITY / 2)).cast()
         }
     }
@@ -997,15 +2945,209 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     ///
     ///  - `ptr` must denote a memory block previously allocated via `self`.
     ///  - The memory block must have been allocated with the same alignment
-    ///    ([`Layout::align`]) as `align`.
+    ///    ([`Layout::align`]) as `align`. Consequently, `align` is a nonzero
+    ///    power of two.
+    ///
+    /// # Panics
     ///
+    /// This method never panics unless the `debug-checks` feature is enabled,
+    /// in which case it panics if it detects that `ptr` does not denote a
+    /// memory block in use (e.g., a double free). In debug builds, it also
+    /// panics if `align` is not a nonzero power of two.
     pub unsafe fn deallocate(&mut self, ptr: NonNull<u8>, align: usize) {
         // Safety: `ptr` is a previously allocated memory block with the same
         //         alignment as `align`. This is upheld by the caller.
         let block = Self::used_block_hdr_for_allocation(ptr, align).cast::<BlockHdr>();
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block);
         self.deallocate_block(block);
     }
 
+    /// Deallocate a memory block previously allocated by
+    /// [`Self::allocate_as`].
+    ///
+    /// The value stored in the memory block is not dropped.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via
+    ///    `self.allocate_as::<T>()` or `self.allocate(Layout::new::<T>())`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::deallocate`].
+    #[inline]
+    pub unsafe fn deallocate_as<T>(&mut self, ptr: NonNull<T>) {
+        // Safety: `ptr` was allocated with alignment `align_of::<T>()`. This
+        //         is upheld by the caller.
+        self.deallocate(ptr.cast(), core::mem::align_of::<T>());
+    }
+
+    /// Deallocate a memory block previously allocated by
+    /// [`Self::allocate_with_offset`].
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via
+    ///    `self.allocate_with_offset`.
+    ///  - The memory block must have been allocated with the same `align`
+    ///    and `offset` as the ones passed to this method.
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::deallocate`].
+    pub unsafe fn deallocate_with_offset(&mut self, ptr: NonNull<u8>, align: usize, offset: usize) {
+        // `align` is a power of two because the allocation succeeded
+        let offset = offset & (align - 1);
+        // Safety: `ptr - offset` is the memory block allocated by `allocate`
+        //         with alignment `align`
+        self.deallocate(NonNull::new_unchecked(ptr.as_ptr().sub(offset)), align);
+    }
+
+    /// Deallocate a previously allocated memory block without merging it
+    /// with its neighbors or returning it to a free list. The memory block
+    /// is only pushed to a stack of deferred frees, which is processed by
+    /// [`Self::flush_deferred_frees`].
+    ///
+    /// This splits the cost of deallocation into a small constant part
+    /// suitable for a context with tight latency requirements (e.g., an
+    /// interrupt handler) and the rest, which can be done later outside of
+    /// such a context. The memory block can't be reused until it's flushed.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::deallocate`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::deallocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let layout = Layout::new::<[u64; 8]>();
+    /// let ptr = tlsf.allocate(layout).unwrap();
+    ///
+    /// // E.g., in an interrupt handler
+    /// unsafe { tlsf.deallocate_deferred(ptr, layout.align()) };
+    ///
+    /// // Later, outside of the interrupt handler
+    /// assert_eq!(tlsf.flush_deferred_frees(), 1);
+    /// ```
+    pub unsafe fn deallocate_deferred(&mut self, ptr: NonNull<u8>, align: usize) {
+        // Safety: `ptr` is a previously allocated memory block with the same
+        //         alignment as `align`. This is upheld by the caller.
+        let block = Self::used_block_hdr_for_allocation(ptr, align);
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block.cast());
+
+        // The memory block stays marked as used, so its neighbors won't
+        // attempt to merge with it until it's flushed
+        // Safety: The payload is no longer in use, and the memory block is
+        //         large enough to contain `DeferredFreeLink`
+        DeferredFreeLink::get_for_block(block).write(DeferredFreeLink {
+            next: self.deferred_frees,
+        });
+        self.deferred_frees = Some(block);
+    }
+
+    /// Complete the deallocation of the memory blocks deallocated by
+    /// [`Self::deallocate_deferred`]. Returns the number of the memory blocks
+    /// processed.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(number_of_deferred_frees)`).
+    pub fn flush_deferred_frees(&mut self) -> usize {
+        let mut count = 0;
+        while let Some(block) = self.deferred_frees {
+            // Safety: `block` is a used memory block owned by `self`, pushed
+            //         to the stack by `deallocate_deferred`
+            unsafe {
+                self.deferred_frees = (*DeferredFreeLink::get_for_block(block)).next;
+                self.deallocate_block(block.cast());
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Deallocate a previously allocated memory block without merging it with
+    /// its free neighbors, regardless of [`COALESCE`](#coalescing-on-free).
+    ///
+    /// This is intended for freeing a batch of memory blocks at once, e.g.,
+    /// at the end of a processing phase. Merging each memory block
+    /// individually is wasted work if most of its neighbors are about to be
+    /// freed, too. Call [`Self::coalesce_all`] afterwards to merge the
+    /// resulting runs of free blocks in a single pass.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::deallocate`].
+    ///
+    /// # Panics
+    ///
+    /// This method panics under the same condition as [`Self::deallocate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::{NonNull, slice_from_raw_parts_mut}};
+    ///
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let pool_ptr = NonNull::new(unsafe { POOL.as_mut_ptr() }).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let pool_len = unsafe { tlsf.insert_free_block_ptr(pool_ptr) }.unwrap().get();
+    /// let pool_ptr = NonNull::new(
+    ///     slice_from_raw_parts_mut(pool_ptr.as_ptr() as *mut u8, pool_len)
+    /// ).unwrap();
+    ///
+    /// let layout = Layout::new::<[u8; 256]>();
+    /// let ptrs = [(); 3].map(|()| tlsf.allocate(layout).unwrap());
+    /// for ptr in ptrs {
+    ///     unsafe { tlsf.mark_free_no_coalesce(ptr, layout.align()) };
+    /// }
+    /// assert!(tlsf.allocate(Layout::new::<[u8; 768]>()).is_none());
+    ///
+    /// // Merge the freed memory blocks in one pass
+    /// assert_ne!(unsafe { tlsf.coalesce_all(pool_ptr) }, 0);
+    /// assert!(tlsf.allocate(Layout::new::<[u8; 768]>()).is_some());
+    /// ```
+    pub unsafe fn mark_free_no_coalesce(&mut self, ptr: NonNull<u8>, align: usize) {
+        // Safety: `ptr` is a previously allocated memory block with the same
+        //         alignment as `align`. This is upheld by the caller.
+        let block = Self::used_block_hdr_for_allocation(ptr, align).cast::<BlockHdr>();
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block);
+        self.deallocate_block_with_coalescing(block, false);
+    }
+
     /// Deallocate a previously allocated memory block with an unknown alignment.
     ///
     /// Unlike `deallocate`, this function does not require knowing the
@@ -1023,25 +3165,177 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         // Safety: `ptr` is a previously allocated memory block. This is upheld
         //         by the caller.
         let block = Self::used_block_hdr_for_allocation_unknown_align(ptr).cast::<BlockHdr>();
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block);
         self.deallocate_block(block);
     }
 
+    /// Check that `block` looks like a valid used memory block containing the
+    /// allocation `ptr`. Panics with a descriptive message otherwise.
+    ///
+    /// This can't check whether `block` is inside a memory pool owned by
+    /// `self`, but it does check that the adjacent blocks agree with `block`,
+    /// which catches most double frees.
+    ///
+    /// # Safety
+    ///
+    /// `block` and the memory blocks it refers to must be readable.
+    #[cfg(feature = "debug-checks")]
+    unsafe fn check_used_block(ptr: NonNull<u8>, block: NonNull<BlockHdr>) {
+        if let Some(reason) = Self::find_used_block_problem(ptr, block) {
+            panic!(
+                "attempted to deallocate an invalid pointer {:p}: {}",
+                ptr, reason
+            );
+        }
+    }
+
+    /// Check that `block` looks like a valid used memory block containing the
+    /// allocation `ptr`. Returns the description of the first problem found.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::check_used_block`].
+    #[cfg(feature = "debug-checks")]
+    unsafe fn find_used_block_problem(
+        ptr: NonNull<u8>,
+        block: NonNull<BlockHdr>,
+    ) -> Option<&'static str> {
+        let block_addr = block.as_ptr() as usize;
+        let ptr_addr = ptr.as_ptr() as usize;
+        let size_and_flags = block.as_ref().size;
+        let size = size_and_flags & SIZE_SIZE_MASK;
+        let prev_phys_block_is_consistent = |prev: NonNull<BlockHdr>| {
+            let prev_addr = prev.as_ptr() as usize;
+            prev_addr % GRANULARITY == 0
+                && prev_addr < block_addr
+                && prev.as_ref().try_next_phys_block() == Some(block)
+        };
+
+        Some(if block_addr % GRANULARITY != 0 {
+            "the block header is misaligned"
+        } else if (size_and_flags & SIZE_USED) == 0 {
+            "the block is not in use (double free?)"
+        } else if (size_and_flags & !SIZE_SIZE_MASK) != SIZE_USED || size == 0 {
+            "the block header is corrupted"
+        } else if ptr_addr < block_addr + GRANULARITY / 2
+            || block_addr
+                .checked_add(size)
+                .map_or(true, |end| ptr_addr > end)
+        {
+            "the pointer is outside its block"
+        } else if !block
+            .as_ref()
+            .prev_phys_block
+            .map_or(true, prev_phys_block_is_consistent)
+        {
+            "the previous block does not end at the block (double free?)"
+        } else if block
+            .as_ref()
+            .try_next_phys_block()
+            .map_or(true, |next| next.as_ref().prev_phys_block != Some(block))
+        {
+            "the next block does not refer back to the block (double free?)"
+        } else {
+            return None;
+        })
+    }
+
+    /// Check that `ptr` plausibly denotes a memory block allocated via `self`
+    /// with alignment `align`, i.e., that [`Self::deallocate`] would accept
+    /// it.
+    ///
+    /// This performs the same checks as `deallocate` does when the
+    /// `debug-checks` feature is enabled, but returns `false` instead of
+    /// panicking. `ptr` must be aligned to `align`, and the memory block
+    /// header found from `ptr` must be marked as in use and have a size
+    /// consistent with the adjacent memory blocks.
+    ///
+    /// This is meant for debugging, e.g., to sanity-check a pointer received
+    /// through FFI before deallocating it. A `true` result doesn't prove that
+    /// `ptr` is a live allocation.
+    ///
+    /// # Safety
+    ///
+    /// `Tlsf` doesn't keep track of memory pools, so this method can't check
+    /// whether `ptr` is inside one. The caller must ensure that `ptr` points
+    /// inside a memory pool of `self` (e.g., by checking it against the pool
+    /// ranges the caller keeps track of). Pointers read from the block
+    /// headers are bounds-checked relative to `ptr` before being dereferenced
+    /// where possible, but a sufficiently corrupted header may still cause
+    /// this method to read memory outside the memory pool.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::NonNull};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 1024];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// let ptr = tlsf.allocate(Layout::new::<[u64; 4]>()).unwrap();
+    /// assert!(unsafe { tlsf.validate_pointer(ptr, 8) });
+    ///
+    /// // A pointer to the middle of the allocation
+    /// let ptr2 = NonNull::new(ptr.as_ptr().wrapping_add(8)).unwrap();
+    /// assert!(!unsafe { tlsf.validate_pointer(ptr2, 8) });
+    ///
+    /// unsafe { tlsf.deallocate(ptr, 8) };
+    /// // Already deallocated
+    /// assert!(!unsafe { tlsf.validate_pointer(ptr, 8) });
+    /// ```
+    #[cfg(feature = "debug-checks")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "debug-checks")))]
+    pub unsafe fn validate_pointer(&self, ptr: NonNull<u8>, align: usize) -> bool {
+        let ptr_addr = ptr.as_ptr() as usize;
+        if !align.is_power_of_two() || ptr_addr % align != 0 {
+            return false;
+        }
+
+        let block = Self::used_block_hdr_for_allocation(ptr, align).cast::<BlockHdr>();
+
+        // `allocate` places the header at most `align + GRANULARITY` bytes
+        // before the payload. Don't follow a pointer beyond that.
+        let block_addr = block.as_ptr() as usize;
+        if block_addr >= ptr_addr || ptr_addr - block_addr > align.max(GRANULARITY) + GRANULARITY {
+            return false;
+        }
+
+        Self::find_used_block_problem(ptr, block).is_none()
+    }
+
     /// Deallocate a previously allocated memory block. Takes a pointer to
     /// `BlockHdr` instead of a payload pointer.
     #[inline]
-    unsafe fn deallocate_block(&mut self, mut block: NonNull<BlockHdr>) {
+    unsafe fn deallocate_block(&mut self, block: NonNull<BlockHdr>) {
+        self.deallocate_block_with_coalescing(block, COALESCE);
+    }
+
+    /// [`Self::deallocate_block`] that merges the memory block with its free
+    /// neighbors only if `coalesce` is `true`.
+    #[inline]
+    unsafe fn deallocate_block_with_coalescing(
+        &mut self,
+        mut block: NonNull<BlockHdr>,
+        coalesce: bool,
+    ) {
         let mut size = block.as_ref().size & !SIZE_USED;
         debug_assert!((block.as_ref().size & SIZE_USED) != 0);
 
         // This variable tracks whose `prev_phys_block` we should update.
         let mut new_next_phys_block;
 
+        #[cfg(feature = "stats")]
+        let (mut merged_forward, mut merged_backward) = (false, false);
+
         // Merge the created hole with the next block if the next block is a
         // free block
         // Safety: `block.common` should be fully up-to-date and valid
         let next_phys_block = block.as_ref().next_phys_block();
         let next_phys_block_size_and_flags = next_phys_block.as_ref().size;
-        if (next_phys_block_size_and_flags & SIZE_USED) == 0 {
+        if coalesce && (next_phys_block_size_and_flags & SIZE_USED) == 0 {
             let next_phys_block_size = next_phys_block_size_and_flags;
             debug_assert_eq!(
                 next_phys_block_size_and_flags & SIZE_SIZE_MASK,
@@ -1059,12 +3353,17 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
 
             // Unlink `next_phys_block`.
             self.unlink_free_block(next_phys_block.cast(), next_phys_block_size);
+
+            #[cfg(feature = "stats")]
+            {
+                merged_forward = true;
+            }
         } else {
             new_next_phys_block = next_phys_block;
         }
 
         // Merge with the previous block if it's a free block.
-        if let Some(prev_phys_block) = block.as_ref().prev_phys_block {
+        if let Some(prev_phys_block) = block.as_ref().prev_phys_block.filter(|_| coalesce) {
             let prev_phys_block_size_and_flags = prev_phys_block.as_ref().size;
 
             if (prev_phys_block_size_and_flags & SIZE_USED) == 0 {
@@ -1084,9 +3383,28 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
                 // this, `block` will implicitly inherit `prev_phys_block.
                 // as_ref().prev_phys_block`.
                 block = prev_phys_block;
+
+                #[cfg(feature = "stats")]
+                {
+                    merged_backward = true;
+                }
             }
         }
 
+        #[cfg(feature = "stats")]
+        {
+            let stats = &mut self.coalesce_stats;
+            let counter = match (merged_forward, merged_backward) {
+                (true, false) => &mut stats.forward,
+                (false, true) => &mut stats.backward,
+                (true, true) => &mut stats.both,
+                (false, false) => &mut stats.none,
+            };
+            *counter = counter.wrapping_add(1);
+
+            self.num_allocations = self.num_allocations.wrapping_sub(1);
+        }
+
         // Write the new free block's size and flags.
         debug_assert!((size & SIZE_USED) == 0);
         block.as_mut().size = size;
@@ -1123,6 +3441,38 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         block_end - payload_start
     }
 
+    /// Get the free memory in the free block immediately following the
+    /// allocation, excluding the free block's header. Returns `None` if the
+    /// next block is in use.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `Self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `align`.
+    ///
+    pub(crate) unsafe fn free_block_after_allocation(
+        ptr: NonNull<u8>,
+        align: usize,
+    ) -> Option<NonNull<[u8]>> {
+        // Safety: `ptr` is a previously allocated memory block with the same
+        //         alignment as `align`. This is upheld by the caller.
+        let block = Self::used_block_hdr_for_allocation(ptr, align);
+
+        // Safety: A used block is never a sentinel
+        let next_block = block.as_ref().common.next_phys_block();
+        let next_size = next_block.as_ref().size;
+        if (next_size & SIZE_USED) != 0 {
+            return None;
+        }
+
+        // Safety: A free block is at least as large as `FreeBlockHdr`
+        Some(nonnull_slice_from_raw_parts(
+            NonNull::new_unchecked(next_block.cast::<FreeBlockHdr>().as_ptr().add(1)).cast(),
+            next_size - mem::size_of::<FreeBlockHdr>(),
+        ))
+    }
+
     /// Get the payload size of the allocation with an unknown alignment. The
     /// returned size might be larger than the size specified at the allocation
     /// time.
@@ -1161,13 +3511,16 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         Self::size_of_allocation_unknown_align(ptr)
     }
 
-    // TODO: `reallocate_no_move` (constant-time reallocation)
-
     /// Shrink or grow a previously allocated memory block.
     ///
     /// Returns the new starting address of the memory block on success;
     /// `None` otherwise.
     ///
+    /// If `new_layout` rounds up to the current memory block's size, this
+    /// method returns `ptr` without modifying anything, even if
+    /// `new_layout.size()` differs from the size the memory block was
+    /// allocated with.
+    ///
     /// # Time Complexity
     ///
     /// Unlike other methods, this method will complete in linear time
@@ -1179,14 +3532,27 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
     ///  - The memory block must have been allocated with the same alignment
     ///    ([`Layout::align`]) as `new_layout`.
     ///
+    /// # Panics
+    ///
+    /// This method never panics unless the `debug-checks` feature is enabled,
+    /// in which case it panics if it detects that `ptr` does not denote a
+    /// memory block in use, or [`Self::set_max_alloc_size_panics`] is
+    /// enabled, in which case it panics if `new_layout.size()` exceeds
+    /// [`Self::max_alloc_size`].
     pub unsafe fn reallocate(
         &mut self,
         ptr: NonNull<u8>,
         new_layout: Layout,
     ) -> Option<NonNull<u8>> {
+        if self.exceeds_max_alloc_size(new_layout.size()) {
+            return None;
+        }
+
         // Safety: `ptr` is a previously allocated memory block with the same
         //         alignment as `align`. This is upheld by the caller.
         let block = Self::used_block_hdr_for_allocation(ptr, new_layout.align());
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block.cast());
 
         // Do this early so that the compiler can de-duplicate common
         // subexpressions such as `block.as_ref().common.size - SIZE_USED`
@@ -1194,31 +3560,120 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
 
         // First try to shrink or grow the block in-place (i.e., without
         // allocating a whole new memory block).
-        if let Some(x) = self.reallocate_inplace(ptr, block, new_layout) {
+        if let Some(x) = self.reallocate_inplace(ptr, block, new_layout, true) {
             return Some(x);
         }
 
         // Allocate a whole new memory block
-        let new_ptr = self.allocate(new_layout)?;
+        let new_ptr = self.allocate_inner(new_layout)?;
+
+        // Move the existing data into the new location
+        debug_assert!(new_layout.size() >= old_size);
+        core::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_size);
+
+        // Deallocate the old memory block.
+        self.deallocate(ptr, new_layout.align());
+
+        Some(new_ptr)
+    }
+
+    /// Attempt to grow a previously allocated memory block without moving
+    /// it.
+    ///
+    /// Returns the memory block with its new usable size on success; `None`
+    /// otherwise, in which case the memory block is left unchanged. If the
+    /// memory block is already large enough to contain `new_layout`, this
+    /// method succeeds without modifying it.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `new_layout`.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics unless the `debug-checks` feature is enabled,
+    /// in which case it panics if it detects that `ptr` does not denote a
+    /// memory block in use.
+    pub unsafe fn grow_in_place(
+        &mut self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Option<NonNull<[u8]>> {
+        // Safety: `ptr` is a previously allocated memory block with the same
+        //         alignment as `align`. This is upheld by the caller.
+        let block = Self::used_block_hdr_for_allocation(ptr, new_layout.align());
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block.cast());
+
+        if new_layout.size() > Self::size_of_allocation(ptr, new_layout.align()) {
+            self.reallocate_inplace(ptr, block, new_layout, false)?;
+        }
+
+        let new_len = Self::size_of_allocation(ptr, new_layout.align());
+        Some(nonnull_slice_from_raw_parts(ptr, new_len))
+    }
+
+    /// Shrink a previously allocated memory block without moving it.
+    ///
+    /// Returns the memory block with its new usable size on success; `None`
+    /// if `new_layout` doesn't fit in the memory block, in which case the
+    /// memory block is left unchanged. The freed space is returned to the
+    /// free list if it's large enough to form a free block.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in constant time.
+    ///
+    /// # Safety
+    ///
+    ///  - `ptr` must denote a memory block previously allocated via `self`.
+    ///  - The memory block must have been allocated with the same alignment
+    ///    ([`Layout::align`]) as `new_layout`.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics unless the `debug-checks` feature is enabled,
+    /// in which case it panics if it detects that `ptr` does not denote a
+    /// memory block in use.
+    pub unsafe fn shrink_in_place(
+        &mut self,
+        ptr: NonNull<u8>,
+        new_layout: Layout,
+    ) -> Option<NonNull<[u8]>> {
+        // Safety: `ptr` is a previously allocated memory block with the same
+        //         alignment as `align`. This is upheld by the caller.
+        let block = Self::used_block_hdr_for_allocation(ptr, new_layout.align());
+        #[cfg(feature = "debug-checks")]
+        Self::check_used_block(ptr, block.cast());
 
-        // Move the existing data into the new location
-        debug_assert!(new_layout.size() >= old_size);
-        core::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_size);
+        if new_layout.size() > Self::size_of_allocation(ptr, new_layout.align()) {
+            return None;
+        }
 
-        // Deallocate the old memory block.
-        self.deallocate(ptr, new_layout.align());
+        // Shrinking never fails
+        let new_ptr = self.reallocate_inplace(ptr, block, new_layout, false);
+        debug_assert_eq!(new_ptr, Some(ptr));
 
-        Some(new_ptr)
+        let new_len = Self::size_of_allocation(ptr, new_layout.align());
+        Some(nonnull_slice_from_raw_parts(ptr, new_len))
     }
 
     /// A subroutine of [`Self::reallocate`] that tries to reallocate a memory
-    /// block in-place.
+    /// block in-place. The memory block's starting address is allowed to
+    /// change only if `allow_move` is `true`.
     #[inline]
     unsafe fn reallocate_inplace(
         &mut self,
         ptr: NonNull<u8>,
         mut block: NonNull<UsedBlockHdr>,
         new_layout: Layout,
+        allow_move: bool,
     ) -> Option<NonNull<u8>> {
         // The extra bytes consumed by the header and any padding
         let overhead = ptr.as_ptr() as usize - block.as_ptr() as usize;
@@ -1358,6 +3813,10 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         // In-place moving reallocation
         // ------------------------------------------------------------------
 
+        if !allow_move {
+            return None;
+        }
+
         // The non-moving reallocation was failure. Now try the moving approach.
         // I.e., grow into the previous free block as well.
         // Get the previous block. If there isn't such a block, the moving
@@ -1383,7 +3842,8 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         let unaligned_ptr =
             prev_phys_block.as_ptr() as *mut u8 as usize + mem::size_of::<UsedBlockHdr>();
         let new_ptr = NonNull::new_unchecked(
-            ((unaligned_ptr + new_layout.align() - 1) & !(new_layout.align() - 1)) as *mut u8,
+            (unaligned_ptr.wrapping_add(new_layout.align() - 1) & !(new_layout.align() - 1))
+                as *mut u8,
         );
 
         // Calculate the new block size
@@ -1531,30 +3991,615 @@ impl<'pool, FLBitmap: BinInteger, SLBitmap: BinInteger, const FLLEN: usize, cons
         // even call this method. This means this method don't have to repeat
         // this cut-off step from `insert_free_block_ptr`.
         let unaligned_start = pool.as_ptr() as *mut u8 as usize;
-        let mut start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
-        let mut len = len.saturating_sub(start.wrapping_sub(unaligned_start));
+        let start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+        let len = len.saturating_sub(start.wrapping_sub(unaligned_start));
+
+        let mut next_block = Some(start).filter(|_| len != 0);
 
         core::iter::from_fn(move || {
-            if len == 0 {
-                None
-            } else {
-                let block_hdr = &*(start as *const BlockHdr);
-                let block_size = block_hdr.size & SIZE_SIZE_MASK;
+            let block_hdr = &*(next_block? as *const BlockHdr);
 
-                // Advance the cursor
-                len -= block_size;
-                start = start.wrapping_add(block_size);
+            // Advance the cursor. Stop at the end of the pool, or if the pool
+            // turns out to be corrupted.
+            next_block = block_hdr
+                .checked_size()
+                .and_then(|size| (block_hdr as *const _ as usize).checked_add(size))
+                .filter(|&next| next.wrapping_sub(start) < len);
 
-                Some(BlockInfo { block_hdr })
-            }
+            Some(BlockInfo { block_hdr })
         })
         .filter(|block_info| {
             // Exclude sentinel blocks
-            (block_info.block_hdr.size & SIZE_SENTINEL) == 0
+            !block_info.is_sentinel()
+        })
+    }
+
+    /// Examine the memory block whose header is located at `ptr`.
+    ///
+    /// Unlike [`Self::iter_blocks`], this method can also examine sentinel
+    /// blocks, which occupy the last [`POOL_OVERHEAD`] bytes of each memory
+    /// pool (see [`BlockInfo::is_sentinel`]). This is useful for verifying
+    /// the structure of memory pools built by [`Self::insert_free_block_ptr`]
+    /// and [`Self::append_free_block_ptr`].
+    ///
+    /// Returns `None` if `ptr` is not aligned to [`GRANULARITY`] or the
+    /// header at `ptr` is implausible, i.e., its size is zero or it has
+    /// unknown flags set. This catches some, but not all, invalid pointers.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to the start of a memory block in a memory pool that
+    /// belongs to `self`, e.g., the start of a memory block yielded by
+    /// [`Self::iter_blocks`] or the start of a memory pool's last
+    /// [`POOL_OVERHEAD`] bytes. The returned `BlockInfo` must not outlive any
+    /// modification of the memory pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::{Tlsf, POOL_OVERHEAD};
+    /// use std::{mem::MaybeUninit, ptr::NonNull};
+    ///
+    /// // Align the memory pool so that its first block starts at `pool_ptr`
+    /// #[repr(align(64))]
+    /// struct Pool([MaybeUninit<u8>; 1024]);
+    /// let mut pool = Pool([MaybeUninit::uninit(); 1024]);
+    /// let pool_ptr = NonNull::from(&mut pool.0).cast::<u8>();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let pool_len = unsafe {
+    ///     tlsf.insert_free_block_ptr(NonNull::new(&mut pool.0[..] as *mut _ as *mut [u8]).unwrap())
+    /// }
+    /// .unwrap()
+    /// .get();
+    ///
+    /// // The memory pool starts with a free block...
+    /// let first = unsafe { tlsf.block_info_at(pool_ptr) }.unwrap();
+    /// assert!(!first.is_occupied());
+    /// assert!(!first.is_sentinel());
+    ///
+    /// // ...and ends with a sentinel block
+    /// let sentinel_ptr = NonNull::new(pool_ptr.as_ptr().wrapping_add(pool_len - POOL_OVERHEAD));
+    /// let sentinel = unsafe { tlsf.block_info_at(sentinel_ptr.unwrap()) }.unwrap();
+    /// assert!(sentinel.is_occupied());
+    /// assert!(sentinel.is_sentinel());
+    /// assert_eq!(first.size() + sentinel.size(), pool_len);
+    /// ```
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub unsafe fn block_info_at(&self, ptr: NonNull<u8>) -> Option<BlockInfo<'_>> {
+        if (ptr.as_ptr() as usize) % GRANULARITY != 0 {
+            return None;
+        }
+        let block_hdr = &*ptr.as_ptr().cast::<BlockHdr>();
+        block_hdr.checked_size()?;
+        Some(BlockInfo { block_hdr })
+    }
+
+    /// Enumerate the allocations in the specified memory pool, reporting how
+    /// many bytes of each memory block are spent on the header and alignment
+    /// padding.
+    ///
+    /// The starting address of an allocation isn't recorded in its memory
+    /// block, so it's reconstructed from `align`, the alignment with which
+    /// the allocations were made. Allocations with alignments smaller than
+    /// [`GRANULARITY`] are all placed right after the header, so any such
+    /// alignment can be passed for them. Allocations with larger
+    /// alignments should be kept in separate memory pools by alignment to be
+    /// enumerated by this method.
+    ///
+    /// # Safety
+    ///
+    ///  - See [`Self::iter_blocks`].
+    ///  - Every allocation in `pool` must have been made with alignment
+    ///    `align`, or both of them must be smaller than [`GRANULARITY`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::{NonNull, slice_from_raw_parts_mut}};
+    ///
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let pool_ptr = NonNull::new(unsafe { POOL.as_mut_ptr() }).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let pool_len = unsafe { tlsf.insert_free_block_ptr(pool_ptr) }.unwrap().get();
+    /// let pool_ptr = NonNull::new(
+    ///     slice_from_raw_parts_mut(pool_ptr.as_ptr() as *mut u8, pool_len)
+    /// ).unwrap();
+    ///
+    /// let layout = Layout::from_size_align(100, 64).unwrap();
+    /// tlsf.allocate(layout).unwrap();
+    /// tlsf.allocate(layout).unwrap();
+    ///
+    /// for info in unsafe { tlsf.iter_allocations(pool_ptr, layout.align()) } {
+    ///     assert!(info.usable >= layout.size());
+    ///     assert_eq!(info.overhead + info.usable, info.block.size());
+    /// }
+    /// ```
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub unsafe fn iter_allocations(
+        &self,
+        pool: NonNull<[u8]>,
+        align: usize,
+    ) -> impl Iterator<Item = AllocationInfo<'_>> + Send + '_ {
+        self.iter_blocks(pool)
+            .filter(|block_info| block_info.is_occupied())
+            .map(move |block| AllocationInfo::new(block, align))
+    }
+
+    /// Enumerate the allocations in all memory pools created in the specified
+    /// memory block by [`Self::insert_free_block_ptr`] and the subsequent
+    /// calls to [`Self::append_free_block_ptr`].
+    ///
+    /// Unlike [`Self::iter_allocations`], this method doesn't need the exact
+    /// length of the memory pools. The memory pools created in a memory block
+    /// are placed back to back, and less than `GRANULARITY * 2` bytes are
+    /// left after the last one, so the walk continues past a sentinel block
+    /// only if there's room for another memory pool.
+    ///
+    /// # Safety
+    ///
+    /// `block` must be a memory block that was passed to
+    /// `insert_free_block_ptr` (or `insert_free_block_ptr_aligned`) and
+    /// extended only by `append_free_block_ptr`. The memory pools must be
+    /// managed by `self`.
+    #[cfg(feature = "unstable")]
+    pub(crate) unsafe fn iter_allocations_in_block(
+        &self,
+        block: NonNull<[u8]>,
+        align: usize,
+    ) -> impl Iterator<Item = AllocationInfo<'_>> + '_ {
+        let unaligned_start = block.as_ptr() as *mut u8 as usize;
+        let start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+        let end = unaligned_start.wrapping_add(nonnull_slice_len(block));
+
+        // Is there room for a memory pool at `addr`?
+        let has_pool_at = move |addr: usize| {
+            end.checked_sub(addr)
+                .map_or(false, |x| x >= GRANULARITY * 2)
+        };
+
+        let mut next_block = Some(start).filter(|&start| has_pool_at(start));
+
+        core::iter::from_fn(move || loop {
+            let block_hdr = &*(next_block? as *const BlockHdr);
+
+            // Advance the cursor. Stop at the end of the memory block, or if
+            // the pool turns out to be corrupted.
+            next_block = block_hdr
+                .checked_size()
+                .and_then(|size| (block_hdr as *const _ as usize).checked_add(size))
+                .filter(|&next| next < end);
+
+            if (block_hdr.size & SIZE_SENTINEL) != 0 {
+                // Another memory pool may follow
+                next_block = next_block.filter(|&next| has_pool_at(next));
+            } else if (block_hdr.size & SIZE_USED) != 0 {
+                return Some(AllocationInfo::new(BlockInfo { block_hdr }, align));
+            }
+        })
+    }
+
+    /// Deallocate all allocations in the specified memory pool, yielding
+    /// each of them beforehand.
+    ///
+    /// Each item is the payload area of an occupied memory block, i.e., the
+    /// block minus its header. The allocation is contained in it and starts
+    /// at its beginning if the allocation's alignment is not larger than
+    /// `GRANULARITY / 2`. An allocation remains valid until the iterator
+    /// is advanced past it or dropped, so the caller can run destructors on
+    /// it in the meantime.
+    ///
+    /// If the iterator is dropped early, the allocations that haven't been
+    /// yielded yet remain allocated. The [deferred frees] are flushed first,
+    /// so they aren't yielded.
+    ///
+    /// [deferred frees]: Self::deallocate_deferred
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::iter_blocks`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::{NonNull, slice_from_raw_parts_mut}};
+    ///
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let pool_ptr = NonNull::new(unsafe { POOL.as_mut_ptr() }).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// let pool_len = unsafe { tlsf.insert_free_block_ptr(pool_ptr) }.unwrap().get();
+    /// let pool_ptr = NonNull::new(
+    ///     slice_from_raw_parts_mut(pool_ptr.as_ptr() as *mut u8, pool_len)
+    /// ).unwrap();
+    ///
+    /// for i in 0..3u64 {
+    ///     let ptr = tlsf.allocate(Layout::new::<u64>()).unwrap();
+    ///     unsafe { ptr.cast::<u64>().as_ptr().write(i) };
+    /// }
+    ///
+    /// // "Destruct" the allocations
+    /// let values: Vec<u64> = unsafe { tlsf.drain_allocations(pool_ptr) }
+    ///     .map(|payload| unsafe { (payload.as_ptr() as *mut u64).read() })
+    ///     .collect();
+    /// assert_eq!(values, [0, 1, 2]);
+    ///
+    /// // The memory pool is now entirely free
+    /// assert!(tlsf.allocate(Layout::new::<[u8; 512]>()).is_some());
+    /// ```
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub unsafe fn drain_allocations(
+        &mut self,
+        pool: NonNull<[u8]>,
+    ) -> DrainAllocations<'_, 'pool, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+    {
+        self.flush_deferred_frees();
+
+        let len = nonnull_slice_len(pool);
+
+        // Round up the starting address in the same way as `iter_blocks` does
+        let unaligned_start = pool.as_ptr() as *mut u8 as usize;
+        let start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+        let len = len.saturating_sub(start.wrapping_sub(unaligned_start));
+
+        DrainAllocations {
+            tlsf: self,
+            start,
+            len,
+            cursor: Some(start)
+                .filter(|_| len != 0)
+                .map(|start| NonNull::new_unchecked(start as *mut BlockHdr)),
+            pending: None,
+        }
+    }
+
+    /// Merge all runs of physically adjacent free blocks in the specified
+    /// memory pool. Returns the number of free blocks that were merged into
+    /// their preceding free blocks.
+    ///
+    /// Adjacent free blocks only occur when
+    /// [coalescing on free](#coalescing-on-free) is disabled or
+    /// memory blocks are deallocated by [`Self::mark_free_no_coalesce`]. This
+    /// method restores the state that would have resulted if they had been
+    /// deallocated normally with coalescing enabled.
+    ///
+    /// If [`Self::insert_free_block_ptr`] divided the memory pool into
+    /// multiple chunks to meet the maximum block size requirement, all of
+    /// them are processed.
+    ///
+    /// # Time Complexity
+    ///
+    /// This method will complete in linear time
+    /// (`O(number_of_blocks_in_pool)`).
+    ///
+    /// # Safety
+    ///
+    /// `pool` must precisely represent a memory pool that belongs to `self`.
+    /// See [`Self::iter_blocks`] for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout, ptr::{NonNull, slice_from_raw_parts_mut}};
+    ///
+    /// static mut POOL: MaybeUninit<[u8; 1024]> = MaybeUninit::uninit();
+    /// let pool_ptr = NonNull::new(unsafe { POOL.as_mut_ptr() }).unwrap();
+    ///
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16, false, false, false> = Tlsf::new();
+    ///
+    /// let pool_len = unsafe { tlsf.insert_free_block_ptr(pool_ptr) }.unwrap().get();
+    /// let pool_ptr = NonNull::new(
+    ///     slice_from_raw_parts_mut(pool_ptr.as_ptr() as *mut u8, pool_len)
+    /// ).unwrap();
+    ///
+    /// let layout = Layout::new::<[u8; 256]>();
+    /// let ptrs = [(); 3].map(|()| tlsf.allocate(layout).unwrap());
+    /// for ptr in ptrs {
+    ///     unsafe { tlsf.deallocate(ptr, layout.align()) };
+    /// }
+    ///
+    /// // The freed blocks are too small individually
+    /// assert!(tlsf.allocate(Layout::new::<[u8; 768]>()).is_none());
+    ///
+    /// assert_ne!(unsafe { tlsf.coalesce_all(pool_ptr) }, 0);
+    /// assert!(tlsf.allocate(Layout::new::<[u8; 768]>()).is_some());
+    /// ```
+    pub unsafe fn coalesce_all(&mut self, pool: NonNull<[u8]>) -> usize {
+        let len = nonnull_slice_len(pool);
+
+        // Round up the starting address in the same way as `iter_blocks` does
+        let unaligned_start = pool.as_ptr() as *mut u8 as usize;
+        let start = unaligned_start.wrapping_add(GRANULARITY - 1) & !(GRANULARITY - 1);
+        let len = len.saturating_sub(start.wrapping_sub(unaligned_start));
+        if len == 0 {
+            return 0;
+        }
+
+        let mut num_merged = 0;
+        let mut block = NonNull::new_unchecked(start as *mut BlockHdr);
+
+        loop {
+            let size_and_flags = block.as_ref().size;
+            if (size_and_flags & SIZE_SENTINEL) != 0 {
+                // `insert_free_block_ptr` places chunks back to back, so
+                // the next one (if any) starts right after the sentinel block
+                let next = (block.as_ptr() as usize).wrapping_add(size_and_flags & SIZE_SIZE_MASK);
+                if next.wrapping_sub(start) >= len {
+                    break;
+                }
+                block = NonNull::new_unchecked(next as *mut BlockHdr);
+                continue;
+            }
+
+            if (size_and_flags & SIZE_USED) != 0 {
+                block = block.as_ref().next_phys_block();
+                continue;
+            }
+
+            // `block` is a free block. Absorb the free blocks following it.
+            let mut size = size_and_flags;
+            debug_assert_eq!(size, size & SIZE_SIZE_MASK);
+            let mut next_phys_block = block.as_ref().next_phys_block();
+
+            if (next_phys_block.as_ref().size & SIZE_USED) == 0 {
+                self.unlink_free_block(block.cast(), size);
+
+                while (next_phys_block.as_ref().size & SIZE_USED) == 0 {
+                    let next_phys_block_size = next_phys_block.as_ref().size;
+                    debug_assert_eq!(next_phys_block_size, next_phys_block_size & SIZE_SIZE_MASK);
+                    self.unlink_free_block(next_phys_block.cast(), next_phys_block_size);
+                    size += next_phys_block_size;
+                    num_merged += 1;
+
+                    // Safety: `next_phys_block` is a free block and therefore
+                    //         is not a sentinel block
+                    next_phys_block = next_phys_block.as_ref().next_phys_block();
+                }
+
+                block.as_mut().size = size;
+                self.link_free_block(block.cast(), size);
+                next_phys_block.as_mut().prev_phys_block = Some(block);
+            }
+
+            // `next_phys_block` is a used block or a sentinel block
+            block = next_phys_block;
+        }
+
+        num_merged
+    }
+
+    /// Enumerate the free blocks in descending order of their size classes.
+    ///
+    /// Each item is a pair of a block's size (including the header, as in
+    /// [`BlockInfo::size`]) and its starting address. Unlike
+    /// [`Self::iter_blocks`], this method only visits free blocks and is
+    /// driven by the bitmaps, so empty size classes are skipped without
+    /// examining them, and no memory pool needs to be specified.
+    ///
+    /// Free blocks in the same size class (i.e., the same second-level free
+    /// list) are not sorted and are yielded in an unspecified order.
+    /// Therefore, the sizes are only guaranteed to be non-increasing when
+    /// rounded down to their size classes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rlsf::Tlsf;
+    /// use std::{mem::MaybeUninit, alloc::Layout};
+    ///
+    /// let mut pool = [MaybeUninit::uninit(); 4096];
+    /// let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+    /// tlsf.insert_free_block(&mut pool);
+    ///
+    /// // Split the pool into two free blocks
+    /// let ptr1 = tlsf.allocate(Layout::new::<[u8; 1024]>()).unwrap();
+    /// let ptr2 = tlsf.allocate(Layout::new::<[u8; 1024]>()).unwrap();
+    /// unsafe { tlsf.deallocate(ptr1, 1) };
+    ///
+    /// let sizes: Vec<usize> = tlsf.free_blocks_descending().map(|(size, _)| size).collect();
+    /// assert_eq!(sizes.len(), 2);
+    /// assert!(sizes[0] > sizes[1]);
+    /// # unsafe { tlsf.deallocate(ptr2, 1) };
+    /// ```
+    #[cfg(feature = "unstable")]
+    #[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+    pub fn free_blocks_descending(&self) -> impl Iterator<Item = (usize, NonNull<u8>)> + Send + '_ {
+        // The first-level lists that haven't been visited yet
+        let mut fl_bitmap = self.fl_bitmap;
+        // The second-level lists in `fl` that haven't been visited yet
+        let mut sl_bitmap = SLBitmap::ZERO;
+        let mut fl = 0;
+        let mut next_free = FreeBlockCursor(None);
+
+        // Find the most significant set bit
+        fn pop_msb<T: BinInteger>(bitmap: &mut T) -> Option<usize> {
+            if *bitmap == T::ZERO {
+                None
+            } else {
+                let i = T::BITS - 1 - bitmap.leading_zeros();
+                bitmap.clear_bit(i);
+                Some(i as usize)
+            }
+        }
+
+        /// A pointer to the next free block to visit.
+        struct FreeBlockCursor(Option<NonNull<FreeBlockHdr>>);
+
+        // Safety: The free blocks are logically owned by `self`, which is
+        //         borrowed for the lifetime of the iterator.
+        unsafe impl Send for FreeBlockCursor {}
+
+        core::iter::from_fn(move || loop {
+            // Capture `next_free` as a whole, not the non-`Send` field
+            let next_free = &mut next_free;
+
+            if let Some(block) = next_free.0 {
+                // Safety: All free blocks in the free lists are owned by
+                //         `self`, which outlives the iterator
+                let block = unsafe { block.as_ref() };
+                next_free.0 = block.next_free;
+                return Some((block.common.size, NonNull::from(block).cast()));
+            }
+
+            if let Some(sl) = pop_msb(&mut sl_bitmap) {
+                next_free.0 = self.first_free[fl][sl];
+                debug_assert!(next_free.0.is_some());
+                continue;
+            }
+
+            fl = pop_msb(&mut fl_bitmap)?;
+            sl_bitmap = self.sl_bitmap[fl];
         })
     }
 }
 
+/// Report an allocation request rejected by [`Tlsf::max_alloc_size`].
+#[cold]
+#[inline(never)]
+fn max_alloc_size_exceeded(size: usize, max_alloc_size: usize) -> ! {
+    panic!(
+        "allocation of {} bytes exceeds the maximum allocation size ({} bytes)",
+        size, max_alloc_size
+    );
+}
+
+/// The iterator returned by [`Tlsf::drain_allocations`].
+#[cfg(feature = "unstable")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+#[derive(Debug)]
+pub struct DrainAllocations<
+    'a,
+    'pool,
+    FLBitmap: BinInteger,
+    SLBitmap: BinInteger,
+    const FLLEN: usize,
+    const SLLEN: usize,
+    const FIFO: bool,
+    const BEST_FIT: bool,
+    const COALESCE: bool,
+> {
+    tlsf: &'a mut Tlsf<'pool, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>,
+    /// The starting address of the memory pool
+    start: usize,
+    /// The length of the memory pool
+    len: usize,
+    /// The next block to examine. `None` if we have reached the end of the
+    /// memory pool.
+    cursor: Option<NonNull<BlockHdr>>,
+    /// The occupied block yielded last, which is yet to be deallocated
+    pending: Option<NonNull<BlockHdr>>,
+}
+
+#[cfg(feature = "unstable")]
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > DrainAllocations<'_, '_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    /// Deallocate the block yielded last.
+    fn release_pending(&mut self) {
+        if let Some(block) = self.pending.take() {
+            // Safety: `pending` is an occupied block in the memory pool, and
+            //         `cursor` is the block following it
+            unsafe {
+                let next_phys_block = self.cursor.unwrap_or_else(|| unreachable_unchecked());
+
+                // If the next block is free, it will be merged into `block`.
+                // Skip it because it's not a block header anymore. The block
+                // after a free block is an occupied block or a sentinel block,
+                // which remains valid.
+                if COALESCE && (next_phys_block.as_ref().size & SIZE_USED) == 0 {
+                    self.cursor = Some(next_phys_block.as_ref().next_phys_block());
+                }
+
+                self.tlsf.deallocate_block(block);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Iterator
+    for DrainAllocations<'_, '_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    type Item = NonNull<[u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.release_pending();
+
+        while let Some(block) = self.cursor {
+            // Safety: `cursor` points to a block header in the memory pool
+            //         (upheld by `drain_allocations`'s caller)
+            let size_and_flags = unsafe { block.as_ref().size };
+            if (size_and_flags & SIZE_SENTINEL) != 0 {
+                // Move on to the next chunk of the memory pool, if any
+                let next = (block.as_ptr() as usize).wrapping_add(size_and_flags & SIZE_SIZE_MASK);
+                self.cursor = Some(next)
+                    .filter(|&next| next.wrapping_sub(self.start) < self.len)
+                    // Safety: `next` is within the memory pool, so it's non-null
+                    .map(|next| unsafe { NonNull::new_unchecked(next as *mut BlockHdr) });
+                continue;
+            }
+
+            // Safety: `block` is not a sentinel block
+            self.cursor = Some(unsafe { block.as_ref().next_phys_block() });
+
+            if (size_and_flags & SIZE_USED) != 0 {
+                self.pending = Some(block);
+                let size = size_and_flags & SIZE_SIZE_MASK;
+                // Safety: The payload area starts after the header and is
+                //         within the memory pool, so it's non-null
+                let payload = unsafe {
+                    NonNull::new_unchecked(block.cast::<u8>().as_ptr().add(GRANULARITY / 2))
+                };
+                return Some(nonnull_slice_from_raw_parts(
+                    payload,
+                    size - GRANULARITY / 2,
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<
+        FLBitmap: BinInteger,
+        SLBitmap: BinInteger,
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    > Drop
+    for DrainAllocations<'_, '_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>
+{
+    fn drop(&mut self) {
+        self.release_pending();
+    }
+}
+
 /// Allows the caller of [`Tlsf::iter_blocks`] to examine the properties of a
 /// memory block in a [`Tlsf`] memory pool.
 #[derive(Clone, Copy)]
@@ -1565,12 +4610,13 @@ pub struct BlockInfo<'a> {
 }
 
 #[cfg(feature = "unstable")]
-impl fmt::Debug for BlockInfo<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl core::fmt::Debug for BlockInfo<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.debug_struct("BlockInfo")
             .field("ptr", &self.as_ptr_range())
             .field("size", &self.size())
             .field("is_occupied", &self.is_occupied())
+            .field("is_sentinel", &self.is_sentinel())
             .finish()
     }
 }
@@ -1611,6 +4657,53 @@ impl BlockInfo<'_> {
     pub fn is_occupied(&self) -> bool {
         (self.block_hdr.size & SIZE_USED) != 0
     }
+
+    /// Get a flag indicating whether this block is a sentinel block, a
+    /// permanently occupied block marking the end of a memory pool.
+    ///
+    /// Sentinel blocks are never yielded by [`Tlsf::iter_blocks`], but they
+    /// can be examined by [`Tlsf::block_info_at`]. A sentinel block is always
+    /// [occupied](Self::is_occupied).
+    #[inline]
+    pub fn is_sentinel(&self) -> bool {
+        (self.block_hdr.size & SIZE_SENTINEL) != 0
+    }
+}
+
+/// An allocation yielded by [`Tlsf::iter_allocations`].
+#[derive(Debug, Clone, Copy)]
+#[cfg(feature = "unstable")]
+#[cfg_attr(feature = "doc_cfg", doc(cfg(feature = "unstable")))]
+pub struct AllocationInfo<'a> {
+    /// The memory block containing the allocation.
+    pub block: BlockInfo<'a>,
+    /// The number of bytes from the start of the memory block to the start
+    /// of the allocation, i.e., the header plus alignment padding.
+    pub overhead: usize,
+    /// The number of bytes from the start of the allocation to the end of
+    /// the memory block. This is at least the size requested at allocation
+    /// time; the rest is the slack caused by rounding up to
+    /// [`GRANULARITY`].
+    pub usable: usize,
+}
+
+#[cfg(feature = "unstable")]
+impl<'a> AllocationInfo<'a> {
+    /// Construct `AllocationInfo` for an occupied memory block, assuming the
+    /// allocation was made with alignment `align`.
+    fn new(block: BlockInfo<'a>, align: usize) -> Self {
+        // Replicate the placement by `allocate_from_free_block`. This also
+        // matches `allocate_from_free_block_end` and the moving reallocation.
+        let start = block.block_hdr as *const _ as usize;
+        let unaligned_ptr = start + mem::size_of::<UsedBlockHdr>();
+        let ptr = unaligned_ptr.wrapping_add(align - 1) & !(align - 1);
+        let overhead = ptr - start;
+        Self {
+            block,
+            overhead,
+            usable: block.size() - overhead,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2,21 +2,62 @@ use quickcheck_macros::quickcheck;
 use std::{mem::MaybeUninit, prelude::v1::*};
 
 use super::*;
-use crate::{tests::ShadowAllocator, utils::nonnull_slice_from_raw_parts};
+use crate::{
+    tests::ShadowAllocator,
+    utils::{nonnull_slice_from_raw_parts, nonnull_slice_len},
+};
+
+mod soak;
 
 #[repr(align(64))]
 struct Align<T>(T);
 
+/// A heap-allocated memory pool aligned to 64 bytes, for pools too large to
+/// place on the stack
+struct AlignedPool {
+    storage: Vec<MaybeUninit<Align<[u8; 64]>>>,
+    len: usize,
+}
+
+impl AlignedPool {
+    fn new(len: usize) -> Self {
+        Self {
+            storage: Vec::with_capacity((len + 63) / 64),
+            len,
+        }
+    }
+
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.storage.as_mut_ptr() as *mut u8
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [MaybeUninit<u8>] {
+        // Safety: `storage` has the capacity for at least `len` bytes
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.storage.as_mut_ptr() as *mut MaybeUninit<u8>,
+                self.len,
+            )
+        }
+    }
+}
+
 /// Dump the output of `iter_blocks` in a separate module so that it can be
 /// filtered separately with `env_logger`
 mod blocks_checker {
     use super::*;
-    use std::ptr::NonNull;
 
-    pub unsafe fn trace_blocks<const FLLEN: usize, const SLLEN: usize>(
+    #[cfg_attr(not(feature = "unstable"), allow(unused_variables))]
+    pub unsafe fn trace_blocks<
+        const FLLEN: usize,
+        const SLLEN: usize,
+        const FIFO: bool,
+        const BEST_FIT: bool,
+        const COALESCE: bool,
+    >(
         pool_ptr: *mut u8,
         pool_len: Option<usize>,
-        tlsf: &Tlsf<'_, impl BinInteger, impl BinInteger, FLLEN, SLLEN>,
+        tlsf: &Tlsf<'_, impl BinInteger, impl BinInteger, FLLEN, SLLEN, FIFO, BEST_FIT, COALESCE>,
     ) {
         #[cfg(feature = "unstable")]
         {
@@ -52,81 +93,1513 @@ macro_rules! gen_test {
 
                 log::trace!("tlsf = {:?}", tlsf);
 
-                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
-                if let Some(ptr) = ptr {
-                    unsafe { tlsf.deallocate(ptr, 1) };
+                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+                if let Some(ptr) = ptr {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+            }
+
+            #[test]
+            fn adaa() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = [MaybeUninit::uninit(); 65536];
+                tlsf.insert_free_block(&mut pool);
+
+                log::trace!("tlsf = {:?}", tlsf);
+
+                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+                if let Some(ptr) = ptr {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+
+                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+
+                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+            }
+
+            #[test]
+            fn aadd() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 96]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                log::trace!("tlsf = {:?}", tlsf);
+
+                let ptr1 = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr1 = {:?}", ptr1);
+
+                let ptr2 = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr2 = {:?}", ptr2);
+
+                if let (Some(ptr1), Some(ptr2)) = (ptr1, ptr2) {
+                    unsafe { tlsf.deallocate(ptr1, 1) };
+                    unsafe { tlsf.deallocate(ptr2, 1) };
+                }
+            }
+
+            #[test]
+            fn zero_sized() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let mut ptrs = Vec::new();
+                for i in 0..16 {
+                    let align = 1 << (i % 7);
+                    let layout = Layout::from_size_align(0, align).unwrap();
+                    if let Some(ptr) = tlsf.allocate(layout) {
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+                        ptrs.push((ptr, align));
+                    }
+                }
+                log::trace!("ptrs = {:?}", ptrs);
+
+                // Zero-sized allocations are real allocations with distinct
+                // addresses
+                let mut addrs: Vec<_> = ptrs.iter().map(|&(ptr, _)| ptr.as_ptr() as usize).collect();
+                addrs.sort_unstable();
+                addrs.dedup();
+                assert_eq!(addrs.len(), ptrs.len());
+
+                // ...which can be grown and deallocated
+                for (ptr, align) in ptrs {
+                    let ptr = unsafe {
+                        tlsf.reallocate(ptr, Layout::from_size_align(16, align).unwrap())
+                    }
+                    .unwrap_or(ptr);
+                    unsafe { tlsf.deallocate(ptr, align) };
+                }
+            }
+
+            #[test]
+            fn allocate_as() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                #[repr(align(128))]
+                struct OverAligned([u8; 40]);
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let mut ptrs = Vec::new();
+                while let Some(ptr) = tlsf.allocate_as::<OverAligned>() {
+                    assert_eq!(ptr.as_ptr() as usize % 128, 0);
+                    unsafe { ptr.as_ptr().write(OverAligned([ptrs.len() as u8; 40])) };
+                    ptrs.push(ptr);
+                }
+                log::trace!("ptrs = {:?}", ptrs);
+
+                for (i, &ptr) in ptrs.iter().enumerate() {
+                    assert_eq!(unsafe { &(*ptr.as_ptr()).0 }, &[i as u8; 40]);
+                    unsafe { tlsf.deallocate_as(ptr) };
+                }
+
+                // If `deallocate_as` used a wrong alignment, the memory
+                // blocks wouldn't be returned intact
+                let mut count = 0;
+                while let Some(ptr) = tlsf.allocate(Layout::new::<OverAligned>()) {
+                    assert!(ptrs.contains(&ptr.cast()));
+                    count += 1;
+                }
+                assert_eq!(count, ptrs.len());
+            }
+
+            #[test]
+            fn ara() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 96]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                log::trace!("tlsf = {:?}", tlsf);
+
+                let ptr = tlsf.allocate(Layout::from_size_align(17, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+
+                if let Some(ptr) = ptr {
+                    unsafe { tlsf.reallocate(ptr, Layout::from_size_align(0, 1).unwrap()) };
+                    log::trace!("ptr = {:?}", ptr);
+                }
+
+                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+            }
+
+            #[test]
+            fn insert_free_block_ptr_aligned() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 512]);
+                let pool_len = unsafe {
+                    tlsf.insert_free_block_ptr_aligned(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool.0.as_mut_ptr().cast::<u8>()).unwrap(),
+                        GRANULARITY * 4,
+                    ))
+                };
+                assert_eq!(pool_len.map(NonZeroUsize::get), Some(GRANULARITY * 4));
+
+                // The pool should be usable
+                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap());
+                log::trace!("ptr = {:?}", ptr);
+                let ptr = ptr.unwrap();
+                unsafe { tlsf.deallocate(ptr, 1) };
+
+                // Too small
+                let pool_len = unsafe {
+                    tlsf.insert_free_block_ptr_aligned(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool.0.as_mut_ptr().cast::<u8>().add(GRANULARITY * 4)).unwrap(),
+                        GRANULARITY,
+                    ))
+                };
+                assert_eq!(pool_len, None);
+            }
+
+            #[test]
+            fn pool_overhead() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 512]);
+                // Make the free block's size a power of two so that it can be
+                // allocated as a whole regardless of `SLLEN`
+                let pool_len = TheTlsf::MAX_POOL_SIZE
+                    .map_or(GRANULARITY * 3, |mps| mps.min(GRANULARITY * 3));
+                unsafe {
+                    tlsf.insert_free_block_ptr_aligned(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool.0.as_mut_ptr().cast::<u8>()).unwrap(),
+                        pool_len,
+                    ))
+                };
+
+                // Everything but `POOL_OVERHEAD` and the allocated block's
+                // header is usable
+                let max_size = pool_len - POOL_OVERHEAD - mem::size_of::<UsedBlockHdr>();
+                assert!(tlsf
+                    .allocate(Layout::from_size_align(max_size + 1, 1).unwrap())
+                    .is_none());
+                let ptr = tlsf
+                    .allocate(Layout::from_size_align(max_size, 1).unwrap())
+                    .unwrap();
+                unsafe { tlsf.deallocate(ptr, 1) };
+            }
+
+            #[test]
+            fn allocate_with_headroom() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let min_layout = Layout::from_size_align(16, 1).unwrap();
+                let preferred_layout = Layout::from_size_align(256, 8).unwrap();
+
+                // The preferred size is available (if the pool is large enough)
+                if let Some(block) = tlsf.allocate_with_headroom(min_layout, preferred_layout) {
+                    log::trace!("block = {:?}", block);
+                    let len = nonnull_slice_len(block);
+                    assert!(len >= 16);
+                    assert_eq!(block.as_ptr() as *mut u8 as usize % 8, 0);
+                    if TheTlsf::MAX_POOL_SIZE.map_or(true, |mps| mps >= 1024) {
+                        assert!(len >= 256);
+                    }
+                    unsafe { tlsf.deallocate(block.cast(), 8) };
+                }
+
+                // Only the minimum size is available
+                let huge_layout = Layout::from_size_align(1 << 20, 8).unwrap();
+                if let Some(block) = tlsf.allocate_with_headroom(min_layout, huge_layout) {
+                    log::trace!("block = {:?}", block);
+                    let len = nonnull_slice_len(block);
+                    assert!((16..1 << 20).contains(&len));
+                    unsafe { tlsf.deallocate(block.cast(), 8) };
+                }
+            }
+
+            #[test]
+            fn grow_shrink_in_place() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let check_block = |ptr: NonNull<u8>, block: NonNull<[u8]>| {
+                    log::trace!("block = {:?}", block);
+                    assert_eq!(block.as_ptr() as *mut u8, ptr.as_ptr());
+                    let len = nonnull_slice_len(block);
+                    assert_eq!(len, unsafe { TheTlsf::size_of_allocation(ptr, 8) });
+                    #[cfg(feature = "unstable")]
+                    assert_eq!(len, unsafe { TheTlsf::allocation_usable_size(ptr) });
+                    len
+                };
+
+                let layout = Layout::from_size_align(64, 8).unwrap();
+                let ptr = if let Some(ptr) = tlsf.allocate(layout) {
+                    ptr
+                } else {
+                    return;
+                };
+
+                unsafe {
+                    let block = tlsf
+                        .shrink_in_place(ptr, Layout::from_size_align(8, 8).unwrap())
+                        .unwrap();
+                    let len = check_block(ptr, block);
+                    assert!(len >= 8);
+
+                    // Can't shrink to a larger size
+                    assert!(tlsf
+                        .shrink_in_place(ptr, Layout::from_size_align(len + 1, 8).unwrap())
+                        .is_none());
+
+                    // The block is already large enough
+                    let block = tlsf
+                        .grow_in_place(ptr, Layout::from_size_align(len, 8).unwrap())
+                        .unwrap();
+                    assert_eq!(check_block(ptr, block), len);
+
+                    // Grow into the space freed by `shrink_in_place`
+                    let block = tlsf.grow_in_place(ptr, layout).unwrap();
+                    assert!(check_block(ptr, block) >= 64);
+
+                    // Block the growth by allocating the following space
+                    let len = check_block(ptr, block);
+                    let mut others = Vec::new();
+                    while let Some(other) = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()) {
+                        others.push(other);
+                    }
+                    assert!(tlsf
+                        .grow_in_place(ptr, Layout::from_size_align(len + 1, 8).unwrap())
+                        .is_none());
+                    assert_eq!(TheTlsf::size_of_allocation(ptr, 8), len);
+
+                    for other in others {
+                        tlsf.deallocate(other, 1);
+                    }
+                    tlsf.deallocate(ptr, 8);
+                }
+            }
+
+            #[test]
+            fn allocate_from_end() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                // Keep the pool in one chunk so that addresses in it are
+                // comparable
+                let pool_len = TheTlsf::MAX_POOL_SIZE.map_or(4096, |mps| mps.min(4096));
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                let pool = &mut pool.0[..pool_len];
+                let pool_range = pool.as_ptr_range();
+                tlsf.insert_free_block(pool);
+
+                for align in [1, 8, GRANULARITY / 2, GRANULARITY, GRANULARITY * 2, 256] {
+                    let layout = Layout::from_size_align(100, align).unwrap();
+                    log::trace!("layout = {:?}", layout);
+
+                    let (low, high) = match (tlsf.allocate(layout), tlsf.allocate_from_end(layout)) {
+                        (Some(low), Some(high)) => (low, high),
+                        (low, high) => {
+                            for ptr in low.into_iter().chain(high) {
+                                unsafe { tlsf.deallocate(ptr, align) };
+                            }
+                            continue;
+                        }
+                    };
+                    log::trace!("low = {:?}, high = {:?}", low, high);
+
+                    assert_eq!(high.as_ptr() as usize % align, 0);
+                    assert!(high.as_ptr() as usize >= low.as_ptr() as usize + layout.size());
+                    assert!((high.as_ptr() as usize + layout.size()) <= pool_range.end as usize);
+                    assert!(pool_range.contains(&(high.as_ptr() as *const _)));
+
+                    unsafe {
+                        high.as_ptr().write_bytes(0x5a, layout.size());
+                        assert_eq!(TheTlsf::size_of_allocation(high, align), TheTlsf::size_of_allocation_unknown_align(high));
+                        assert!(TheTlsf::size_of_allocation(high, align) >= layout.size());
+                        tlsf.deallocate(high, align);
+                        tlsf.deallocate(low, align);
+                    }
+                }
+
+                // All free blocks should have coalesced back
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .collect();
+                let mut tlsf2: TheTlsf = Tlsf::new();
+                let mut pool2 = Align([MaybeUninit::uninit(); 4096]);
+                tlsf2.insert_free_block(&mut pool2.0[..pool_len]);
+                let num_expected = std::iter::from_fn(|| tlsf2.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .count();
+                assert_eq!(ptrs.len(), num_expected);
+            }
+
+            #[quickcheck]
+            fn allocate_with_offset(size: u16, align_log2: u8, offset: u16) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                let pool_range = pool.0.as_ptr_range();
+                tlsf.insert_free_block(&mut pool.0);
+
+                let size = size as usize % 1024;
+                let align = 1 << (align_log2 % 9);
+                let offset = offset as usize;
+                log::trace!("(size, align, offset) = {:?}", (size, align, offset));
+
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate_with_offset(size, align, offset))
+                    .take(4)
+                    .collect();
+                for &ptr in &ptrs {
+                    log::trace!("ptr = {:?}", ptr);
+                    assert_eq!(ptr.as_ptr().wrapping_sub(offset) as usize % align, 0);
+                    assert!(pool_range.contains(&(ptr.as_ptr() as *const _)));
+                    assert!((ptr.as_ptr() as usize + size) <= pool_range.end as usize);
+                    unsafe { ptr.as_ptr().write_bytes(0x5a, size) };
+                }
+
+                for ptr in ptrs {
+                    unsafe { tlsf.deallocate_with_offset(ptr, align, offset) };
+                }
+
+                // All free blocks should have coalesced back
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .collect();
+                let mut tlsf2: TheTlsf = Tlsf::new();
+                let mut pool2 = Align([MaybeUninit::uninit(); 4096]);
+                tlsf2.insert_free_block(&mut pool2.0);
+                let num_expected = std::iter::from_fn(|| tlsf2.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .count();
+                assert_eq!(ptrs.len(), num_expected);
+            }
+
+            #[quickcheck]
+            fn mark_free_no_coalesce(sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                // Coalescing on free stays enabled
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 4096]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 4096)) };
+                let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len.map_or(0, |x| x.get()));
+
+                let ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| tlsf.allocate(Layout::from_size_align(size as usize % 512, 1).unwrap()))
+                    .collect();
+                log::trace!("ptrs = {:?}", ptrs);
+
+                for &ptr in ptrs.iter() {
+                    unsafe { tlsf.mark_free_no_coalesce(ptr, 1) };
+                }
+
+                #[cfg(feature = "stats")]
+                {
+                    assert!(tlsf.is_empty());
+                    assert_eq!(tlsf.coalesce_stats().none, ptrs.len() as u64);
+                }
+
+                // None of the freed blocks were merged on free
+                let num_merged = unsafe { tlsf.coalesce_all(pool_ptr) };
+                log::trace!("num_merged = {:?}", num_merged);
+                assert!(num_merged <= ptrs.len());
+                // If the pool was divided into chunks, each allocation might
+                // have filled a whole chunk, leaving nothing to merge
+                if ptrs.len() >= 2 && TheTlsf::MAX_POOL_SIZE.map_or(true, |mps| mps >= 4096) {
+                    assert_ne!(num_merged, 0);
+                }
+                assert_eq!(unsafe { tlsf.coalesce_all(pool_ptr) }, 0);
+
+                // The pool should be in the same state as a fresh one
+                let num_allocated = std::iter::from_fn(|| tlsf.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .count();
+                let mut tlsf2: TheTlsf = Tlsf::new();
+                let mut pool2 = Align([MaybeUninit::<u8>::uninit(); 4096]);
+                let pool2_ptr = NonNull::new(pool2.0.as_mut_ptr() as *mut u8).unwrap();
+                unsafe { tlsf2.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool2_ptr, 4096)) };
+                let num_expected = std::iter::from_fn(|| tlsf2.allocate(Layout::from_size_align(1, 1).unwrap()))
+                    .count();
+                assert_eq!(num_allocated, num_expected);
+            }
+
+            #[test]
+            fn reallocate_moving_preserves_alignment_and_contents() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                for align in [GRANULARITY, 64, 128, 256] {
+                    // If `prev_free`, the allocation can move into the preceding
+                    // free block (an in-place moving reallocation). Otherwise,
+                    // it must move to a new memory block.
+                    for prev_free in [false, true] {
+                        log::trace!("(align, prev_free) = {:?}", (align, prev_free));
+
+                        let mut tlsf: TheTlsf = Tlsf::new();
+                        let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                        tlsf.insert_free_block(&mut pool.0);
+
+                        let small = Layout::from_size_align(200, 8).unwrap();
+                        let layout = Layout::from_size_align(100, align).unwrap();
+                        let new_layout = Layout::from_size_align(400, align).unwrap();
+
+                        // Surround the allocation so that it can't grow forward
+                        let (before, ptr, after) =
+                            match (tlsf.allocate(small), tlsf.allocate(layout), tlsf.allocate(small)) {
+                                (Some(before), Some(ptr), Some(after)) => (before, ptr, after),
+                                _ => continue,
+                            };
+
+                        let pattern = |i: usize| (i as u8).reverse_bits() ^ 0xa5;
+                        for i in 0..layout.size() {
+                            unsafe { *ptr.as_ptr().add(i) = pattern(i) };
+                        }
+
+                        if prev_free {
+                            unsafe { tlsf.deallocate(before, small.align()) };
+                        }
+
+                        if let Some(new_ptr) = unsafe { tlsf.reallocate(ptr, new_layout) } {
+                            log::trace!("{:?} → {:?}", ptr, new_ptr);
+                            assert_ne!(new_ptr, ptr);
+                            assert_eq!(new_ptr.as_ptr() as usize % align, 0);
+
+                            // All bytes survived, including the ones near the
+                            // start of the payload, which might have been
+                            // overlapped by the new block header
+                            for i in 0..layout.size() {
+                                assert_eq!(unsafe { *new_ptr.as_ptr().add(i) }, pattern(i), "byte {}", i);
+                            }
+
+                            unsafe { tlsf.deallocate(new_ptr, align) };
+                        } else {
+                            unsafe { tlsf.deallocate(ptr, align) };
+                        }
+
+                        if !prev_free {
+                            unsafe { tlsf.deallocate(before, small.align()) };
+                        }
+                        unsafe { tlsf.deallocate(after, small.align()) };
+                    }
+                }
+            }
+
+            #[test]
+            fn reallocate_within_block_size() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                // Both sizes round up to the same block size (e.g., 40 and
+                // 48 bytes on a 64-bit target)
+                let new_size = GRANULARITY * 2 - GRANULARITY / 2;
+                let old_size = new_size - 8;
+                let ptr = if let Some(ptr) = tlsf.allocate(Layout::from_size_align(old_size, 8).unwrap()) {
+                    ptr
+                } else {
+                    return;
+                };
+
+                unsafe {
+                    ptr.as_ptr().write_bytes(0x5a, old_size);
+                    let state = format!("{:?}", tlsf);
+
+                    // Neither the block nor the free lists should change
+                    let new_ptr = tlsf
+                        .reallocate(ptr, Layout::from_size_align(new_size, 8).unwrap())
+                        .unwrap();
+                    assert_eq!(new_ptr, ptr);
+                    assert_eq!(TheTlsf::size_of_allocation(ptr, 8), new_size);
+                    assert_eq!(format!("{:?}", tlsf), state);
+                    assert!(std::slice::from_raw_parts(ptr.as_ptr(), old_size)
+                        .iter()
+                        .all(|&x| x == 0x5a));
+
+                    // Same for shrinking
+                    let new_ptr = tlsf
+                        .reallocate(ptr, Layout::from_size_align(old_size, 8).unwrap())
+                        .unwrap();
+                    assert_eq!(new_ptr, ptr);
+                    assert_eq!(format!("{:?}", tlsf), state);
+
+                    tlsf.deallocate(ptr, 8);
+                }
+            }
+
+            #[test]
+            fn rebind() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                // Fill the pool and free a half of it
+                let layout = Layout::from_size_align(1, 1).unwrap();
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout)).collect();
+                log::trace!("ptrs.len() = {}", ptrs.len());
+                for ptr in ptrs.iter().step_by(2) {
+                    unsafe { tlsf.deallocate(*ptr, 1) };
+                }
+
+                let mut tlsf: Tlsf<'_, u64, u64, 64, 64> = tlsf.rebind();
+
+                // The existing allocations can be deallocated through the new
+                // `Tlsf`
+                for ptr in ptrs.iter().skip(1).step_by(2) {
+                    unsafe { tlsf.deallocate(*ptr, 1) };
+                }
+
+                // All free blocks should have been carried over
+                let new_ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout)).collect();
+                assert_eq!(new_ptrs.len(), ptrs.len());
+            }
+
+            #[test]
+            fn absorb() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf0: TheTlsf = Tlsf::new();
+                let mut tlsf1: TheTlsf = Tlsf::new();
+
+                let mut pool0 = Align([MaybeUninit::uninit(); 1024]);
+                let mut pool1 = Align([MaybeUninit::uninit(); 1024]);
+                tlsf0.insert_free_block(&mut pool0.0);
+                tlsf1.insert_free_block(&mut pool1.0);
+
+                // Fill both pools and free a half of each
+                let layout = Layout::from_size_align(1, 1).unwrap();
+                let ptrs0: Vec<_> = std::iter::from_fn(|| tlsf0.allocate(layout)).collect();
+                let ptrs1: Vec<_> = std::iter::from_fn(|| tlsf1.allocate(layout)).collect();
+                log::trace!("ptrs0.len() = {}, ptrs1.len() = {}", ptrs0.len(), ptrs1.len());
+                for ptr in ptrs0.iter().chain(ptrs1.iter()).step_by(2) {
+                    let tlsf = if ptrs0.contains(ptr) { &mut tlsf0 } else { &mut tlsf1 };
+                    unsafe { tlsf.deallocate(*ptr, 1) };
+                }
+
+                tlsf0.absorb(tlsf1);
+
+                // The existing allocations of both can be deallocated through
+                // `tlsf0`
+                for ptr in ptrs0.iter().chain(ptrs1.iter()).skip(1).step_by(2) {
+                    unsafe { tlsf0.deallocate(*ptr, 1) };
+                }
+
+                // All free blocks should have been carried over
+                let new_ptrs: Vec<_> = std::iter::from_fn(|| tlsf0.allocate(layout)).collect();
+                assert_eq!(new_ptrs.len(), ptrs0.len() + ptrs1.len());
+            }
+
+            #[test]
+            fn deallocate_deferred() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+                let mut pool = Align([MaybeUninit::uninit(); 1024]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                for align in [1, 64] {
+                    let layout = Layout::from_size_align(1, align).unwrap();
+                    let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout)).collect();
+                    log::trace!("ptrs.len() = {}", ptrs.len());
+                    if ptrs.is_empty() {
+                        // The pool's chunks might be too small for this
+                        // alignment
+                        assert_ne!(align, 1);
+                        continue;
+                    }
+
+                    for &ptr in ptrs.iter() {
+                        unsafe { tlsf.deallocate_deferred(ptr, align) };
+                    }
+
+                    // The memory blocks can't be reused until they are flushed
+                    assert!(tlsf.allocate(layout).is_none());
+
+                    assert_eq!(tlsf.flush_deferred_frees(), ptrs.len());
+                    assert_eq!(tlsf.flush_deferred_frees(), 0);
+
+                    let new_ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout)).collect();
+                    assert_eq!(new_ptrs.len(), ptrs.len());
+                    for ptr in new_ptrs {
+                        unsafe { tlsf.deallocate(ptr, align) };
+                    }
+                }
+            }
+
+            #[cfg(feature = "stats")]
+            #[test]
+            fn write_report() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let mut report = String::new();
+                tlsf.write_report(&mut report).unwrap();
+                log::trace!("report = {}", report);
+                assert!(report.contains("capacity: 4096 bytes\n"));
+                assert!(report.contains("fragmentation: "));
+
+                // Allocate some blocks and free every other one to cause
+                // fragmentation
+                let layout = Layout::from_size_align(1, 1).unwrap();
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout))
+                    .take(8)
+                    .collect();
+                for ptr in ptrs.iter().step_by(2) {
+                    unsafe { tlsf.deallocate(*ptr, 1) };
+                }
+
+                let mut report = String::new();
+                tlsf.write_report(&mut report).unwrap();
+                log::trace!("report = {}", report);
+                assert!(report.contains("capacity: 4096 bytes\n"));
+                if ptrs.len() == 8 {
+                    assert!(!report.contains("fragmentation: 0%"));
+                }
+            }
+
+            #[test]
+            fn max_alloc_size() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+                tlsf.set_max_alloc_size(Some(64));
+                assert_eq!(tlsf.max_alloc_size(), Some(64));
+
+                let small = Layout::from_size_align(64, 8).unwrap();
+                let large = Layout::from_size_align(65, 8).unwrap();
+
+                assert!(tlsf.allocate(large).is_none());
+                assert!(tlsf.allocate_probing::<4>(large).is_none());
+                assert!(tlsf.allocate_class_rounded(large).is_none());
+                assert!(tlsf.allocate_with_headroom(large, large).is_none());
+
+                let ptr = if let Some(ptr) = tlsf.allocate(small) {
+                    ptr
+                } else {
+                    return;
+                };
+
+                unsafe {
+                    // Growing past the limit fails, leaving the memory block
+                    // intact
+                    assert!(tlsf.reallocate(ptr, large).is_none());
+                    assert!(TheTlsf::size_of_allocation(ptr, 8) >= 64);
+                    tlsf.deallocate(ptr, 8);
+                }
+
+                // The headroom is not provided past the limit
+                if let Some(block) = tlsf.allocate_with_headroom(small, Layout::from_size_align(4000, 8).unwrap()) {
+                    assert!(nonnull_slice_len(block) < 4000);
+                    unsafe { tlsf.deallocate(block.cast(), 8) };
+                }
+
+                // The limit is carried over by `rebind`
+                let mut tlsf: Tlsf<'_, u64, u64, 64, 64> = tlsf.rebind();
+                assert_eq!(tlsf.max_alloc_size(), Some(64));
+
+                tlsf.set_max_alloc_size(None);
+                assert!(tlsf.allocate(large).is_some());
+            }
+
+            #[test]
+            #[should_panic = "exceeds the maximum allocation size"]
+            fn max_alloc_size_panics() {
+                let mut tlsf: TheTlsf = Tlsf::new();
+                tlsf.set_max_alloc_size(Some(64));
+                tlsf.set_max_alloc_size_panics(true);
+
+                assert!(tlsf.allocate(Layout::from_size_align(64, 8).unwrap()).is_none());
+                tlsf.allocate(Layout::from_size_align(65, 8).unwrap());
+            }
+
+            #[cfg(feature = "stats")]
+            #[test]
+            fn coalesce_stats() {
+                use crate::CoalesceStats;
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                // The allocations below must be physically adjacent and
+                // followed by free space, so keep the pool in one chunk
+                let pool_len = TheTlsf::MAX_POOL_SIZE.map_or(4096, |mps| mps.min(4096));
+                if pool_len < GRANULARITY * 8 {
+                    return;
+                }
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0[..pool_len]);
+
+                let layout = Layout::from_size_align(1, 1).unwrap();
+                let ptrs: Vec<_> = std::iter::from_fn(|| tlsf.allocate(layout))
+                    .take(5)
+                    .collect();
+                assert_eq!(ptrs.len(), 5);
+
+                unsafe {
+                    // No free neighbors
+                    tlsf.deallocate(ptrs[1], 1);
+                    assert_eq!(tlsf.coalesce_stats().none, 1);
+
+                    // Merged with `ptrs[1]`'s block
+                    tlsf.deallocate(ptrs[2], 1);
+                    assert_eq!(tlsf.coalesce_stats().backward, 1);
+
+                    // Merged with the trailing free space
+                    tlsf.deallocate(ptrs[4], 1);
+                    assert_eq!(tlsf.coalesce_stats().forward, 1);
+
+                    // Merged with both
+                    tlsf.deallocate(ptrs[3], 1);
+                    assert_eq!(tlsf.coalesce_stats().both, 1);
+                }
+
+                assert_eq!(
+                    tlsf.coalesce_stats(),
+                    CoalesceStats {
+                        forward: 1,
+                        backward: 1,
+                        both: 1,
+                        none: 1,
+                    }
+                );
+
+                // The counts are carried over by `rebind`
+                let tlsf: Tlsf<'_, u64, u64, 64, 64> = tlsf.rebind();
+                assert_eq!(tlsf.coalesce_stats().both, 1);
+            }
+
+            #[cfg(feature = "stats")]
+            #[quickcheck]
+            fn is_empty(sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 65536]);
+                tlsf.insert_free_block(&mut pool.0);
+                assert!(tlsf.is_empty());
+
+                let ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| {
+                        tlsf.allocate(Layout::from_size_align(size as usize % 1024, 8).unwrap())
+                    })
+                    .collect();
+                assert_eq!(tlsf.is_empty(), ptrs.is_empty());
+
+                // Deferred frees count as live until they are flushed
+                for (i, &ptr) in ptrs.iter().enumerate() {
+                    if i % 2 == 0 {
+                        unsafe { tlsf.deallocate(ptr, 8) };
+                    } else {
+                        unsafe { tlsf.deallocate_deferred(ptr, 8) };
+                    }
+                }
+                assert_eq!(tlsf.is_empty(), ptrs.len() < 2);
+
+                tlsf.flush_deferred_frees();
+                assert!(tlsf.is_empty());
+            }
+
+            #[quickcheck]
+            fn is_pool_empty(sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) };
+                let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len.map_or(0, |x| x.get()));
+                assert!(unsafe { tlsf.is_pool_empty(pool_ptr) });
+
+                let ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| {
+                        tlsf.allocate(Layout::from_size_align(size as usize % 1024, 8).unwrap())
+                    })
+                    .collect();
+                assert_eq!(unsafe { tlsf.is_pool_empty(pool_ptr) }, ptrs.is_empty());
+
+                // Deferred frees count as live until they are flushed
+                for (i, &ptr) in ptrs.iter().enumerate() {
+                    if i % 2 == 0 {
+                        unsafe { tlsf.deallocate(ptr, 8) };
+                    } else {
+                        unsafe { tlsf.deallocate_deferred(ptr, 8) };
+                    }
+                }
+                assert_eq!(unsafe { tlsf.is_pool_empty(pool_ptr) }, ptrs.len() < 2);
+
+                tlsf.flush_deferred_frees();
+                assert!(unsafe { tlsf.is_pool_empty(pool_ptr) });
+            }
+
+            #[quickcheck]
+            fn stats(sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) }
+                    .map_or(0, NonZeroUsize::get);
+                let initial = tlsf.stats();
+                log::trace!("initial = {:?}", initial);
+                assert_eq!(initial.total_bytes, pool_len);
+                assert_eq!(initial.used_bytes + initial.free_bytes, pool_len);
+
+                let ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| {
+                        let layout = Layout::from_size_align(size as usize % 1024, 8).unwrap();
+                        Some((tlsf.allocate(layout)?, layout))
+                    })
+                    .collect();
+
+                // Deallocate every other allocation to create holes
+                for &(ptr, layout) in ptrs.iter().step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+
+                let stats = tlsf.stats();
+                log::trace!("stats = {:?}", stats);
+                assert_eq!(stats.total_bytes, pool_len);
+                assert_eq!(stats.used_bytes + stats.free_bytes, pool_len);
+                assert!(stats.largest_free_block <= stats.free_bytes);
+                assert_eq!(stats.num_free_blocks == 0, stats.free_bytes == 0);
+
+                // Each remaining allocation consumes at least its payload and
+                // header
+                let min_used: usize = ptrs
+                    .iter()
+                    .skip(1)
+                    .step_by(2)
+                    .map(|&(_, layout)| layout.size() + mem::size_of::<UsedBlockHdr>())
+                    .sum();
+                assert!(stats.used_bytes - initial.used_bytes >= min_used);
+
+                #[cfg(feature = "unstable")]
+                {
+                    let free_sizes: Vec<_> = tlsf.free_blocks_descending().map(|(size, _)| size).collect();
+                    assert_eq!(stats.num_free_blocks, free_sizes.len());
+                    assert_eq!(stats.free_bytes, free_sizes.iter().sum::<usize>());
+                    assert_eq!(stats.largest_free_block, free_sizes.iter().copied().max().unwrap_or(0));
+                }
+
+                for &(ptr, layout) in ptrs.iter().skip(1).step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+                assert_eq!(tlsf.stats(), initial);
+            }
+
+            #[cfg(feature = "fault-injection")]
+            #[test]
+            fn fail_policy() {
+                use crate::FailPolicy;
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let small = Layout::from_size_align(8, 1).unwrap();
+                let large = Layout::from_size_align(16, 1).unwrap();
+
+                // Skip the configurations that can't satisfy these requests
+                // in the first place
+                for layout in [small, large] {
+                    match tlsf.allocate(layout) {
+                        Some(ptr) => unsafe { tlsf.deallocate(ptr, 1) },
+                        None => return,
+                    }
+                }
+
+                for (policy, expected) in [
+                    (FailPolicy::Never, [true, true, true, true]),
+                    (FailPolicy::Nth(1), [true, false, true, true]),
+                    (FailPolicy::AfterNth(2), [true, true, false, false]),
+                ] {
+                    log::trace!("policy = {:?}", policy);
+                    tlsf.set_fail_policy(policy);
+                    for expected in expected {
+                        let ptr = tlsf.allocate(small);
+                        assert_eq!(ptr.is_some(), expected);
+                        if let Some(ptr) = ptr {
+                            unsafe { tlsf.deallocate(ptr, 1) };
+                        }
+                    }
+                }
+
+                tlsf.set_fail_policy(FailPolicy::LargerThan(8));
+                assert!(tlsf.allocate_with_headroom(large, large).is_none());
+                assert!(tlsf.allocate(large).is_none());
+                let ptr = tlsf.allocate(small).unwrap();
+                unsafe { tlsf.deallocate(ptr, 1) };
+            }
+
+            #[test]
+            fn huge_layouts() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 512]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                // None of these should panic, even in a debug build. Some of
+                // them are rejected by `Layout` itself depending on the Rust
+                // version, in which case they are skipped.
+                let max_align = 1 << (usize::BITS - 1);
+                for layout in [
+                    Layout::from_size_align(isize::MAX as usize, 1).ok(),
+                    Layout::from_size_align(isize::MAX as usize - 15, 16).ok(),
+                    Layout::from_size_align(0, max_align).ok(),
+                    Layout::from_size_align(1, max_align).ok(),
+                    Layout::from_size_align(1, max_align / 2).ok(),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    log::trace!("layout = {:?}", layout);
+                    assert_eq!(tlsf.allocate(layout), None);
+                }
+
+                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()).unwrap();
+                let huge = Layout::from_size_align(isize::MAX as usize, 1).unwrap();
+                assert_eq!(unsafe { tlsf.reallocate(ptr, huge) }, None);
+                unsafe { tlsf.deallocate(ptr, 1) };
+            }
+
+            #[test]
+            fn near_max_sizes() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 1024]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let aligns = (0..).map(|i| 1 << i).take_while(|&align| align <= GRANULARITY * 4);
+                for align in aligns {
+                    let ptr = tlsf.allocate(Layout::from_size_align(1, align).unwrap());
+                    log::trace!("align = {}, ptr = {:?}", align, ptr);
+
+                    // Sweep the sizes that overflow when the header, padding,
+                    // or rounding is added
+                    let max_size = (isize::MAX as usize + 1) - align;
+                    for offset in 0..GRANULARITY * 8 {
+                        let size = max_size - offset * align;
+                        let layout = Layout::from_size_align(size, align).unwrap();
+
+                        assert_eq!(tlsf.allocate(layout), None, "{:?}", layout);
+                        assert_eq!(
+                            tlsf.allocate_with_headroom(layout, layout),
+                            None,
+                            "{:?}",
+                            layout
+                        );
+
+                        if let Some(ptr) = ptr {
+                            assert_eq!(unsafe { tlsf.reallocate(ptr, layout) }, None, "{:?}", layout);
+                            assert_eq!(unsafe { tlsf.grow_in_place(ptr, layout) }, None, "{:?}", layout);
+                        }
+                    }
+
+                    if let Some(ptr) = ptr {
+                        unsafe { tlsf.deallocate(ptr, align) };
+                    }
+                }
+            }
+
+            #[test]
+            fn alignment_boundary() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 4096]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let large_layout = Layout::from_size_align(2048, 1).unwrap();
+                let large_ptr = tlsf.allocate(large_layout);
+                if let Some(ptr) = large_ptr {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+
+                // `GRANULARITY / 2` (e.g., 16 on a 64-bit target) is the
+                // largest alignment for which no `UsedBlockPad` is stored
+                for &align in &[GRANULARITY / 4, GRANULARITY / 2, GRANULARITY, GRANULARITY * 2] {
+                    // Interleave allocations with the minimum alignment so
+                    // that memory blocks start at various addresses
+                    let mut ptrs = Vec::new();
+                    for size in [0, 1, GRANULARITY / 2, GRANULARITY + 1] {
+                        // Small `FLLEN`s can't serve every allocation
+                        if let Some(ptr) = tlsf.allocate(Layout::from_size_align(size, 1).unwrap()) {
+                            ptrs.push((ptr, 1));
+                        }
+                        let ptr = if let Some(ptr) = tlsf.allocate(Layout::from_size_align(size, align).unwrap()) {
+                            ptr
+                        } else {
+                            continue;
+                        };
+                        log::trace!("align = {}, size = {}, ptr = {:?}", align, size, ptr);
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+
+                        unsafe {
+                            let hdr = TheTlsf::used_block_hdr_for_allocation(ptr, align);
+                            assert_eq!(hdr, TheTlsf::used_block_hdr_for_allocation_unknown_align(ptr));
+                            if align < GRANULARITY {
+                                // The header immediately precedes the payload
+                                assert_eq!(hdr.as_ptr() as usize, ptr.as_ptr() as usize - GRANULARITY / 2);
+                            }
+                            assert!(TheTlsf::size_of_allocation(ptr, align) >= size);
+                            ptr.as_ptr().write_bytes(0xcc, size);
+                        }
+
+                        ptrs.push((ptr, align));
+                    }
+
+                    for (ptr, align) in ptrs {
+                        unsafe { tlsf.deallocate(ptr, align) };
+                    }
+                }
+
+                // All memory blocks should have been returned
+                assert_eq!(tlsf.allocate(large_layout).is_some(), large_ptr.is_some());
+            }
+
+            #[test]
+            fn allocate_probing() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                // `allocate` searches for a free block of at least
+                // `search_size` bytes. A free block at `64 - GRANULARITY`
+                // (mod 64) needs less padding and can contain the allocation
+                // with `block_size` bytes.
+                let layout = Layout::from_size_align(4096, 64).unwrap();
+                let search_size = layout.size() + 64;
+                let block_size = layout.size() + GRANULARITY;
+
+                if TheTlsf::map_floor(search_size).is_none()
+                    || TheTlsf::map_floor(search_size) != TheTlsf::map_floor(block_size)
+                {
+                    // The free block isn't in the list examined by
+                    // `allocate_probing`
+                    return;
+                }
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 8192]);
+                let pool_start = 64 - GRANULARITY;
+                let pool_len = unsafe {
+                    tlsf.insert_free_block_ptr(
+                        NonNull::new(
+                            &mut pool.0[pool_start..pool_start + block_size + GRANULARITY]
+                                as *mut [MaybeUninit<u8>] as *mut [u8],
+                        )
+                        .unwrap(),
+                    )
+                };
+                assert_eq!(pool_len.unwrap().get(), block_size + GRANULARITY);
+
+                assert_eq!(tlsf.allocate(layout), None);
+                assert_eq!(tlsf.allocate_probing::<0>(layout), None);
+
+                let ptr = tlsf.allocate_probing::<1>(layout).unwrap();
+                log::trace!("ptr = {:?}", ptr);
+                assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+                assert!(unsafe { TheTlsf::size_of_allocation(ptr, layout.align()) } >= layout.size());
+                unsafe { tlsf.deallocate(ptr, layout.align()) };
+            }
+
+            #[test]
+            fn allocate_class_rounded() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::uninit(); 65536]);
+                tlsf.insert_free_block(&mut pool.0);
+
+                let mut ptrs = Vec::new();
+                for size in (1..2000).step_by(37) {
+                    let layout = Layout::from_size_align(size, 8).unwrap();
+                    let ptr = if let Some(ptr) = tlsf.allocate_class_rounded(layout) {
+                        ptr
+                    } else {
+                        continue;
+                    };
+                    log::trace!("{:?} -> {:?}", layout, ptr);
+
+                    // `align < GRANULARITY`, so there's no padding
+                    let block_size = unsafe { TheTlsf::size_of_allocation(ptr, 8) }
+                        + mem::size_of::<UsedBlockHdr>();
+                    assert!(block_size >= size + mem::size_of::<UsedBlockHdr>());
+                    assert_eq!(TheTlsf::map_ceil_and_unmap(block_size), Some(block_size));
+                    ptrs.push(ptr);
+                }
+
+                for ptr in ptrs {
+                    unsafe { tlsf.deallocate(ptr, 8) };
+                }
+            }
+
+            #[cfg(feature = "unstable")]
+            #[quickcheck]
+            fn free_blocks_descending(sizes: Vec<u16>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) };
+                let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len.map_or(0, |x| x.get()));
+
+                // Free every other allocation to fragment the pool
+                let ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| tlsf.allocate(Layout::from_size_align(size as usize, 1).unwrap()))
+                    .collect();
+                for &ptr in ptrs.iter().step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+
+                let blocks: Vec<_> = tlsf.free_blocks_descending().collect();
+                log::trace!("blocks = {:?}", blocks);
+
+                // The size classes are visited in descending order
+                for pair in blocks.windows(2) {
+                    assert!(TheTlsf::map_floor(pair[0].0) >= TheTlsf::map_floor(pair[1].0));
+                }
+
+                // The same free blocks as found by `iter_blocks`
+                let mut blocks: Vec<_> = blocks.iter().map(|&(size, ptr)| (ptr.as_ptr(), size)).collect();
+                blocks.sort_unstable();
+                let expected: Vec<_> = if pool_len.is_some() {
+                    unsafe { tlsf.iter_blocks(pool_ptr) }
+                        .filter(|block_info| !block_info.is_occupied())
+                        .map(|block_info| (block_info.as_ptr().as_ptr() as *mut u8, block_info.size()))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                assert_eq!(blocks, expected);
+
+                for &ptr in ptrs.iter().skip(1).step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+            }
+
+            #[cfg(feature = "unstable")]
+            #[quickcheck]
+            fn drain_allocations(layouts: Vec<(u16, u8)>, num_taken: usize) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) };
+                let pool_len = if let Some(pool_len) = pool_len {
+                    pool_len.get()
+                } else {
+                    return;
+                };
+                let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len);
+
+                let mut ptrs: Vec<_> = layouts
+                    .iter()
+                    .filter_map(|&(size, align)| {
+                        let layout = Layout::from_size_align(size as usize, 1 << (align % 8)).unwrap();
+                        Some((tlsf.allocate(layout)?, layout.size()))
+                    })
+                    .collect();
+                for &(ptr, _) in ptrs.iter().step_by(3) {
+                    unsafe { tlsf.deallocate_unknown_align(ptr) };
+                }
+                let mut i = 0;
+                ptrs.retain(|_| {
+                    i += 1;
+                    (i - 1) % 3 != 0
+                });
+                ptrs.sort_unstable();
+
+                // Drain some of the allocations. Each allocation is yielded in
+                // the order of address and contained in the yielded payload
+                // area.
+                let num_taken = num_taken % (ptrs.len() + 1);
+                let payloads: Vec<_> = unsafe { tlsf.drain_allocations(pool_ptr) }
+                    .take(num_taken)
+                    .collect();
+                assert_eq!(payloads.len(), num_taken);
+                for (&(ptr, size), &payload) in ptrs.iter().zip(payloads.iter()) {
+                    let start = payload.as_ptr() as *mut u8 as usize;
+                    let end = start + nonnull_slice_len(payload);
+                    assert!(ptr.as_ptr() as usize >= start);
+                    assert!(ptr.as_ptr() as usize + size <= end);
+                }
+
+                // The rest remain allocated
+                let num_blocks = unsafe { tlsf.iter_blocks(pool_ptr) }
+                    .filter(|block_info| block_info.is_occupied())
+                    .count();
+                assert_eq!(num_blocks, ptrs.len() - num_taken);
+
+                // Drain the rest. The pool should be entirely free afterwards.
+                let num_drained = unsafe { tlsf.drain_allocations(pool_ptr) }.count();
+                assert_eq!(num_drained, ptrs.len() - num_taken);
+                let blocks: Vec<_> = unsafe { tlsf.iter_blocks(pool_ptr) }.collect();
+                log::trace!("blocks = {:?}", blocks);
+                assert!(blocks.iter().all(|block| !block.is_occupied()));
+                // Each chunk of the pool is now a single free block
+                if TheTlsf::MAX_POOL_SIZE.map_or(true, |mps| mps >= pool_len) {
+                    assert_eq!(blocks.len(), 1);
+                }
+            }
+
+            #[cfg(feature = "unstable")]
+            #[quickcheck]
+            fn block_info_at(sizes: Vec<u16>, split: u16) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+
+                // Create a memory pool and extend it
+                let split = split as usize % pool.0.len();
+                let pool_len = if let Some(pool_len) =
+                    unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, split)) }
+                {
+                    pool_len.get()
+                } else {
+                    return;
+                };
+                let pool_len = pool_len + unsafe {
+                    tlsf.append_free_block_ptr(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool_ptr.as_ptr().wrapping_add(pool_len)).unwrap(),
+                        pool.0.len() - pool_len,
+                    ))
+                };
+
+                for &size in sizes.iter() {
+                    tlsf.allocate(Layout::from_size_align(size as usize % 1024, 1).unwrap());
+                }
+
+                // `block_info_at` agrees with `iter_blocks`. `iter_blocks`
+                // excludes sentinel blocks, which `block_info_at` can examine.
+                let mut cursor = pool_ptr.as_ptr();
+                let pool_end = cursor.wrapping_add(pool_len);
+                let mut num_sentinels = 0;
+                let mut blocks = unsafe { tlsf.iter_blocks(nonnull_slice_from_raw_parts(pool_ptr, pool_len)) };
+                while cursor < pool_end {
+                    let info = unsafe { tlsf.block_info_at(NonNull::new(cursor).unwrap()) }.unwrap();
+                    log::trace!("{:?}", info);
+                    assert_eq!(info.as_ptr().as_ptr() as *mut u8, cursor);
+                    if info.is_sentinel() {
+                        assert!(info.is_occupied());
+                        assert_eq!(info.size(), POOL_OVERHEAD);
+                        num_sentinels += 1;
+                    } else {
+                        let expected = blocks.next().unwrap();
+                        assert_eq!(expected.as_ptr(), info.as_ptr());
+                        assert_eq!(expected.is_occupied(), info.is_occupied());
+                    }
+                    cursor = cursor.wrapping_add(info.size());
+                }
+                assert!(blocks.next().is_none());
+                assert_eq!(cursor, pool_end);
+
+                // The memory pool ends with a sentinel block. There's another
+                // one at the end of each chunk if the memory pool was divided.
+                assert!(num_sentinels >= 1);
+                let last = pool_end.wrapping_sub(POOL_OVERHEAD);
+                assert!(unsafe { tlsf.block_info_at(NonNull::new(last).unwrap()) }
+                    .unwrap()
+                    .is_sentinel());
+
+                // Misaligned pointers are rejected
+                let misaligned = NonNull::new(pool_ptr.as_ptr().wrapping_add(1)).unwrap();
+                assert!(unsafe { tlsf.block_info_at(misaligned) }.is_none());
+            }
+
+            #[cfg(feature = "unstable")]
+            #[quickcheck]
+            fn iter_allocations(sizes: Vec<u16>, align_log2: u8) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                let pool_len = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) };
+                let pool_len = if let Some(pool_len) = pool_len {
+                    pool_len.get()
+                } else {
+                    return;
+                };
+                let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len);
+
+                // All allocations share the alignment
+                let align = 1 << (align_log2 % 8);
+                let mut ptrs: Vec<_> = sizes
+                    .iter()
+                    .filter_map(|&size| {
+                        let layout = Layout::from_size_align(size as usize, align).unwrap();
+                        Some((tlsf.allocate(layout)?, layout.size()))
+                    })
+                    .collect();
+                for &(ptr, _) in ptrs.iter().step_by(2) {
+                    unsafe { tlsf.deallocate(ptr, align) };
+                }
+                let mut i = 0;
+                ptrs.retain(|_| {
+                    i += 1;
+                    (i - 1) % 2 != 0
+                });
+                ptrs.sort_unstable();
+
+                let infos: Vec<_> = unsafe { tlsf.iter_allocations(pool_ptr, align) }.collect();
+                log::trace!("infos = {:?}", infos);
+                assert_eq!(infos.len(), ptrs.len());
+                for (&(ptr, size), info) in ptrs.iter().zip(infos.iter()) {
+                    let start = info.block.as_ptr().as_ptr() as *mut u8 as usize;
+                    assert_eq!(start + info.overhead, ptr.as_ptr() as usize);
+                    assert_eq!(info.overhead + info.usable, info.block.size());
+                    assert!(info.overhead >= GRANULARITY / 2);
+                    assert!(info.usable >= size);
+                    assert_eq!(info.usable, unsafe { TheTlsf::size_of_allocation(ptr, align) });
+                }
+
+                for &(ptr, _) in ptrs.iter() {
+                    unsafe { tlsf.deallocate(ptr, align) };
                 }
             }
 
             #[test]
-            fn adaa() {
+            #[cfg(feature = "debug-checks")]
+            fn validate_pointer() {
                 let _ = env_logger::builder().is_test(true).try_init();
 
                 let mut tlsf: TheTlsf = Tlsf::new();
 
-                let mut pool = [MaybeUninit::uninit(); 65536];
-                tlsf.insert_free_block(&mut pool);
-
-                log::trace!("tlsf = {:?}", tlsf);
+                let mut pool = Align([MaybeUninit::uninit(); 65536]);
+                tlsf.insert_free_block(&mut pool.0);
 
-                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
-                if let Some(ptr) = ptr {
-                    unsafe { tlsf.deallocate(ptr, 1) };
+                let mut allocs = Vec::new();
+                for (i, &align) in [1, 8, 16, 64, 256].iter().cycle().take(20).enumerate() {
+                    let layout = Layout::from_size_align(i * 13, align).unwrap();
+                    if let Some(ptr) = tlsf.allocate(layout) {
+                        allocs.push((ptr, align));
+                    }
                 }
 
-                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
+                for &(ptr, align) in &allocs {
+                    assert!(unsafe { tlsf.validate_pointer(ptr, align) }, "{:?}", (ptr, align));
+                    // A wrong `align` yields a misaligned header location.
+                    // (Larger values would make it read the uninitialized
+                    // payload as `UsedBlockPad`.)
+                    if align > 1 && align / 2 < GRANULARITY {
+                        let ptr2 = NonNull::new(ptr.as_ptr().wrapping_add(align / 2)).unwrap();
+                        assert!(!unsafe { tlsf.validate_pointer(ptr2, align / 2) });
+                    }
+                }
 
-                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
+                // Deallocated pointers are rejected
+                for (i, &(ptr, align)) in allocs.iter().enumerate() {
+                    unsafe { tlsf.deallocate(ptr, align) };
+                    assert!(!unsafe { tlsf.validate_pointer(ptr, align) }, "{:?}", (ptr, align));
+                    for &(ptr, align) in &allocs[i + 1..] {
+                        assert!(unsafe { tlsf.validate_pointer(ptr, align) });
+                    }
+                }
             }
 
             #[test]
-            fn aadd() {
-                let _ = env_logger::builder().is_test(true).try_init();
-
+            #[cfg(debug_assertions)]
+            #[should_panic(expected = "nonzero power of two")]
+            fn deallocate_zero_align() {
                 let mut tlsf: TheTlsf = Tlsf::new();
 
-                let mut pool = Align([MaybeUninit::uninit(); 96]);
+                let mut pool = Align([MaybeUninit::uninit(); 512]);
                 tlsf.insert_free_block(&mut pool.0);
 
-                log::trace!("tlsf = {:?}", tlsf);
-
-                let ptr1 = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr1 = {:?}", ptr1);
-
-                let ptr2 = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr2 = {:?}", ptr2);
-
-                if let (Some(ptr1), Some(ptr2)) = (ptr1, ptr2) {
-                    unsafe { tlsf.deallocate(ptr1, 1) };
-                    unsafe { tlsf.deallocate(ptr2, 1) };
-                }
+                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()).unwrap();
+                unsafe { tlsf.deallocate(ptr, 0) };
             }
 
             #[test]
-            fn ara() {
-                let _ = env_logger::builder().is_test(true).try_init();
-
+            #[cfg(feature = "debug-checks")]
+            #[should_panic(expected = "double free")]
+            fn double_free() {
                 let mut tlsf: TheTlsf = Tlsf::new();
 
-                let mut pool = Align([MaybeUninit::uninit(); 96]);
+                let mut pool = Align([MaybeUninit::uninit(); 512]);
                 tlsf.insert_free_block(&mut pool.0);
 
-                log::trace!("tlsf = {:?}", tlsf);
+                let ptr = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()).unwrap();
+                unsafe { tlsf.deallocate(ptr, 1) };
+                unsafe { tlsf.deallocate(ptr, 1) };
+            }
 
-                let ptr = tlsf.allocate(Layout::from_size_align(17, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
+            #[test]
+            #[cfg(feature = "debug-checks")]
+            #[should_panic(expected = "double free")]
+            fn double_free_coalesced() {
+                let mut tlsf: TheTlsf = Tlsf::new();
 
-                if let Some(ptr) = ptr {
-                    unsafe { tlsf.reallocate(ptr, Layout::from_size_align(0, 1).unwrap()) };
-                    log::trace!("ptr = {:?}", ptr);
-                }
+                let mut pool = Align([MaybeUninit::uninit(); 512]);
+                tlsf.insert_free_block(&mut pool.0);
 
-                let ptr = tlsf.allocate(Layout::from_size_align(0, 1).unwrap());
-                log::trace!("ptr = {:?}", ptr);
+                // `ptr2`'s block will be merged into `ptr1`'s
+                let ptr1 = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()).unwrap();
+                let ptr2 = tlsf.allocate(Layout::from_size_align(1, 1).unwrap()).unwrap();
+                unsafe { tlsf.deallocate(ptr1, 1) };
+                unsafe { tlsf.deallocate(ptr2, 1) };
+                unsafe { tlsf.deallocate_unknown_align(ptr2) };
             }
 
             #[test]
@@ -171,7 +1644,7 @@ macro_rules! gen_test {
             #[test]
             fn insert_free_block_ptr_near_end_fail() {
                 let mut tlsf: TheTlsf = Tlsf::new();
-                unsafe {
+                let pool_len = unsafe {
                     // FIXME: Use `NonNull::<[T]>::slice_from_raw_parts` when it's stable
                     tlsf.insert_free_block_ptr(
                         NonNull::new(core::ptr::slice_from_raw_parts_mut(
@@ -179,23 +1652,73 @@ macro_rules! gen_test {
                             0,
                         ))
                         .unwrap(),
-                    );
-                }
+                    )
+                };
+                assert_eq!(pool_len, None);
 
-                // TODO: Allocation should fail
+                // No memory pool was created, so allocation should fail
+                assert_eq!(tlsf.allocate(Layout::from_size_align(0, 1).unwrap()), None);
             }
 
             #[test]
             fn insert_free_block_ptr_near_end() {
-                let _tlsf: TheTlsf = Tlsf::new();
-                // TODO: Find a way to test this case
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                // These memory blocks are too small to contain a memory pool
+                // after aligning their starting addresses, some of which wrap
+                // around to zero. `insert_free_block_ptr` must reject them
+                // without touching the memory, which is unmapped.
                 //
-                // unsafe {
-                //     tlsf.insert_free_block_ptr(core::ptr::slice_from_raw_parts_mut(
-                //         usize::MAX - GRANULARITY as _,
-                //         GRANULARITY,
-                //     ));
-                // }
+                // Larger memory blocks near the end of the address space can't
+                // be tested this way because we can't map memory there in a
+                // user-mode process on common hosts.
+                for &(start, len) in &[
+                    (usize::MAX - GRANULARITY, GRANULARITY),
+                    (usize::MAX - GRANULARITY * 2 + 2, GRANULARITY * 2 - 1),
+                    (usize::MAX - 3, 4),
+                    (usize::MAX, 1),
+                ] {
+                    let pool_len = unsafe {
+                        tlsf.insert_free_block_ptr(
+                            NonNull::new(core::ptr::slice_from_raw_parts_mut(start as *mut u8, len))
+                                .unwrap(),
+                        )
+                    };
+                    assert_eq!(pool_len, None, "{:#x}..+{:#x}", start, len);
+                }
+
+                assert_eq!(tlsf.allocate(Layout::from_size_align(0, 1).unwrap()), None);
+                assert_eq!(tlsf.allocate(Layout::from_size_align(1, GRANULARITY).unwrap()), None);
+            }
+
+            #[test]
+            fn append_free_block_ptr_near_end() {
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                if TheTlsf::MAX_POOL_SIZE.is_none() {
+                    // `append_free_block_ptr` would access the preceding
+                    // memory pool's sentinel block, which we can't map at the
+                    // end of the address space
+                    return;
+                }
+
+                // These memory blocks extend past the end of the address space.
+                // After clamping, they are too small to contain a memory pool,
+                // so `append_free_block_ptr` must ignore them without touching
+                // the memory, which is unmapped.
+                for &(start, len) in &[
+                    (0usize.wrapping_sub(GRANULARITY), GRANULARITY * 2),
+                    (0usize.wrapping_sub(GRANULARITY), usize::MAX),
+                    (0usize.wrapping_sub(GRANULARITY), GRANULARITY * 3 - 1),
+                ] {
+                    let pool_len = unsafe {
+                        tlsf.append_free_block_ptr(
+                            NonNull::new(core::ptr::slice_from_raw_parts_mut(start as *mut u8, len))
+                                .unwrap(),
+                        )
+                    };
+                    assert_eq!(pool_len, 0, "{:#x}..+{:#x}", start, len);
+                }
             }
 
             #[quickcheck]
@@ -354,6 +1877,216 @@ macro_rules! gen_test {
                 }
             }
 
+            /// Run a random sequence of allocations and deallocations in a
+            /// fresh memory pool. The allocations are made by `allocate`,
+            /// which also receives a random flag.
+            fn random_alloc_dealloc_inner(
+                bytecode: Vec<u8>,
+                mut allocate: impl FnMut(&mut TheTlsf<'_>, Layout, bool) -> Option<NonNull<u8>>,
+            ) -> Option<()> {
+                let mut sa = ShadowAllocator::new();
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+                if let Some(pool_len) = unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 65536)) } {
+                    sa.insert_free_block(std::ptr::slice_from_raw_parts(pool_ptr.as_ptr(), pool_len.get()));
+                }
+
+                let mut allocs = Vec::new();
+                let mut it = bytecode.iter().cloned();
+                loop {
+                    let op = it.next()?;
+                    if op % 2 == 0 {
+                        let len = u16::from_le_bytes([it.next()?, it.next()?]) as usize % 4096;
+                        let align = 1 << (op / 2 % 6);
+                        let flag = op >= 0x80;
+                        let layout = Layout::from_size_align(len, align).unwrap();
+                        log::trace!("alloc {:?} (flag = {:?})", layout, flag);
+
+                        let ptr = allocate(&mut tlsf, layout, flag);
+                        log::trace!(" → {:?}", ptr);
+
+                        if let Some(ptr) = ptr {
+                            allocs.push((ptr, layout));
+                            sa.allocate(layout, ptr);
+                        }
+                    } else if !allocs.is_empty() {
+                        let (ptr, layout) = allocs.swap_remove(it.next()? as usize % allocs.len());
+                        log::trace!("dealloc {:?}", (ptr, layout));
+
+                        unsafe { tlsf.deallocate(ptr, layout.align()) };
+                        sa.deallocate(layout, ptr);
+                    }
+                }
+            }
+
+            #[quickcheck]
+            fn random_from_end(bytecode: Vec<u8>) {
+                random_alloc_dealloc_inner(bytecode, |tlsf, layout, from_end| {
+                    if !from_end {
+                        return tlsf.allocate(layout);
+                    }
+
+                    let ptr = tlsf.allocate_from_end(layout)?;
+
+                    // The allocation was placed at the end of the free block,
+                    // which was followed by a used block or a sentinel block
+                    unsafe {
+                        let block = TheTlsf::used_block_hdr_for_allocation(ptr, layout.align());
+                        let next_phys_block = block.as_ref().common.next_phys_block();
+                        assert_ne!(next_phys_block.as_ref().size & SIZE_USED, 0);
+                    }
+                    Some(ptr)
+                });
+            }
+
+            #[quickcheck]
+            fn would_fill_exactly(bytecode: Vec<u8>) {
+                random_alloc_dealloc_inner(bytecode, |tlsf, layout, from_end| {
+                    let fills_exactly = tlsf.would_fill_exactly(layout);
+
+                    let ptr = if from_end {
+                        tlsf.allocate_from_end(layout)
+                    } else {
+                        tlsf.allocate(layout)
+                    };
+
+                    // If the free block was split, the remainder is a free
+                    // block following the allocation (or preceding it if
+                    // `from_end`)
+                    let remainder_is_absent = ptr.map_or(false, |ptr| unsafe {
+                        let block = TheTlsf::used_block_hdr_for_allocation(ptr, layout.align());
+                        if from_end {
+                            block.as_ref().common.prev_phys_block.map_or(true, |prev| {
+                                (prev.as_ref().size & SIZE_USED) != 0
+                            })
+                        } else {
+                            (block.as_ref().common.next_phys_block().as_ref().size & SIZE_USED) != 0
+                        }
+                    });
+                    assert_eq!(fills_exactly, remainder_is_absent);
+
+                    ptr
+                });
+            }
+
+            #[quickcheck]
+            fn preview_allocation(bytecode: Vec<u8>) {
+                random_alloc_dealloc_inner(bytecode, |tlsf, layout, _| {
+                    let preview = tlsf.preview_allocation(layout);
+
+                    let ptr = tlsf.allocate(layout);
+
+                    if let Some(ptr) = ptr {
+                        // The allocation was placed in the previewed free
+                        // block. (A zero-sized allocation may start at its
+                        // end.)
+                        let (block, size, fl, sl) = preview.unwrap();
+                        let offset = ptr.as_ptr() as usize - block.as_ptr() as usize;
+                        assert!(offset + layout.size() <= size);
+                        assert_eq!(TheTlsf::map_floor(size), Some((fl, sl)));
+                    } else {
+                        assert!(preview.is_none());
+                    }
+
+                    ptr
+                });
+            }
+
+            /// Memory pools created by separate calls to
+            /// `insert_free_block_ptr` are never merged, even if they are
+            /// physically adjacent. No memory block may straddle the
+            /// boundary between them.
+            #[quickcheck]
+            fn adjacent_pools_are_isolated(split: u16, bytecode: Vec<u8>) {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+
+                let mut pool = Align([MaybeUninit::<u8>::uninit(); 65536]);
+                let pool_ptr = pool.0.as_mut_ptr() as *mut u8;
+                let split = split as usize % pool.0.len();
+
+                let (pool0_len, pool1_len) = unsafe {
+                    let pool0_len = tlsf
+                        .insert_free_block_ptr(nonnull_slice_from_raw_parts(
+                            NonNull::new(pool_ptr).unwrap(),
+                            split,
+                        ))
+                        .map_or(0, NonZeroUsize::get);
+                    // The second memory pool starts exactly where the first
+                    // one ends
+                    let pool1_len = tlsf
+                        .insert_free_block_ptr(nonnull_slice_from_raw_parts(
+                            NonNull::new(pool_ptr.wrapping_add(pool0_len)).unwrap(),
+                            pool.0.len() - pool0_len,
+                        ))
+                        .map_or(0, NonZeroUsize::get);
+                    (pool0_len, pool1_len)
+                };
+                let boundary = pool_ptr as usize + pool0_len;
+                log::trace!("pools = {:?}, boundary = {:#x}", (pool0_len, pool1_len), boundary);
+
+                let check_isolated = |tlsf: &TheTlsf, ptr: NonNull<u8>, align: usize| unsafe {
+                    let block = TheTlsf::used_block_hdr_for_allocation(ptr, align);
+                    let start = block.as_ptr() as usize;
+                    let end = start + (block.as_ref().common.size & SIZE_SIZE_MASK);
+                    assert!(
+                        end <= boundary || start >= boundary,
+                        "{:#x}..{:#x} straddles {:#x}: {:?}",
+                        start,
+                        end,
+                        boundary,
+                        tlsf,
+                    );
+                };
+
+                let mut allocs = Vec::new();
+                let mut it = bytecode.iter().cloned();
+                while let Some(op) = it.next() {
+                    let arg = u16::from_le_bytes([it.next().unwrap_or(0), it.next().unwrap_or(0)]);
+                    match op % 4 {
+                        0 | 1 => {
+                            let layout =
+                                Layout::from_size_align(arg as usize, 1 << (op / 4 % 6)).unwrap();
+                            let ptr = if op % 4 == 0 {
+                                tlsf.allocate(layout)
+                            } else {
+                                tlsf.allocate_from_end(layout)
+                            };
+                            if let Some(ptr) = ptr {
+                                check_isolated(&tlsf, ptr, layout.align());
+                                allocs.push((ptr, layout));
+                            }
+                        }
+                        2 if !allocs.is_empty() => {
+                            let (ptr, layout) = allocs.swap_remove(arg as usize % allocs.len());
+                            unsafe { tlsf.deallocate(ptr, layout.align()) };
+                        }
+                        3 if !allocs.is_empty() => {
+                            let i = op as usize % allocs.len();
+                            let (ptr, layout) = allocs[i];
+                            let new_layout =
+                                Layout::from_size_align(arg as usize, layout.align()).unwrap();
+                            if let Some(ptr) = unsafe { tlsf.reallocate(ptr, new_layout) } {
+                                check_isolated(&tlsf, ptr, layout.align());
+                                allocs[i] = (ptr, new_layout);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                for (ptr, layout) in allocs {
+                    unsafe { tlsf.deallocate(ptr, layout.align()) };
+                }
+
+                // The memory pools weren't coalesced into one free block
+                let layout = Layout::from_size_align(pool0_len.max(pool1_len), 1).unwrap();
+                assert!(tlsf.allocate(layout).is_none());
+            }
+
             #[test]
             fn max_pool_size() {
                 if let Some(mps) = TheTlsf::MAX_POOL_SIZE {
@@ -364,6 +2097,108 @@ macro_rules! gen_test {
                 }
             }
 
+            #[test]
+            fn insert_free_block_ptr_max_pool_size() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mps = match TheTlsf::MAX_POOL_SIZE {
+                    // Let's limit pool size
+                    Some(x) if x <= 1 << 22 => x,
+                    _ => return,
+                };
+
+                // Find the largest allocation that fits in a pool of
+                // `MAX_POOL_SIZE` bytes
+                let fits = |size: usize| {
+                    let layout = Layout::from_size_align(size, 1).unwrap();
+                    TheTlsf::pool_size_to_contain_allocation(layout)
+                        .map_or(false, |x| x <= mps)
+                };
+                let (mut lo, mut hi) = (0, mps);
+                assert!(fits(lo));
+                assert!(!fits(hi));
+                while hi - lo > 1 {
+                    let mid = lo + (hi - lo) / 2;
+                    if fits(mid) { lo = mid; } else { hi = mid; }
+                }
+                let max_layout = Layout::from_size_align(lo, 1).unwrap();
+                log::debug!("mps = {}, max_layout = {:?}", mps, max_layout);
+
+                // Two full-size pools and a minimum-size one
+                let pool_len = mps * 2 + GRANULARITY * 2;
+                let mut pool = AlignedPool::new(pool_len);
+                let pool_ptr = pool.as_mut_ptr();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+                let inserted_len = unsafe {
+                    tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool_ptr).unwrap(), pool_len))
+                };
+                assert_eq!(inserted_len.map(NonZeroUsize::get), Some(pool_len));
+                unsafe { blocks_checker::trace_blocks(pool_ptr, Some(pool_len), &tlsf) };
+
+                // Each chunk is capped by a sentinel block
+                for i in 0..3 {
+                    let sentinel = pool_ptr.wrapping_add((mps * (i + 1)).min(pool_len) - GRANULARITY)
+                        as *const BlockHdr;
+                    assert_eq!(
+                        unsafe { (*sentinel).size },
+                        GRANULARITY | SIZE_USED | SIZE_SENTINEL,
+                    );
+                }
+
+                // Blocks never span across chunks, so the largest allocation
+                // can be made only once in each full-size chunk (the last
+                // chunk is full-size only if `MAX_POOL_SIZE` is minimal)
+                let num_full_chunks = if mps == GRANULARITY * 2 { 3 } else { 2 };
+                let ptrs: Vec<_> = (0..num_full_chunks)
+                    .map(|_| tlsf.allocate(max_layout).unwrap())
+                    .collect();
+                assert_eq!(tlsf.allocate(max_layout), None);
+                assert_eq!(tlsf.allocate(Layout::from_size_align(hi, 1).unwrap()), None);
+                unsafe { blocks_checker::trace_blocks(pool_ptr, Some(pool_len), &tlsf) };
+
+                for ptr in ptrs {
+                    unsafe { tlsf.deallocate(ptr, 1) };
+                }
+                tlsf.allocate(max_layout).unwrap();
+            }
+
+            #[test]
+            fn insert_free_block_ptr_with_free_bytes() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mps = match TheTlsf::MAX_POOL_SIZE {
+                    // Let's limit pool size
+                    Some(x) if x <= 1 << 22 => x,
+                    _ => return,
+                };
+
+                // Two full-size chunks and a minimum-size one
+                let pool_len = mps * 2 + GRANULARITY * 2;
+                let mut pool = AlignedPool::new(pool_len);
+                let pool_ptr = pool.as_mut_ptr();
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+                let (inserted_len, free_bytes) = unsafe {
+                    tlsf.insert_free_block_ptr_with_free_bytes(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool_ptr).unwrap(), pool_len))
+                }.unwrap();
+                assert_eq!(inserted_len.get(), pool_len);
+                assert_eq!(free_bytes, pool_len - POOL_OVERHEAD * 3);
+
+                // Misaligned on both ends. The rounding leaves
+                // `mps * 2 + GRANULARITY` bytes, so the last chunk is too
+                // small and is discarded.
+                let mut tlsf: TheTlsf = Tlsf::new();
+                let (inserted_len, free_bytes) = unsafe {
+                    tlsf.insert_free_block_ptr_with_free_bytes(nonnull_slice_from_raw_parts(
+                        NonNull::new(pool_ptr.wrapping_add(1)).unwrap(), pool_len - 1))
+                }.unwrap();
+                assert_eq!(inserted_len.get(), mps * 2 + GRANULARITY - 1);
+                assert_eq!(free_bytes, (mps - POOL_OVERHEAD) * 2);
+            }
+
             #[quickcheck]
             fn map_ceil_and_unmap(size: usize, shift: u32) -> quickcheck::TestResult {
                 let size = size.rotate_left(shift % usize::BITS)
@@ -431,39 +2266,101 @@ macro_rules! gen_test {
                 }
 
                 let layout = Layout::from_size_align(size, align).unwrap();
-                log::debug!("layout = {:?}", layout);
 
-                let pool_size = if let Some(x) = TheTlsf::pool_size_to_contain_allocation(layout) {
+                if check_pool_size_to_contain_allocation(layout).is_none() {
+                    return quickcheck::TestResult::discard();
+                }
+
+                quickcheck::TestResult::passed()
+            }
+
+            #[test]
+            fn pool_size_to_contain_allocation_small() {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let mut large_pool = Align([MaybeUninit::uninit(); 65536]);
+
+                let aligns = (0..).map(|i| 1 << i).take_while(|&align| align <= GRANULARITY * 4);
+                for align in aligns {
+                    for size in 0..512 {
+                        let layout = Layout::from_size_align(size, align).unwrap();
+
+                        if check_pool_size_to_contain_allocation(layout).is_none() {
+                            // No memory pool can contain the allocation
+                            let mut tlsf: TheTlsf = Tlsf::new();
+                            tlsf.insert_free_block(&mut large_pool.0);
+                            assert_eq!(tlsf.allocate(layout), None, "{:?}", layout);
+                        }
+                    }
+                }
+            }
+
+            #[quickcheck]
+            fn pool_size_for_allocations(layouts: Vec<(u16, u8)>) -> quickcheck::TestResult {
+                let _ = env_logger::builder().is_test(true).try_init();
+
+                let layouts: Vec<_> = layouts
+                    .into_iter()
+                    .take(16)
+                    .map(|(size, align)| {
+                        Layout::from_size_align(size as usize, 1 << (align % 8)).unwrap()
+                    })
+                    .collect();
+                log::debug!("layouts = {:?}", layouts);
+
+                let pool_size = if let Some(x) = TheTlsf::pool_size_for_allocations(&layouts) {
                     x
                 } else {
                     return quickcheck::TestResult::discard();
                 };
+                log::debug!("pool_size_for_allocations = {:?}", pool_size);
+                assert_eq!(pool_size % super::GRANULARITY, 0);
+
+                if layouts.is_empty() {
+                    assert_eq!(pool_size, 0);
+                    return quickcheck::TestResult::passed();
+                }
+
+                // Create a well-aligned pool
+                let mut pool = AlignedPool::new(pool_size);
+
+                let mut tlsf: TheTlsf = Tlsf::new();
+                tlsf.insert_free_block(pool.as_mut_slice());
+
+                // The allocations should succeed because
+                // `pool_size_for_allocations` said so
+                for &layout in layouts.iter() {
+                    tlsf.allocate(layout)
+                        .unwrap_or_else(|| panic!("allocation unexpectedly failed: {:?}", layout));
+                }
+
+                quickcheck::TestResult::passed()
+            }
+
+            /// Check that a memory pool of the size calculated by
+            /// `pool_size_to_contain_allocation` can actually contain the
+            /// specified allocation. Returns `None` if
+            /// `pool_size_to_contain_allocation` returns `None`.
+            fn check_pool_size_to_contain_allocation(layout: Layout) -> Option<()> {
+                log::debug!("layout = {:?}", layout);
+
+                let pool_size = TheTlsf::pool_size_to_contain_allocation(layout)?;
                 log::debug!("pool_size_to_contain_allocation = {:?}", pool_size);
 
                 assert_eq!(pool_size % super::GRANULARITY, 0);
 
                 // Create a well-aligned pool
-                type Bk = Align<[u8; 64]>;
-                assert_eq!(std::mem::size_of::<Bk>(), 64);
-                assert_eq!(std::mem::align_of::<Bk>(), 64);
-                let mut pool: Vec<MaybeUninit<Bk>> = Vec::new();
-                pool.reserve((pool_size + 63) / 64);
-                let pool = unsafe {
-                    std::slice::from_raw_parts_mut(
-                        pool.as_mut_ptr() as *mut MaybeUninit<u8>,
-                        pool_size,
-                    )
-                };
+                let mut pool = AlignedPool::new(pool_size);
 
                 let mut tlsf: TheTlsf = Tlsf::new();
-                tlsf.insert_free_block(pool);
+                tlsf.insert_free_block(pool.as_mut_slice());
 
                 // The allocation should success because
                 // `pool_size_to_contain_allocation` said so
                 tlsf.allocate(layout)
-                    .expect("allocation unexpectedly failed");
+                    .unwrap_or_else(|| panic!("allocation unexpectedly failed: {:?}", layout));
 
-                quickcheck::TestResult::passed()
+                Some(())
             }
         }
     };
@@ -499,3 +2396,213 @@ gen_test!(tlsf_u64_u8_59_8, u64, u64, 59, 8);
 gen_test!(tlsf_u64_u8_60_8, u64, u64, 60, 8);
 gen_test!(tlsf_u64_u8_61_8, u64, u64, 61, 8);
 gen_test!(tlsf_u64_u8_64_8, u64, u64, 64, 8);
+gen_test!(tlsf_u8_u8_8_8_fifo, u8, u8, 8, 8, true);
+gen_test!(tlsf_u16_u16_11_16_fifo, u16, u16, 11, 16, true);
+gen_test!(tlsf_u32_u32_28_32_fifo, u32, u32, 28, 32, true);
+gen_test!(tlsf_u8_u8_8_8_best_fit, u8, u8, 8, 8, false, true);
+gen_test!(tlsf_u16_u16_11_16_best_fit, u16, u16, 11, 16, false, true);
+gen_test!(tlsf_u32_u32_28_32_best_fit, u32, u32, 28, 32, false, true);
+
+type NoCoalesceTlsf<'a> = Tlsf<'a, u16, u16, 12, 16, false, false, false>;
+
+#[quickcheck]
+fn coalesce_all(sizes: Vec<u16>) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut tlsf: NoCoalesceTlsf = Tlsf::new();
+
+    let mut pool = Align([MaybeUninit::<u8>::uninit(); 4096]);
+    let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+    let pool_len =
+        unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 4096)) };
+    let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len.map_or(0, |x| x.get()));
+
+    let ptrs: Vec<_> = sizes
+        .iter()
+        .filter_map(|&size| tlsf.allocate(Layout::from_size_align(size as usize % 512, 1).unwrap()))
+        .collect();
+    log::trace!("ptrs = {:?}", ptrs);
+
+    // Deallocate every other allocation first so that both
+    // neighbors of the rest are free
+    for &ptr in ptrs.iter().step_by(2).chain(ptrs.iter().skip(1).step_by(2)) {
+        unsafe { tlsf.deallocate(ptr, 1) };
+    }
+
+    let num_merged = unsafe { tlsf.coalesce_all(pool_ptr) };
+    log::trace!("num_merged = {:?}", num_merged);
+    assert!(num_merged <= ptrs.len());
+    if ptrs.len() >= 2 {
+        assert_ne!(num_merged, 0);
+    }
+
+    // There's nothing left to merge
+    assert_eq!(unsafe { tlsf.coalesce_all(pool_ptr) }, 0);
+
+    // The pool should be in the same state as a fresh one
+    let num_allocated =
+        std::iter::from_fn(|| tlsf.allocate(Layout::from_size_align(1, 1).unwrap())).count();
+    let mut tlsf2: NoCoalesceTlsf = Tlsf::new();
+    let mut pool2 = Align([MaybeUninit::<u8>::uninit(); 4096]);
+    let pool2_ptr = NonNull::new(pool2.0.as_mut_ptr() as *mut u8).unwrap();
+    unsafe { tlsf2.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool2_ptr, 4096)) };
+    let num_expected =
+        std::iter::from_fn(|| tlsf2.allocate(Layout::from_size_align(1, 1).unwrap())).count();
+    assert_eq!(num_allocated, num_expected);
+}
+
+/// Deallocate three adjacent memory blocks and allocate one as large as all
+/// of them. Returns whether it succeeded.
+fn reuse_freed_neighbors<const COALESCE: bool>() -> bool {
+    let mut tlsf: Tlsf<'_, u16, u16, 12, 16, false, false, COALESCE> = Tlsf::new();
+
+    let mut pool = Align([MaybeUninit::uninit(); 1024]);
+    tlsf.insert_free_block(&mut pool.0);
+
+    let layout = Layout::from_size_align(256, 1).unwrap();
+    let ptrs = [(); 3].map(|()| tlsf.allocate(layout).unwrap());
+    for ptr in ptrs {
+        unsafe { tlsf.deallocate(ptr, 1) };
+    }
+
+    tlsf.allocate(Layout::from_size_align(768, 1).unwrap())
+        .is_some()
+}
+
+#[test]
+fn coalesce_on_free() {
+    assert!(reuse_freed_neighbors::<true>());
+}
+
+#[test]
+fn no_coalesce_on_free() {
+    assert!(!reuse_freed_neighbors::<false>());
+}
+
+/// Check the reuse order of free blocks of the same size class
+fn reuse_order<const FIFO: bool>() -> Vec<usize> {
+    let mut tlsf: Tlsf<'_, u16, u16, 12, 16, FIFO> = Tlsf::new();
+
+    let mut pool = Align([MaybeUninit::uninit(); 4096]);
+    tlsf.insert_free_block(&mut pool.0);
+
+    // Allocate blocks separated by guard blocks so that they don't coalesce
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    let ptrs: Vec<_> = (0..4)
+        .map(|_| {
+            let ptr = tlsf.allocate(layout).unwrap();
+            tlsf.allocate(layout).unwrap();
+            ptr
+        })
+        .collect();
+    for &ptr in &ptrs {
+        unsafe { tlsf.deallocate(ptr, 1) };
+    }
+
+    (0..4)
+        .map(|_| {
+            let ptr = tlsf.allocate(layout).unwrap();
+            ptrs.iter().position(|&p| p == ptr).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn lifo_reuse_order() {
+    assert_eq!(reuse_order::<false>(), [3, 2, 1, 0]);
+}
+
+#[test]
+fn fifo_reuse_order() {
+    assert_eq!(reuse_order::<true>(), [0, 1, 2, 3]);
+}
+
+/// Free two blocks of different size classes and allocate one that fits the
+/// smaller one exactly, then one that fits the larger one exactly. Returns
+/// whether each of them reused the freed block.
+fn fit_choice<const BEST_FIT: bool>() -> (bool, bool) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut tlsf: Tlsf<'_, u16, u16, 12, 16, false, BEST_FIT> = Tlsf::new();
+
+    let mut pool = Align([MaybeUninit::uninit(); 65536]);
+    tlsf.insert_free_block(&mut pool.0);
+
+    // `small_layout`'s block size is not the minimum size of its size class,
+    // so `allocate` skips the size class when searching for it
+    let large_layout = Layout::from_size_align(2000, 8).unwrap();
+    let small_layout = Layout::from_size_align(1100, 8).unwrap();
+    let guard_layout = Layout::from_size_align(1, 1).unwrap();
+
+    // Allocate blocks separated by guard blocks so that they don't coalesce
+    let large = tlsf.allocate(large_layout).unwrap();
+    tlsf.allocate(guard_layout).unwrap();
+    let small = tlsf.allocate(small_layout).unwrap();
+    tlsf.allocate(guard_layout).unwrap();
+    unsafe {
+        tlsf.deallocate(large, large_layout.align());
+        tlsf.deallocate(small, small_layout.align());
+    }
+
+    let ptr1 = tlsf.allocate(small_layout).unwrap();
+    let ptr2 = tlsf.allocate(large_layout).unwrap();
+    log::trace!("{:?}", (large, small, ptr1, ptr2));
+
+    (ptr1 == small, ptr2 == large)
+}
+
+#[test]
+fn good_fit_splits_larger_block() {
+    // The larger block is split for the smaller allocation, so the larger
+    // allocation has to be made elsewhere
+    assert_eq!(fit_choice::<false>(), (false, false));
+}
+
+#[test]
+fn best_fit_reduces_fragmentation() {
+    assert_eq!(fit_choice::<true>(), (true, true));
+}
+
+#[test]
+#[cfg(feature = "unstable")]
+fn iter_blocks_corrupted() {
+    let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+
+    let mut pool = Align([MaybeUninit::<u8>::uninit(); 4096]);
+    let pool_ptr = NonNull::new(pool.0.as_mut_ptr() as *mut u8).unwrap();
+    let pool_len =
+        unsafe { tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(pool_ptr, 4096)) }
+            .unwrap()
+            .get();
+    let pool_ptr = nonnull_slice_from_raw_parts(pool_ptr, pool_len);
+
+    let layout = Layout::from_size_align(64, 1).unwrap();
+    let _ptr1 = tlsf.allocate(layout).unwrap();
+    let ptr2 = tlsf.allocate(layout).unwrap();
+    assert_eq!(unsafe { tlsf.iter_blocks(pool_ptr) }.count(), 3);
+
+    let block2 = unsafe { Tlsf::<'_, u16, u16, 12, 16>::used_block_hdr_for_allocation(ptr2, 1) };
+    let size_field = unsafe { &mut (*block2.as_ptr()).common.size };
+    let original_size = *size_field;
+
+    // The walk stops at the corrupted block instead of looping or going
+    // past the pool
+    for &size in &[
+        0,
+        SIZE_USED,
+        original_size | 4,
+        usize::MAX & !2,
+        original_size + 4096,
+    ] {
+        *size_field = size;
+        assert_eq!(
+            unsafe { tlsf.iter_blocks(pool_ptr) }.count(),
+            2,
+            "{:#x}",
+            size
+        );
+    }
+
+    *size_field = original_size;
+    assert_eq!(unsafe { tlsf.iter_blocks(pool_ptr) }.count(), 3);
+}
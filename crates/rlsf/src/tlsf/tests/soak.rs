@@ -0,0 +1,384 @@
+//! A long-running randomized test, ignored by default. Run it by:
+//!
+//! ```text
+//! cargo test -p rlsf --release -- --ignored soak
+//! ```
+//!
+//! The following environment variables control its behavior:
+//!
+//!  - `RLSF_SOAK_SECS`: The duration in seconds (default: 60)
+//!  - `RLSF_SOAK_SEED`: The random seed (default: derived from the current
+//!    time). The seed is logged at the start so that a failure can be
+//!    reproduced.
+use std::{
+    collections::BTreeSet,
+    env,
+    ops::Range,
+    time::{Duration, Instant, SystemTime},
+    vec,
+};
+
+use super::*;
+
+/// The size of the memory region from which memory pools are carved out
+const ARENA_LEN: usize = 1 << 20;
+
+/// The maximum number of live allocations
+const MAX_LIVE_ALLOCS: usize = 4096;
+
+/// The number of operations between integrity checks. Each check takes time
+/// linear in the number of blocks, so checking after every operation would
+/// make the test quadratic.
+const CHECK_INTERVAL: u64 = 1000;
+
+/// xorshift64*
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Generate a number in range `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Generate a number in range `0..=max`, biased toward small numbers.
+    fn log_uniform(&mut self, max: usize) -> usize {
+        let num_bits = self.below((usize::BITS - max.leading_zeros()) as usize + 1) as u32;
+        let x = (self.next() as usize)
+            .checked_shr(usize::BITS - num_bits)
+            .unwrap_or(0);
+        x.min(max)
+    }
+}
+
+#[derive(Debug)]
+struct Alloc {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    tag: u8,
+}
+
+impl Alloc {
+    /// Mark the first and last bytes of the allocation with `self.tag`.
+    unsafe fn write_tag(&self) {
+        if self.layout.size() > 0 {
+            *self.ptr.as_ptr() = self.tag;
+            *self.ptr.as_ptr().add(self.layout.size() - 1) = self.tag;
+        }
+    }
+
+    /// Check the marks made by `write_tag`.
+    unsafe fn check_tag(&self, check_last: bool) {
+        if self.layout.size() > 0 {
+            assert_eq!(*self.ptr.as_ptr(), self.tag, "{:?}", self);
+            if check_last {
+                let last = *self.ptr.as_ptr().add(self.layout.size() - 1);
+                assert_eq!(last, self.tag, "{:?}", self);
+            }
+        }
+    }
+}
+
+/// Check the consistency of the physical block chain, the free lists, and
+/// the bitmaps of `tlsf`, whose only memory pool is `pool`.
+///
+/// Returns the number of used blocks, excluding sentinel blocks.
+fn check_integrity<
+    FLBitmap: BinInteger,
+    SLBitmap: BinInteger,
+    const FLLEN: usize,
+    const SLLEN: usize,
+    const FIFO: bool,
+>(
+    tlsf: &Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO>,
+    pool: Range<usize>,
+) -> usize {
+    let mut free_blocks = BTreeSet::new();
+    let mut num_used_blocks = 0;
+
+    // Walk the physical blocks
+    {
+        let end = pool.end;
+        let mut cursor = pool.start;
+        let mut prev: Option<NonNull<BlockHdr>> = None;
+        let mut prev_is_free = false;
+        while cursor < end {
+            let block = NonNull::new(cursor as *mut BlockHdr).unwrap();
+            let hdr = unsafe { block.as_ref() };
+            let size = hdr.size & SIZE_SIZE_MASK;
+            assert_ne!(size, 0, "zero-sized block at {:#x}", cursor);
+            assert!(
+                cursor + size <= end,
+                "block at {:#x} overruns its pool",
+                cursor
+            );
+            assert_eq!(
+                hdr.prev_phys_block, prev,
+                "broken `prev_phys_block` at {:#x}",
+                cursor
+            );
+
+            let is_free = (hdr.size & SIZE_USED) == 0;
+            if is_free {
+                assert!(!prev_is_free, "adjacent free blocks at {:#x}", cursor);
+                free_blocks.insert(cursor);
+            }
+
+            if (hdr.size & SIZE_SENTINEL) != 0 {
+                assert!(!is_free, "free sentinel block at {:#x}", cursor);
+                // The next block (if any) starts a new chunk
+                prev = None;
+            } else {
+                num_used_blocks += !is_free as usize;
+                prev = Some(block);
+            }
+            prev_is_free = is_free;
+            cursor += size;
+        }
+        assert_eq!(cursor, end);
+    }
+
+    // Walk the free lists
+    let mut num_listed_blocks = 0;
+    for fl in 0..FLLEN {
+        let sl_bitmap = &tlsf.sl_bitmap[fl];
+        assert_eq!(
+            tlsf.fl_bitmap.get_bit(fl as u32),
+            *sl_bitmap != SLBitmap::ZERO
+        );
+
+        for sl in 0..SLLEN {
+            let first_free = tlsf.first_free[fl][sl];
+            assert_eq!(
+                sl_bitmap.get_bit(sl as u32),
+                first_free.is_some(),
+                "({}, {})",
+                fl,
+                sl
+            );
+
+            let mut prev_free = None;
+            let mut next_free = first_free;
+            while let Some(block) = next_free {
+                let hdr = unsafe { block.as_ref() };
+                assert!(
+                    free_blocks.contains(&(block.as_ptr() as usize)),
+                    "{:?} in free list ({}, {}) is not a free block",
+                    block,
+                    fl,
+                    sl
+                );
+                // The head's `prev_free` is checked after the walk if `FIFO`
+                if !FIFO || prev_free.is_some() {
+                    assert_eq!(hdr.prev_free, prev_free);
+                }
+                assert_eq!(
+                    Tlsf::<FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO>::map_floor(hdr.common.size),
+                    Some((fl, sl)),
+                    "{:?} is in a wrong free list",
+                    block
+                );
+                num_listed_blocks += 1;
+                prev_free = Some(block);
+                next_free = hdr.next_free;
+            }
+
+            // In a FIFO free list, the head's `prev_free` points to the tail
+            if let (true, Some(head)) = (FIFO, first_free) {
+                assert_eq!(unsafe { head.as_ref() }.prev_free, prev_free);
+            }
+        }
+    }
+    assert_eq!(
+        num_listed_blocks,
+        free_blocks.len(),
+        "some free blocks are not listed"
+    );
+
+    #[cfg(feature = "stats")]
+    assert_eq!(tlsf.pool_capacity, pool.len());
+
+    num_used_blocks
+}
+
+/// Run randomized operations on instances of `Tlsf` until `deadline`.
+fn run<
+    FLBitmap: BinInteger,
+    SLBitmap: BinInteger,
+    const FLLEN: usize,
+    const SLLEN: usize,
+    const FIFO: bool,
+>(
+    rng: &mut Rng,
+    deadline: Instant,
+) {
+    let mut arena = vec![MaybeUninit::<u8>::uninit(); ARENA_LEN];
+    let arena_ptr = arena.as_mut_ptr() as *mut u8;
+    let mut num_ops = 0u64;
+    let mut num_epochs = 0u64;
+
+    while Instant::now() < deadline {
+        // Start a new epoch with a fresh `Tlsf` and a memory pool of a random
+        // size
+        num_epochs += 1;
+        let mut sa = ShadowAllocator::new();
+        let mut tlsf: Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO> = Tlsf::new();
+
+        let pool_ptr = arena_ptr.wrapping_add(rng.below(GRANULARITY * 2));
+        let arena_end = arena_ptr as usize + ARENA_LEN;
+        let initial_len = rng.log_uniform(arena_end - pool_ptr as usize);
+        let pool_len = unsafe {
+            tlsf.insert_free_block_ptr(nonnull_slice_from_raw_parts(
+                NonNull::new(pool_ptr).unwrap(),
+                initial_len,
+            ))
+        }
+        .map_or(0, |len| len.get());
+
+        // `insert_free_block_ptr` rounds up the starting address. The
+        // returned length includes the skipped bytes.
+        let mut pool = if pool_len > 0 {
+            let start = (pool_ptr as usize + GRANULARITY - 1) & !(GRANULARITY - 1);
+            start..pool_ptr as usize + pool_len
+        } else {
+            0..0
+        };
+        sa.insert_free_block(std::ptr::slice_from_raw_parts(
+            pool.start as *const u8,
+            pool.len(),
+        ));
+
+        let mut allocs: Vec<Alloc> = Vec::new();
+        let epoch_len = 1000 + rng.log_uniform(1 << 20) as u64;
+
+        for i in 0..epoch_len {
+            num_ops += 1;
+            match rng.below(16) {
+                0..=6 if allocs.len() < MAX_LIVE_ALLOCS => {
+                    let size = rng.log_uniform(pool.len() / 4);
+                    let align = 1 << rng.below(8);
+                    let layout = Layout::from_size_align(size, align).unwrap();
+                    let ptr = match rng.below(4) {
+                        0 => tlsf.allocate_probing::<4>(layout),
+                        1 => tlsf.allocate_class_rounded(layout),
+                        _ => tlsf.allocate(layout),
+                    };
+                    if let Some(ptr) = ptr {
+                        assert_eq!(ptr.as_ptr() as usize % align, 0);
+                        sa.allocate(layout, ptr);
+                        let alloc = Alloc {
+                            ptr,
+                            layout,
+                            tag: rng.next() as u8,
+                        };
+                        unsafe { alloc.write_tag() };
+                        allocs.push(alloc);
+                    }
+                }
+                7..=13 if !allocs.is_empty() => {
+                    let alloc = allocs.swap_remove(rng.below(allocs.len()));
+                    unsafe { alloc.check_tag(true) };
+                    sa.deallocate(alloc.layout, alloc.ptr);
+                    if rng.below(2) == 0 {
+                        unsafe { tlsf.deallocate(alloc.ptr, alloc.layout.align()) };
+                    } else {
+                        unsafe { tlsf.deallocate_unknown_align(alloc.ptr) };
+                    }
+                }
+                14 if !allocs.is_empty() => {
+                    let alloc_i = rng.below(allocs.len());
+                    let alloc = &mut allocs[alloc_i];
+                    unsafe { alloc.check_tag(true) };
+                    let size = rng.log_uniform(pool.len() / 4);
+                    let new_layout = Layout::from_size_align(size, alloc.layout.align()).unwrap();
+                    if let Some(ptr) = unsafe { tlsf.reallocate(alloc.ptr, new_layout) } {
+                        sa.deallocate(alloc.layout, alloc.ptr);
+                        sa.allocate(new_layout, ptr);
+                        let old_size = alloc.layout.size();
+                        alloc.ptr = ptr;
+                        alloc.layout = new_layout;
+                        // Only the first byte is known to be preserved
+                        unsafe {
+                            if old_size > 0 {
+                                alloc.check_tag(false);
+                            }
+                            alloc.write_tag();
+                        }
+                    }
+                }
+                15 if !pool.is_empty() && pool.end < arena_end => {
+                    // Grow the memory pool
+                    let len = rng.log_uniform(arena_end - pool.end);
+                    let appended_ptr = pool.end as *mut u8;
+                    let appended_len = unsafe {
+                        tlsf.append_free_block_ptr(nonnull_slice_from_raw_parts(
+                            NonNull::new(appended_ptr).unwrap(),
+                            len,
+                        ))
+                    };
+                    sa.insert_free_block(std::ptr::slice_from_raw_parts(
+                        appended_ptr,
+                        appended_len,
+                    ));
+                    pool.end += appended_len;
+                }
+                _ => {}
+            }
+
+            if i % CHECK_INTERVAL == 0 {
+                assert_eq!(check_integrity(&tlsf, pool.clone()), allocs.len());
+                if Instant::now() >= deadline {
+                    break;
+                }
+            }
+        }
+        // Release everything. No used blocks should remain.
+        for alloc in allocs.drain(..) {
+            unsafe { alloc.check_tag(true) };
+            sa.deallocate(alloc.layout, alloc.ptr);
+            unsafe { tlsf.deallocate(alloc.ptr, alloc.layout.align()) };
+        }
+        assert_eq!(check_integrity(&tlsf, pool.clone()), 0);
+    }
+
+    log::info!(
+        "{}: {} epochs, {} operations",
+        core::any::type_name::<Tlsf<'_, FLBitmap, SLBitmap, FLLEN, SLLEN, FIFO>>(),
+        num_epochs,
+        num_ops
+    );
+}
+
+#[test]
+#[ignore]
+fn soak() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let secs: u64 = env::var("RLSF_SOAK_SECS").map_or(60, |s| s.parse().unwrap());
+    let seed: u64 = env::var("RLSF_SOAK_SEED").map_or_else(
+        |_| {
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64
+        },
+        |s| s.parse().unwrap(),
+    );
+    log::info!("RLSF_SOAK_SEED={}", seed);
+    // xorshift's state must be non-zero
+    let mut rng = Rng(seed | 1);
+
+    // Split the duration among configurations with and without a maximum
+    // pool size and with a FIFO free list
+    let start = Instant::now();
+    let third = Duration::from_secs(secs) / 3;
+    run::<u16, u16, 12, 16, false>(&mut rng, start + third);
+    run::<u32, u32, 28, 32, false>(&mut rng, start + third * 2);
+    run::<u16, u16, 12, 16, true>(&mut rng, start + third * 3);
+}
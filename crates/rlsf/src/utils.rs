@@ -16,6 +16,28 @@ pub fn nonnull_slice_len<T>(ptr: NonNull<[T]>) -> usize {
     unsafe { (*(ptr.as_ptr() as *const [MaybeUninit<T>])).len() }
 }
 
+/// [`core::hint::unreachable_unchecked`], or a panic if the
+/// `safe-unreachable` feature is enabled.
+///
+/// # Safety
+///
+/// Reaching this function is undefined behavior unless `safe-unreachable` is
+/// enabled.
+#[inline(always)]
+pub unsafe fn unreachable_unchecked() -> ! {
+    #[cfg(feature = "safe-unreachable")]
+    invariant_violated();
+    #[cfg(not(feature = "safe-unreachable"))]
+    core::hint::unreachable_unchecked()
+}
+
+#[cfg(feature = "safe-unreachable")]
+#[cold]
+#[inline(never)]
+fn invariant_violated() -> ! {
+    panic!("internal invariant violated; the heap is probably corrupted")
+}
+
 // Polyfill for <https://github.com/rust-lang/rust/issues/74265>
 #[inline]
 pub fn nonnull_slice_start<T>(ptr: NonNull<[T]>) -> NonNull<T> {
@@ -26,3 +48,80 @@ pub fn nonnull_slice_start<T>(ptr: NonNull<[T]>) -> NonNull<T> {
 pub fn nonnull_slice_end<T>(ptr: NonNull<[T]>) -> *mut T {
     (ptr.as_ptr() as *mut T).wrapping_add(nonnull_slice_len(ptr))
 }
+
+/// Populate the page tables for the memory pages overlapping with `ptr` as if
+/// they were written to, without modifying their contents. Failures (e.g.,
+/// `MADV_POPULATE_WRITE` being unsupported by Linux < 5.14) are ignored.
+///
+/// # Safety
+///
+/// `ptr` must be in a writable private anonymous mapping.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn madvise_populate_write(ptr: NonNull<[u8]>) {
+    // Not provided by older versions of `libc`
+    const MADV_POPULATE_WRITE: libc::c_int = 23;
+
+    let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+    if !page_size.is_power_of_two() {
+        return;
+    }
+
+    // `madvise` requires a page-aligned starting address
+    let start = ptr.as_ptr() as *mut u8 as usize;
+    let aligned_start = start & !(page_size - 1);
+    let len = nonnull_slice_end(ptr) as usize - aligned_start;
+    libc::madvise(aligned_start as *mut libc::c_void, len, MADV_POPULATE_WRITE);
+}
+
+/// Release the physical memory backing the memory pages completely
+/// contained in `ptr`. The pages' contents become undefined. Errors are
+/// ignored.
+///
+/// This uses `MADV_FREE` (Linux 4.5 and later), falling back to
+/// `MADV_DONTNEED` if it's unsupported.
+///
+/// # Safety
+///
+/// `ptr` must be in a writable private anonymous mapping, and its contents
+/// must not be needed anymore.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub unsafe fn madvise_free(ptr: NonNull<[u8]>) {
+    // Not provided by older versions of `libc`
+    const MADV_FREE: libc::c_int = 8;
+
+    let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+    if !page_size.is_power_of_two() {
+        return;
+    }
+
+    // Only discard the pages that `ptr` covers entirely
+    let start = (ptr.as_ptr() as *mut u8 as usize).wrapping_add(page_size - 1) & !(page_size - 1);
+    let end = nonnull_slice_end(ptr) as usize & !(page_size - 1);
+    if end <= start {
+        return;
+    }
+
+    let (addr, len) = (start as *mut libc::c_void, end - start);
+    if libc::madvise(addr, len, MADV_FREE) != 0 {
+        libc::madvise(addr, len, libc::MADV_DONTNEED);
+    }
+}
+
+/// Get the soft limit on the process's virtual address space
+/// (`RLIMIT_AS`). Returns `None` if there's no limit or it can't be queried.
+#[cfg(unix)]
+pub fn address_space_soft_limit() -> Option<usize> {
+    let mut rlim = core::mem::MaybeUninit::<libc::rlimit>::uninit();
+    // Safety: `rlim` is valid for writes
+    if unsafe { libc::getrlimit(libc::RLIMIT_AS, rlim.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // Safety: `getrlimit` succeeded and initialized `rlim`
+    let limit = unsafe { rlim.assume_init() }.rlim_cur;
+
+    if limit == libc::RLIM_INFINITY {
+        None
+    } else {
+        Some(usize::try_from(limit).unwrap_or(usize::MAX))
+    }
+}
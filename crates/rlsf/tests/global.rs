@@ -52,3 +52,73 @@ fn test_larger_than_word_alignment() {
         }
     }
 }
+
+#[test]
+fn test_high_alignment_interleaved() {
+    use std::alloc::{alloc, dealloc, realloc, Layout};
+
+    fn fill(ptr: *mut u8, len: usize, seed: usize) {
+        for i in 0..len {
+            unsafe { *ptr.add(i) = (i ^ seed) as u8 };
+        }
+    }
+
+    fn check(ptr: *mut u8, len: usize, seed: usize) {
+        for i in 0..len {
+            assert_eq!(unsafe { *ptr.add(i) }, (i ^ seed) as u8, "{:p}[{}]", ptr, i);
+        }
+    }
+
+    // A simple LCG to make the sizes and the deallocation order irregular
+    let mut state = 0x1234_5678u32;
+    let mut next = move || {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        (state >> 16) as usize
+    };
+
+    let mut allocs: Vec<(*mut u8, Layout, usize)> = Vec::new();
+    for round in 0..20 {
+        for _ in 0..50 {
+            let align = [128, 256, 512][next() % 3];
+            let layout = Layout::from_size_align(1 + next() % 1024, align).unwrap();
+            let ptr = unsafe { alloc(layout) };
+            assert!(!ptr.is_null());
+            assert_eq!(
+                ptr as usize % align,
+                0,
+                "{:p} should be aligned to {}",
+                ptr,
+                align
+            );
+
+            let seed = next();
+            fill(ptr, layout.size(), seed);
+            allocs.push((ptr, layout, seed));
+        }
+
+        // Reallocate some of the allocations. The alignment is preserved.
+        for _ in 0..10 {
+            let i = next() % allocs.len();
+            let (ptr, layout, seed) = allocs[i];
+            let new_size = 1 + next() % 2048;
+            let new_ptr = unsafe { realloc(ptr, layout, new_size) };
+            assert!(!new_ptr.is_null());
+            assert_eq!(new_ptr as usize % layout.align(), 0);
+            check(new_ptr, layout.size().min(new_size), seed);
+
+            let new_layout = Layout::from_size_align(new_size, layout.align()).unwrap();
+            fill(new_ptr, new_size, seed);
+            allocs[i] = (new_ptr, new_layout, seed);
+        }
+
+        // Deallocate some of the allocations in an arbitrary order, leaving
+        // the rest for later rounds
+        let num_deallocs = if round == 19 { allocs.len() } else { 40 };
+        for _ in 0..num_deallocs {
+            let (ptr, layout, seed) = allocs.swap_remove(next() % allocs.len());
+            check(ptr, layout.size(), seed);
+            unsafe { dealloc(ptr, layout) };
+        }
+    }
+    assert!(allocs.is_empty());
+}
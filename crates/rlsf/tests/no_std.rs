@@ -0,0 +1,147 @@
+//! Exercises the typical bare-metal usage pattern, `FlexTlsf` with a custom
+//! `FlexSource` carving memory pools out of a `static` memory region.
+//!
+//! This crate is `#![no_std]`, so the code below can only use `core`, just
+//! like an embedded application. `std` is linked only for the test harness.
+#![no_std]
+
+extern crate std;
+
+use core::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use rlsf::{FlexSource, FlexTlsf, GRANULARITY};
+
+const ARENA_LEN: usize = 32768;
+
+/// A memory region that would be reserved by the linker script on an
+/// embedded target
+#[repr(align(64))]
+struct Arena(UnsafeCell<[MaybeUninit<u8>; ARENA_LEN]>);
+
+// Safety: `ARENA` is only accessed through the `ArenaSource` that took it
+unsafe impl Sync for Arena {}
+
+static ARENA: Arena = Arena(UnsafeCell::new([MaybeUninit::uninit(); ARENA_LEN]));
+static ARENA_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// A `FlexSource` that hands out `ARENA` from the bottom up
+struct ArenaSource {
+    arena: NonNull<u8>,
+    /// The number of bytes handed out
+    used: usize,
+}
+
+impl ArenaSource {
+    /// Take the ownership of `ARENA`. Returns `None` if it's already taken.
+    fn take() -> Option<Self> {
+        if ARENA_TAKEN.swap(true, Ordering::Acquire) {
+            return None;
+        }
+
+        Some(Self {
+            arena: NonNull::new(ARENA.0.get() as *mut u8)?,
+            used: 0,
+        })
+    }
+}
+
+// Safety: The returned memory blocks are parts of `ARENA` that don't overlap
+//         with each other. `min_align` and the other query methods are
+//         truthful.
+unsafe impl FlexSource for ArenaSource {
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        let start = (self.used + GRANULARITY - 1) & !(GRANULARITY - 1);
+        let end = start
+            .checked_add(min_size)
+            .filter(|&end| end <= ARENA_LEN)?;
+        self.used = end;
+
+        NonNull::new(core::ptr::slice_from_raw_parts_mut(
+            self.arena.as_ptr().add(start),
+            min_size,
+        ))
+    }
+
+    unsafe fn realloc_inplace_grow(
+        &mut self,
+        ptr: NonNull<[u8]>,
+        min_new_len: usize,
+    ) -> Option<usize> {
+        // Only the last memory block can grow
+        let start = ptr.as_ptr() as *mut u8 as usize - self.arena.as_ptr() as usize;
+        if start + (*ptr.as_ptr()).len() != self.used {
+            return None;
+        }
+
+        self.used = start
+            .checked_add(min_new_len)
+            .filter(|&end| end <= ARENA_LEN)?;
+        Some(min_new_len)
+    }
+
+    fn supports_realloc_inplace_grow(&self) -> bool {
+        true
+    }
+
+    fn is_contiguous_growable(&self) -> bool {
+        true
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.used == ARENA_LEN
+    }
+
+    fn max_total_bytes(&self) -> Option<usize> {
+        Some(ARENA_LEN)
+    }
+
+    fn min_align(&self) -> usize {
+        GRANULARITY
+    }
+}
+
+#[test]
+fn flex_tlsf_over_static_arena() {
+    let mut tlsf: FlexTlsf<ArenaSource, u16, u16, 12, 16> =
+        FlexTlsf::new(ArenaSource::take().unwrap());
+
+    // There's no `Vec`, so keep track of allocations in a fixed-size array
+    let mut allocs: [Option<(NonNull<u8>, Layout)>; 32] = [None; 32];
+
+    for (i, alloc) in allocs.iter_mut().enumerate() {
+        let layout = Layout::from_size_align(16 + i * 8, 1 << (i % 7)).unwrap();
+        let ptr = tlsf.allocate(layout).unwrap();
+        assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+        unsafe { ptr.as_ptr().write_bytes(i as u8, layout.size()) };
+        *alloc = Some((ptr, layout));
+    }
+    assert!(tlsf.resident_bytes() <= ARENA_LEN);
+
+    // Grow every other allocation
+    for (i, alloc) in allocs.iter_mut().enumerate().step_by(2) {
+        let (ptr, layout) = alloc.unwrap();
+        let new_layout = Layout::from_size_align(layout.size() * 2, layout.align()).unwrap();
+        let new_ptr = unsafe { tlsf.reallocate(ptr, new_layout) }.unwrap();
+        unsafe { new_ptr.as_ptr().write_bytes(i as u8, new_layout.size()) };
+        *alloc = Some((new_ptr, new_layout));
+    }
+
+    // Check the contents and deallocate everything
+    for (i, alloc) in allocs.iter_mut().enumerate() {
+        let (ptr, layout) = alloc.take().unwrap();
+        let payload = unsafe { core::slice::from_raw_parts(ptr.as_ptr(), layout.size()) };
+        assert!(payload.iter().all(|&x| x == i as u8));
+        unsafe { tlsf.deallocate(ptr, layout.align()) };
+    }
+
+    // The freed memory can be reused for a large allocation
+    let layout = Layout::from_size_align(ARENA_LEN / 4, 8).unwrap();
+    let ptr = tlsf.allocate(layout).unwrap();
+    unsafe { tlsf.deallocate(ptr, layout.align()) };
+    assert!(tlsf.resident_bytes() <= ARENA_LEN);
+}
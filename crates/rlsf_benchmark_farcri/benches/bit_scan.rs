@@ -0,0 +1,44 @@
+//! Benchmark for the bit scan operations used to find free block lists
+//!
+//! On targets without a count-leading-zeros instruction (e.g., ARMv6-M),
+//! `rlsf::int::BinInteger` uses table-based implementations instead of the
+//! built-in methods, which compile to calls to compiler-rt helpers there.
+//! This benchmark compares the two.
+#![no_std]
+// TODO: Get rid of this conditional attribute; it's FarCri.rs's
+//       implementation detail
+#![cfg_attr(target_os = "none", no_main)]
+
+use farcri::{criterion_group, criterion_main, Criterion};
+use rlsf::int::BinInteger;
+
+/// Read through a volatile pointer so that the operations are not
+/// constant-folded
+static INPUT: u32 = 0x0012_3400;
+
+fn input() -> u32 {
+    unsafe { core::ptr::read_volatile(&INPUT) }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    c.bench_function("noop", |b| b.iter(noop));
+
+    let mut group = c.benchmark_group("bit_scan");
+
+    group.bench_function("BinInteger::trailing_zeros", |b| {
+        b.iter(|| BinInteger::trailing_zeros(&input()))
+    });
+    group.bench_function("u32::trailing_zeros", |b| {
+        b.iter(|| input().trailing_zeros())
+    });
+    group.bench_function("BinInteger::leading_zeros", |b| {
+        b.iter(|| BinInteger::leading_zeros(&input()))
+    });
+    group.bench_function("u32::leading_zeros", |b| b.iter(|| input().leading_zeros()));
+}
+
+#[inline(never)]
+fn noop() {}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,135 @@
+//! Benchmark for `FlexTlsf`'s pool growth
+//!
+//! Unlike `Tlsf`'s methods, `FlexTlsf::allocate` doesn't complete in constant
+//! time when it has to acquire memory from `FlexSource`. This benchmark
+//! measures such allocations separately from the ones served by existing
+//! memory pools.
+#![no_std]
+#![feature(const_maybe_uninit_assume_init)]
+#![feature(slice_ptr_len)]
+// TODO: Get rid of this conditional attribute; it's FarCri.rs's
+//       implementation detail
+#![cfg_attr(target_os = "none", no_main)]
+
+use core::{alloc::Layout, mem::MaybeUninit, ptr::NonNull};
+use farcri::{criterion_group, criterion_main, Criterion};
+use rlsf::{FlexSource, FlexTlsf};
+
+static mut ARENA: [MaybeUninit<u8>; 1024 * 16] = unsafe { MaybeUninit::uninit().assume_init() };
+
+/// A `FlexSource` that hands out `ARENA` from the beginning, like `sbrk`
+struct BumpFlexSource {
+    arena: &'static mut [MaybeUninit<u8>],
+    allocated: usize,
+}
+
+impl BumpFlexSource {
+    /// Construct a `BumpFlexSource`.
+    ///
+    /// # Safety
+    ///
+    /// No other instances of `BumpFlexSource` may exist at the same time.
+    unsafe fn new() -> Self {
+        Self {
+            arena: &mut ARENA,
+            allocated: 0,
+        }
+    }
+}
+
+unsafe impl FlexSource for BumpFlexSource {
+    unsafe fn alloc(&mut self, min_size: usize) -> Option<NonNull<[u8]>> {
+        let start = self.allocated;
+        let end = start
+            .checked_add(min_size)
+            .filter(|&x| x <= self.arena.len())?;
+        self.allocated = end;
+        NonNull::new(&mut self.arena[start..end] as *mut [MaybeUninit<u8>] as *mut [u8])
+    }
+
+    unsafe fn realloc_inplace_grow(
+        &mut self,
+        ptr: NonNull<[u8]>,
+        min_new_len: usize,
+    ) -> Option<usize> {
+        // `FlexTlsf` only grows the last allocation because of
+        // `is_contiguous_growable`
+        debug_assert_eq!(
+            (ptr.as_ptr() as *mut u8).wrapping_add(ptr.as_ptr().len()),
+            self.arena.as_mut_ptr().wrapping_add(self.allocated) as *mut u8
+        );
+        self.alloc(min_new_len - ptr.as_ptr().len())
+            .map(|_| min_new_len)
+    }
+
+    fn supports_realloc_inplace_grow(&self) -> bool {
+        true
+    }
+
+    fn is_contiguous_growable(&self) -> bool {
+        true
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.allocated == self.arena.len()
+    }
+}
+
+type TheTlsf = FlexTlsf<BumpFlexSource, u16, u16, 12, 16>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    c.bench_function("noop", |b| b.iter(noop));
+
+    let mut group = c.benchmark_group("flex_tlsf");
+
+    // The first memory pool created for this allocation has no room for
+    // another one
+    let layout = Layout::from_size_align(64, 4).unwrap();
+
+    // The fast path: allocations are served by an existing memory pool
+    {
+        let mut tlsf = TheTlsf::new(unsafe { BumpFlexSource::new() });
+        let p = tlsf.allocate(layout).unwrap();
+        unsafe { tlsf.deallocate(p, layout.align()) };
+
+        group.bench_function("existing pool", |b| {
+            b.iter(|| {
+                let p = tlsf.allocate(layout).unwrap();
+                unsafe { tlsf.deallocate(p, layout.align()) };
+            })
+        });
+    }
+
+    // The following benchmarks start with a fresh `FlexTlsf` every iteration
+    // to defeat the reuse of memory pools. This measures the construction
+    // and destruction of `FlexTlsf` to be subtracted from them.
+    group.bench_function("new", |b| {
+        b.iter(|| TheTlsf::new(unsafe { BumpFlexSource::new() }))
+    });
+
+    // Create a new memory pool by `FlexSource::alloc`
+    group.bench_function("new + source alloc", |b| {
+        b.iter(|| {
+            let mut tlsf = TheTlsf::new(unsafe { BumpFlexSource::new() });
+            tlsf.allocate(layout).unwrap();
+            tlsf
+        })
+    });
+
+    // Create a new memory pool by `FlexSource::alloc` and then grow it by
+    // `FlexSource::realloc_inplace_grow`
+    group.bench_function("new + source alloc + source grow", |b| {
+        b.iter(|| {
+            let mut tlsf = TheTlsf::new(unsafe { BumpFlexSource::new() });
+            tlsf.allocate(layout).unwrap();
+            tlsf.allocate(layout).unwrap();
+            tlsf
+        })
+    });
+}
+
+#[inline(never)]
+fn noop() {}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
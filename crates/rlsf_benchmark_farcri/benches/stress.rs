@@ -6,7 +6,7 @@
 //       implementation detail
 #![cfg_attr(target_os = "none", no_main)]
 
-use core::{cell::Cell, ptr::NonNull};
+use core::{alloc::Layout, cell::Cell, ptr::NonNull};
 use farcri::{criterion_group, criterion_main, Criterion};
 use rlsf::Tlsf;
 
@@ -30,6 +30,27 @@ fn criterion_benchmark(c: &mut Criterion) {
         |tlsf, p, layout| unsafe { tlsf.deallocate(p, layout.align()) },
     );
 
+    // Only use alignments that need no padding (`align < GRANULARITY`), which
+    // is the common case. Comparing this with `rlsf` shows the cost of the
+    // alignment handling in `Tlsf::allocate`.
+    bench_one(
+        c,
+        "rlsf (align 4)",
+        unsafe { ARENA.len() },
+        |arena_len| {
+            let mut tlsf: Tlsf<'_, u16, u16, 12, 16> = Tlsf::new();
+            let arena = unsafe { &mut ARENA[..arena_len] };
+            tlsf.insert_free_block(&mut *arena);
+            tlsf
+        },
+        // ignoring `layout.align()`
+        |tlsf, layout| {
+            let layout = Layout::from_size_align(layout.size(), 4).unwrap();
+            tlsf.allocate(layout).unwrap()
+        },
+        |tlsf, p, _layout| unsafe { tlsf.deallocate(p, 4) },
+    );
+
     bench_one(
         c,
         "umm_malloc",
@@ -1,4 +1,7 @@
 //! Overrides C memory allocation functions with [`::rlsf`].
+// The exported functions follow the contracts of the C and C++ functions they
+// replace, so they are not individually documented.
+#![allow(clippy::missing_safety_doc)]
 use rlsf::CAlloc;
 use std::{
     alloc::Layout,
@@ -23,3 +23,42 @@ fn bz2() {
     decompressor.read_to_string(&mut contents).unwrap();
     assert_eq!(contents, "Hello, World!");
 }
+
+/// `malloc_usable_size` must be measured from the returned pointer, excluding
+/// the padding inserted to satisfy the requested alignment
+#[test]
+fn aligned_usable_size() {
+    use rlsf_override::{aligned_alloc, free, malloc_usable_size};
+
+    for &align in &[64, 256, 4096] {
+        // Allocate repeatedly so that some of the allocations require padding
+        let ptrs: Vec<*mut u8> = (0..16)
+            .map(|_| unsafe { aligned_alloc(align, 100) } as *mut u8)
+            .collect();
+
+        for (i, &ptr) in ptrs.iter().enumerate() {
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % align, 0);
+
+            let usable = unsafe { malloc_usable_size(ptr.cast()) };
+            assert!(usable >= 100, "{} < 100 (align = {})", usable, align);
+            assert!(
+                usable < 100 + rlsf::GRANULARITY,
+                "{} includes padding (align = {})",
+                usable,
+                align
+            );
+
+            // The whole usable range must be writable without clobbering
+            // other allocations
+            unsafe { ptr.write_bytes(i as u8 + 1, usable) };
+        }
+
+        for (i, &ptr) in ptrs.iter().enumerate() {
+            let usable = unsafe { malloc_usable_size(ptr.cast()) };
+            let bytes = unsafe { std::slice::from_raw_parts(ptr, usable) };
+            assert!(bytes.iter().all(|&b| b == i as u8 + 1));
+            unsafe { free(ptr.cast()) };
+        }
+    }
+}